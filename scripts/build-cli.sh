@@ -0,0 +1,20 @@
+#!/bin/sh
+# Build forgekey-cli and put it where `bundle.externalBin` in tauri.conf.json
+# expects it, suffixed with the target triple. Tauri sets
+# TAURI_ENV_TARGET_TRIPLE when running this as a before command.
+set -e
+
+target="${TAURI_ENV_TARGET_TRIPLE:-$(rustc -vV | sed -n 's/^host: //p')}"
+cd "$(dirname "$0")/../src-tauri"
+
+mkdir -p binaries
+if [ "$target" = "universal-apple-darwin" ]; then
+  cargo build --release -p forgekey-cli --target aarch64-apple-darwin
+  cargo build --release -p forgekey-cli --target x86_64-apple-darwin
+  lipo -create -output "binaries/forgekey-cli-$target" \
+    target/aarch64-apple-darwin/release/forgekey-cli \
+    target/x86_64-apple-darwin/release/forgekey-cli
+else
+  cargo build --release -p forgekey-cli --target "$target"
+  cp "target/$target/release/forgekey-cli" "binaries/forgekey-cli-$target"
+fi
@@ -0,0 +1,117 @@
+//! Command line companion to ForgeKey. Talks to the running app over its
+//! control socket, so scripts use the keystores unlocked in the app and every
+//! signature is approved in the tray window. Named `forgekey-cli` because a
+//! `forgekey` binary would clash with the app's `ForgeKey` on case-insensitive
+//! file systems.
+
+use std::env;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use serde_json::{json, Value};
+
+const USAGE: &str = "\
+Usage: forgekey-cli <command>
+
+Commands:
+  list                  List keystores, with the address of unlocked ones
+  address <name>        Print the address of an unlocked keystore
+  sign <name> <message> Sign a message (personal_sign), `-` reads it from stdin
+  lock [name]           Lock a keystore, or all of them
+  password-pipe <name>  Print the path of a pipe that hands out the keystore
+                        password once, for `--password-file`:
+                          cast send --account dev \\
+                            --password-file \"$(forgekey-cli password-pipe dev)\" ...
+
+ForgeKey must be running, and keystores are unlocked in the app.";
+
+fn main() -> ExitCode {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+  let (method, params) = match args.as_slice() {
+    ["list"] => ("forgekey_list", json!([])),
+    ["address", name] => ("forgekey_address", json!([name])),
+    ["sign", name, "-"] => {
+      let mut message = String::new();
+      if let Err(e) = io::stdin().read_to_string(&mut message) {
+        eprintln!("Failed to read the message: {}", e);
+        return ExitCode::FAILURE;
+      }
+      ("forgekey_sign", json!([name, message]))
+    }
+    ["sign", name, message] => ("forgekey_sign", json!([name, message])),
+    ["lock"] => ("forgekey_lock", json!([])),
+    ["lock", name] => ("forgekey_lock", json!([name])),
+    ["password-pipe", name] => ("forgekey_passwordPipe", json!([name])),
+    ["help" | "-h" | "--help"] => {
+      println!("{}", USAGE);
+      return ExitCode::SUCCESS;
+    }
+    _ => {
+      eprintln!("{}", USAGE);
+      return ExitCode::from(2);
+    }
+  };
+
+  match call(method, params) {
+    Ok(result) => {
+      print_result(method, &result);
+      ExitCode::SUCCESS
+    }
+    Err(e) => {
+      eprintln!("{}", e);
+      ExitCode::FAILURE
+    }
+  }
+}
+
+fn print_result(method: &str, result: &Value) {
+  match (method, result) {
+    ("forgekey_list", Value::Array(keystores)) => {
+      for keystore in keystores {
+        let name = keystore["name"].as_str().unwrap_or_default();
+        println!("{}\t{}", name, keystore["address"].as_str().unwrap_or("locked"));
+      }
+    }
+    ("forgekey_lock", Value::Array(names)) => {
+      for name in names {
+        println!("Locked {}", name.as_str().unwrap_or_default());
+      }
+    }
+    (_, Value::String(value)) => println!("{}", value),
+    (_, value) => println!("{}", value),
+  }
+}
+
+/// Send one JSON-RPC call over the control socket and wait for its answer,
+/// which for `sign` only comes once the user responded in the app
+#[cfg(unix)]
+fn call(method: &str, params: Value) -> Result<Value, String> {
+  use std::io::{BufRead, BufReader, Write};
+  use std::os::unix::net::UnixStream;
+  use forgekey_core::utils;
+
+  let path = utils::control_socket_path().ok_or("Could not find ForgeKey's runtime directory")?;
+  let mut stream = UnixStream::connect(&path)
+    .map_err(|e| format!("Could not reach ForgeKey at {} ({}). Is the app running?", path.display(), e))?;
+
+  let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+  writeln!(stream, "{}", request).map_err(|e| format!("Failed to send the request: {}", e))?;
+
+  let mut line = String::new();
+  BufReader::new(stream)
+    .read_line(&mut line)
+    .map_err(|e| format!("Failed to read the response: {}", e))?;
+  let mut response: Value = serde_json::from_str(&line).map_err(|_| "ForgeKey closed the connection".to_string())?;
+
+  match response.get("error") {
+    Some(error) => Err(error["message"].as_str().unwrap_or("Unknown error").to_string()),
+    None => Ok(response["result"].take()),
+  }
+}
+
+#[cfg(not(unix))]
+fn call(_method: &str, _params: Value) -> Result<Value, String> {
+  Err("forgekey-cli is only available on macOS and Linux".to_string())
+}
@@ -0,0 +1,389 @@
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+use log::debug;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio_util::sync::CancellationToken;
+
+use crate::models::{SecretBuffer, SecretString};
+use crate::pty::{self, ExpectScript, PtyConfig, PtyError, PtyResult, ReadEvent, Transcript};
+use super::{CastExecutor, CastFuture, CastOutput};
+
+/// Anvil's first default account. A well-known test key, never use it for real funds.
+pub const FIXTURE_ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+pub const FIXTURE_PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+/// Anvil's default mnemonic, whose first account is `FIXTURE_ADDRESS`
+pub const FIXTURE_MNEMONIC: &str = "test test test test test test test test test test test junk";
+/// What the recorded `cast wallet sign` and `cast mktx` print. Only the shape
+/// matters, neither verifies against the fixture key.
+pub const FIXTURE_SIGNATURE: &str = "0x5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f5f3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b3b1c";
+pub const FIXTURE_RAW_TRANSACTION: &str = "0x02f86b0180843b9aca0084773594008252089470997970c51812dc3a010c7d01b50e0d17dc79c8880de0b6b3a764000080c001a01111111111111111111111111111111111111111111111111111111111111111a02222222222222222222222222222222222222222222222222222222222222222";
+/// What the recorded `cast send` and `forge script` report as sent
+pub const FIXTURE_TRANSACTION_HASH: &str = "0x9d7e5c3a1f2b4d6e8a0c2e4f6a8b0d2f4a6c8e0b2d4f6a8c0e2a4b6d8f0a2c4e";
+/// Password the recorded keystores reject with "Mac Mismatch"
+pub const FIXTURE_WRONG_PASSWORD: &str = "wrong-password";
+/// A keystore the way `cast wallet import` writes it, without an `address`
+pub const FIXTURE_KEYSTORE: &str = r#"{"crypto":{"cipher":"aes-128-ctr","cipherparams":{"iv":"00000000000000000000000000000000"},"ciphertext":"00","kdf":"scrypt","kdfparams":{"dklen":32,"n":8192,"p":1,"r":8,"salt":"00"},"mac":"00"},"id":"00000000-0000-0000-0000-000000000000","version":3}"#;
+
+/// Recorded output of one cast invocation
+#[derive(Clone, Default)]
+pub struct FakeResponse {
+    /// Prompts cast shows, in order, each one waiting for an answer
+    prompts: Vec<String>,
+    stdout: String,
+    stderr: String,
+    exit_code: i32,
+    /// Answers cast rejects, with what it prints instead of `stdout`
+    rejections: Vec<(String, String)>,
+    /// Whether a successful run writes `FIXTURE_KEYSTORE`, see `writing_keystore`
+    writes_keystore: bool,
+}
+
+impl FakeResponse {
+    /// cast prints `stdout` and exits successfully
+    pub fn output(stdout: &str) -> Self {
+        Self {
+            stdout: stdout.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// cast prints `stderr` and exits with `exit_code`
+    pub fn failure(stderr: &str, exit_code: i32) -> Self {
+        Self {
+            stderr: stderr.to_string(),
+            exit_code,
+            ..Default::default()
+        }
+    }
+
+    /// Show `prompt` and wait for an answer before printing the output
+    pub fn prompt(mut self, prompt: &str) -> Self {
+        self.prompts.push(prompt.to_string());
+        self
+    }
+
+    /// When a prompt is answered with `answer`, print `output` and exit with 1
+    pub fn rejecting(mut self, answer: &str, output: &str) -> Self {
+        self.rejections.push((answer.to_string(), output.to_string()));
+        self
+    }
+
+    /// On success, write `FIXTURE_KEYSTORE` like `cast wallet import <name>
+    /// --keystore-dir <dir>` does, readable by the user only. Nothing is
+    /// written without `--keystore-dir`.
+    pub fn writing_keystore(mut self) -> Self {
+        self.writes_keystore = true;
+        self
+    }
+}
+
+/// A recorded invocation, for asserting what a command ran
+#[derive(Clone, Debug)]
+pub struct FakeCall {
+    pub args: Vec<String>,
+    /// Names of the script steps that were answered, in order
+    pub answered: Vec<&'static str>,
+}
+
+/// In-process stand-in for cast that replays `FakeResponse`s registered for
+/// argument prefixes. forge invocations are registered with a leading `"forge"`.
+///
+/// Enabled with the `fake-cast` feature (`pnpm tauri-dev-fake`) to run the app
+/// and every Tauri command without Foundry installed. Recorded prompts and
+/// output are fed to the PTY runner's own prompt loop through an in-memory
+/// terminal, so prompt patterns, wrong-password detection, failure patterns,
+/// streamed output and timeouts behave as they would against the real binary.
+pub struct FakeCast {
+    responses: Mutex<Vec<(Vec<String>, FakeResponse)>>,
+    calls: Mutex<Vec<FakeCall>>,
+}
+
+impl Default for FakeCast {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeCast {
+    pub fn new() -> Self {
+        Self {
+            responses: Mutex::new(Vec::new()),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Reply with `response` to any invocation whose arguments start with
+    /// `args`. Later registrations take precedence over earlier ones.
+    pub fn on(self, args: &[&str], response: FakeResponse) -> Self {
+        self.responses
+            .lock()
+            .unwrap()
+            .push((args.iter().map(|arg| arg.to_string()).collect(), response));
+        self
+    }
+
+    /// Output recorded from cast and forge 1.x for every subcommand ForgeKey runs.
+    /// Keystores accept any password except `FIXTURE_WRONG_PASSWORD`.
+    pub fn recorded() -> Self {
+        let wrong_password = "Error: Mac Mismatch\n";
+        let keystore_prompt = "Enter keystore password:";
+
+        Self::new()
+            .on(&["--version"], FakeResponse::output(
+                "cast Version: 1.2.3-stable\n\
+                 Commit SHA: a813a2cee7dd4926e7c56fd8a785b54f32e0d10f\n\
+                 Build Timestamp: 2025-06-08T15:42:50.507050000Z (1749397370)\n\
+                 Build Profile: maxperf\n",
+            ))
+            .on(&["wallet", "list"], FakeResponse::output("fixture (Local)\n"))
+            .on(&["wallet", "new"], FakeResponse::output(&format!(
+                "Successfully created new keypair.\nAddress:     {}\nPrivate key: {}\n",
+                FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY,
+            )))
+            .on(&["wallet", "new", "--json"], FakeResponse::output(&format!(
+                "[\n  {{\n    \"address\": \"{}\",\n    \"private_key\": \"{}\"\n  }}\n]\n",
+                FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY,
+            )))
+            .on(&["wallet", "vanity"], FakeResponse::output(&format!(
+                "Starting to generate vanity address...\n\
+                 Successfully found vanity address in 0.031 seconds.\n\
+                 Address: {}\nPrivate Key: {}\n",
+                FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY,
+            )))
+            .on(&["wallet", "address"], FakeResponse::output(&format!("{}\n", FIXTURE_ADDRESS))
+                .prompt(keystore_prompt)
+                .rejecting(FIXTURE_WRONG_PASSWORD, wrong_password))
+            .on(&["wallet", "address", "--interactive"], FakeResponse::output(&format!("{}\n", FIXTURE_ADDRESS))
+                .prompt("Enter private key:"))
+            .on(&["wallet", "address", "--mnemonic"], FakeResponse::output(&format!("{}\n", FIXTURE_ADDRESS)))
+            .on(&["wallet", "decrypt-keystore"], FakeResponse::output(&format!(
+                "fixture's private key is: {}\n",
+                FIXTURE_PRIVATE_KEY,
+            ))
+                .prompt(keystore_prompt)
+                .rejecting(FIXTURE_WRONG_PASSWORD, wrong_password))
+            .on(&["wallet", "import"], FakeResponse::output(&format!(
+                "`fixture` keystore was saved successfully. Address: {}\n",
+                FIXTURE_ADDRESS,
+            ))
+                .prompt("Enter private key:")
+                .prompt("Enter password:"))
+            .on(&["wallet", "import", "--mnemonic"], FakeResponse::output(&format!(
+                "`fixture` keystore was saved successfully. Address: {}\n",
+                FIXTURE_ADDRESS,
+            ))
+                .prompt("Enter password:"))
+            .on(&["wallet", "sign"], FakeResponse::output(&format!("{}\n", FIXTURE_SIGNATURE))
+                .prompt(keystore_prompt)
+                .rejecting(FIXTURE_WRONG_PASSWORD, wrong_password))
+            .on(&["mktx"], FakeResponse::output(&format!("{}\n", FIXTURE_RAW_TRANSACTION))
+                .prompt(keystore_prompt)
+                .rejecting(FIXTURE_WRONG_PASSWORD, wrong_password))
+            .on(&["send"], FakeResponse::output(&format!(
+                "\nblockHash            0x4c2d6f1b5a8e3c7d9f0a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60\n\
+                 blockNumber          19\n\
+                 from                 {}\n\
+                 status               1 (success)\n\
+                 transactionHash      {}\n",
+                FIXTURE_ADDRESS, FIXTURE_TRANSACTION_HASH,
+            ))
+                .prompt(keystore_prompt)
+                .rejecting(FIXTURE_WRONG_PASSWORD, wrong_password))
+            .on(&["forge", "script"], FakeResponse::output(&format!(
+                "[⠊] Compiling...\nNo files changed, compilation skipped\n\
+                 Script ran successfully.\n\n\
+                 ##### anvil-hardhat\n\
+                 ✅  [Success] Hash: {}\n\
+                 Contract Address: 0x5FbDB2315678afecb367f032d93F642f64180aa3\n\n\
+                 ONCHAIN EXECUTION COMPLETE & SUCCESSFUL.\n",
+                FIXTURE_TRANSACTION_HASH,
+            ))
+                .prompt(keystore_prompt)
+                .rejecting(FIXTURE_WRONG_PASSWORD, wrong_password))
+    }
+
+    /// `recorded` with the output of a pre-1.0 nightly, which reports its
+    /// version differently and has no `wallet new --json`
+    pub fn recorded_legacy() -> Self {
+        Self::recorded()
+            .on(&["--version"], FakeResponse::output(
+                "cast 0.2.0 (6f5e8c1 2024-05-01T00:17:42.044063000Z)\n",
+            ))
+            .on(&["wallet", "new", "--json"], FakeResponse::failure(
+                "error: unexpected argument '--json' found\n",
+                2,
+            ))
+    }
+
+    /// Every invocation so far, oldest first
+    pub fn calls(&self) -> Vec<FakeCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn response(&self, args: &[&str]) -> Result<FakeResponse, PtyError> {
+        self.responses
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(prefix, _)| prefix.len() <= args.len() && prefix.iter().zip(args).all(|(a, b)| a == b))
+            .map(|(_, response)| response.clone())
+            .ok_or_else(|| PtyError::SpawnFailed(format!("No recorded output for cast {}", args.join(" "))))
+    }
+
+    fn record(&self, args: &[&str], answered: Vec<&'static str>) {
+        debug!("Fake cast {}", args.join(" "));
+        self.calls.lock().unwrap().push(FakeCall {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            answered,
+        });
+    }
+}
+
+impl CastExecutor for FakeCast {
+    fn output<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        _timeout: Duration,
+        cancel: &'a CancellationToken,
+    ) -> CastFuture<'a, Result<CastOutput, PtyError>> {
+        Box::pin(async move {
+            if cancel.is_cancelled() {
+                return Err(PtyError::Cancelled);
+            }
+            let response = self.response(args)?;
+            self.record(args, Vec::new());
+
+            Ok(CastOutput {
+                stdout: SecretString::new(&response.stdout),
+                stderr: response.stderr,
+                exit_code: Some(response.exit_code),
+            })
+        })
+    }
+
+    fn run_script<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        script: &'a ExpectScript<'a>,
+        config: &'a PtyConfig,
+    ) -> CastFuture<'a, Result<PtyResult, PtyError>> {
+        Box::pin(async move { self.replay(args, script, config).await })
+    }
+
+    fn run_forge_script<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        script: &'a ExpectScript<'a>,
+        config: &'a PtyConfig,
+    ) -> CastFuture<'a, Result<PtyResult, PtyError>> {
+        Box::pin(async move {
+            let args: Vec<&str> = std::iter::once("forge").chain(args.iter().copied()).collect();
+            self.replay(&args, script, config).await
+        })
+    }
+}
+
+impl FakeCast {
+    /// Play `response` to the same prompt loop `pty::run_script` runs, so the
+    /// script is answered exactly as it would be against the real binary
+    async fn replay(&self, args: &[&str], script: &ExpectScript<'_>, config: &PtyConfig) -> Result<PtyResult, PtyError> {
+        if config.cancel.is_cancelled() {
+            return Err(PtyError::Cancelled);
+        }
+        let response = self.response(args)?;
+        let deadline = tokio::time::Instant::now() + config.timeout;
+        let (output, mut chunks) = unbounded_channel();
+        let (answers, answered) = unbounded_channel();
+
+        let mut transcript = Transcript::default();
+        let (result, exit_code) = tokio::join!(
+            async {
+                // Dropping the terminal at the end lets the played response
+                // stop waiting for answers
+                let mut terminal = FakeTerminal { line: SecretBuffer::with_capacity(256), answers };
+                pty::answer_prompts(&mut chunks, &mut terminal, script, config, deadline, &mut transcript).await
+            },
+            response.play(output, answered),
+        );
+        self.record(args, transcript.answered.clone());
+        result?;
+
+        if response.writes_keystore && exit_code == 0 {
+            write_keystore(args);
+        }
+        Ok(transcript.into_result(exit_code))
+    }
+}
+
+impl FakeResponse {
+    /// Show the prompts on `output` one at a time, waiting for each answer,
+    /// then the recorded output. Returns the exit code.
+    async fn play(&self, output: UnboundedSender<ReadEvent>, mut answers: UnboundedReceiver<SecretString>) -> i32 {
+        for prompt in &self.prompts {
+            // A run that ended early no longer listens, like cast getting SIGHUP
+            if output.send(Ok(SecretBuffer::from_bytes(prompt.as_bytes()))).is_err() {
+                return 1;
+            }
+            let Some(answer) = answers.recv().await else {
+                return 1;
+            };
+            if let Some((_, rejected)) = self.rejections.iter().find(|(rejected, _)| rejected == answer.as_str()) {
+                let _ = output.send(Ok(SecretBuffer::from_bytes(rejected.as_bytes())));
+                return 1;
+            }
+        }
+
+        for text in [&self.stdout, &self.stderr] {
+            if !text.is_empty() {
+                let _ = output.send(Ok(SecretBuffer::from_bytes(text.as_bytes())));
+            }
+        }
+        self.exit_code
+    }
+}
+
+/// The input side of the fake terminal, passing every answered line on to the
+/// played response
+struct FakeTerminal {
+    line: SecretBuffer,
+    answers: UnboundedSender<SecretString>,
+}
+
+impl Write for FakeTerminal {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        for part in bytes.split_inclusive(|b| *b == b'\n') {
+            match part.strip_suffix(b"\n") {
+                Some(rest) => {
+                    self.line.extend_from_slice(rest);
+                    let answer = SecretString::new(&String::from_utf8_lossy(self.line.as_bytes()));
+                    self.line.clear();
+                    // The response may have exited already, like a real command
+                    let _ = self.answers.send(answer);
+                }
+                None => self.line.extend_from_slice(part),
+            }
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// `FIXTURE_KEYSTORE` as `<--keystore-dir>/<name>`, the name being the
+/// argument after the subcommand
+fn write_keystore(args: &[&str]) {
+    let (Some(name), Some(dir)) = (
+        args.get(2),
+        args.iter().position(|arg| *arg == "--keystore-dir").and_then(|index| args.get(index + 1)),
+    ) else {
+        return;
+    };
+    let path = std::path::Path::new(dir).join(name);
+    if let Err(e) = crate::commands::write_new_keystore(&path, FIXTURE_KEYSTORE.as_bytes()) {
+        debug!("Fake cast could not write {}: {}", path.display(), e);
+    }
+}
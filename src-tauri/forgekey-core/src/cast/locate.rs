@@ -0,0 +1,181 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use dirs::home_dir;
+use log::{error, info, warn};
+use serde::Serialize;
+
+use crate::settings;
+use super::version::{self, CastVersion};
+
+#[cfg(windows)]
+pub(crate) const CAST_EXECUTABLE: &str = "cast.exe";
+#[cfg(not(windows))]
+pub(crate) const CAST_EXECUTABLE: &str = "cast";
+#[cfg(windows)]
+pub(crate) const FORGE_EXECUTABLE: &str = "forge.exe";
+#[cfg(not(windows))]
+pub(crate) const FORGE_EXECUTABLE: &str = "forge";
+
+/// Where the cast binary in use was found, in lookup order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CastSource {
+    /// The path the user chose, see `set_cast_path`
+    Setting,
+    /// `$FOUNDRY_DIR/bin/cast`
+    FoundryDir,
+    /// First `cast` on `PATH` (Homebrew, Nix, distro packages, ...)
+    Path,
+    /// `~/.foundry/bin/cast`, where foundryup installs it
+    Default,
+}
+
+/// The cast binary in use and how it was found
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CastLocation {
+    pub path: PathBuf,
+    pub source: CastSource,
+}
+
+/// Result of the last successful lookup, so cast isn't probed on every command
+static LOCATED: Mutex<Option<CastLocation>> = Mutex::new(None);
+
+/// Path of the cast binary to run, see `locate`
+pub fn cast_binary() -> Result<PathBuf, String> {
+    locate().map(|location| location.path)
+}
+
+/// Path of forge, preferably from the same installation as the cast in use
+/// so both come from the same Foundry version. Otherwise looked up like cast
+/// in `$FOUNDRY_DIR/bin`, `PATH` and `~/.foundry/bin`.
+pub fn forge_binary() -> Result<PathBuf, String> {
+    let next_to_cast = cast_binary()?.with_file_name(FORGE_EXECUTABLE);
+    if next_to_cast.is_file() {
+        return Ok(next_to_cast);
+    }
+
+    let (path, source) = search_paths(FORGE_EXECUTABLE)
+        .into_iter()
+        .find(|(path, _)| path.is_file())
+        .ok_or_else(|| {
+            let err_msg = "forge not found. Please ensure Foundry is installed correctly.".to_string();
+            error!("{}", err_msg);
+            err_msg
+        })?;
+
+    warn!("forge is not next to cast, using {} ({:?})", path.display(), source);
+    Ok(path)
+}
+
+/// Find a working cast binary, trying in order the path set by the user,
+/// `$FOUNDRY_DIR/bin`, `PATH` and `~/.foundry/bin`. A candidate only counts
+/// if it runs and reports a cast version.
+pub fn locate() -> Result<CastLocation, String> {
+    if let Some(location) = LOCATED.lock().unwrap().clone() {
+        return Ok(location);
+    }
+
+    let location = candidates()
+        .into_iter()
+        .find(|(path, source)| match verify(path) {
+            Ok(_) => true,
+            Err(e) => {
+                // Only worth a warning if the user pointed us there explicitly
+                if *source == CastSource::Setting {
+                    warn!("Ignoring configured cast {}: {}", path.display(), e);
+                }
+                false
+            }
+        })
+        .map(|(path, source)| CastLocation { path, source })
+        .ok_or_else(|| {
+            let err_msg = "Cast binary not found. Please ensure Foundry is installed correctly.".to_string();
+            error!("{}", err_msg);
+            err_msg
+        })?;
+
+    info!("Using cast at {} ({:?})", location.path.display(), location.source);
+    *LOCATED.lock().unwrap() = Some(location.clone());
+    Ok(location)
+}
+
+/// Use `path` as the cast binary, or go back to the automatic lookup with
+/// `None`. The path must point to a working cast. Drops the pin of a Foundry
+/// installed from a release archive, see `setup::foundry::install_from_archive`.
+pub fn set_cast_path(path: Option<PathBuf>) -> Result<CastLocation, String> {
+    if let Some(path) = &path {
+        verify(path).map_err(|e| {
+            let err_msg = format!("{} is not a usable cast binary: {}", path.display(), e);
+            error!("{}", err_msg);
+            err_msg
+        })?;
+    }
+
+    settings::update(|settings| {
+        settings.cast_path = path;
+        settings.pinned_foundry_version = None;
+    })?;
+    forget();
+    locate()
+}
+
+/// Drop the cached location and version, e.g. after cast was installed or
+/// the configured path changed.
+pub fn forget() {
+    *LOCATED.lock().unwrap() = None;
+    version::forget();
+}
+
+fn candidates() -> Vec<(PathBuf, CastSource)> {
+    let mut candidates = Vec::new();
+
+    if let Some(path) = settings::get().cast_path {
+        candidates.push((path, CastSource::Setting));
+    }
+    candidates.extend(search_paths(CAST_EXECUTABLE));
+
+    candidates
+}
+
+/// Where `executable` is looked for without a configured path:
+/// `$FOUNDRY_DIR/bin`, the first match on `PATH` and `~/.foundry/bin`
+fn search_paths(executable: &str) -> Vec<(PathBuf, CastSource)> {
+    let mut paths = Vec::new();
+
+    if let Some(dir) = env::var_os("FOUNDRY_DIR").filter(|dir| !dir.is_empty()) {
+        paths.push((PathBuf::from(dir).join("bin").join(executable), CastSource::FoundryDir));
+    }
+    if let Some(path_var) = env::var_os("PATH") {
+        if let Some(path) = env::split_paths(&path_var).map(|dir| dir.join(executable)).find(|path| path.is_file()) {
+            paths.push((path, CastSource::Path));
+        }
+    }
+    if let Some(home) = home_dir() {
+        paths.push((home.join(".foundry").join("bin").join(executable), CastSource::Default));
+    }
+
+    paths
+}
+
+/// Check that `path` runs and is cast rather than another tool with the same name
+pub(crate) fn verify(path: &Path) -> Result<CastVersion, String> {
+    if !path.is_file() {
+        return Err("file does not exist".to_string());
+    }
+
+    let output = Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("failed to run it: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("`--version` exited with {}", output.status));
+    }
+
+    CastVersion::parse(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "`--version` did not report a cast version".to_string())
+}
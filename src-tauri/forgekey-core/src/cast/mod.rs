@@ -0,0 +1,105 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::models::{Password, SecretString};
+use crate::pty::{ExpectScript, PtyConfig, PtyError, PtyResult, ANY_PROMPT};
+
+/// Recorded cast output, for the app's fake-cast mode and for tests
+#[cfg(feature = "fake-cast")]
+pub mod fake;
+pub mod locate;
+pub mod parse;
+#[cfg_attr(feature = "fake-cast", allow(dead_code))]
+mod system;
+pub mod version;
+
+pub use system::SystemCast;
+
+/// How long quick commands such as `--version`, `wallet list` and `wallet new`
+/// get to run without a terminal before cast is killed
+pub const OUTPUT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Boxed future returned by `CastExecutor`, so executors can be used as trait objects
+pub type CastFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Output of a cast command run without a terminal
+///
+/// stdout can contain private keys (e.g. `wallet new`, `wallet vanity`), so it
+/// is kept in locked memory and zeroized when dropped.
+pub struct CastOutput {
+    pub stdout: SecretString,
+    pub stderr: String,
+    /// Exit code, `None` if cast was killed by a signal
+    pub exit_code: Option<i32>,
+}
+
+impl CastOutput {
+    /// Check if the command succeeded (exit code 0)
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Runs cast on behalf of the commands in `commands/*`.
+///
+/// Commands never spawn cast themselves, they get an executor passed in. The
+/// app uses `SystemCast`, which runs the real binary; building with the
+/// `fake-cast` feature swaps in `fake::FakeCast`, which replays recorded cast
+/// output so every command can be exercised without Foundry installed.
+pub trait CastExecutor: Send + Sync {
+    /// Run cast with `args` and collect its output, e.g. `wallet list`.
+    /// cast is killed if it hasn't exited within `timeout` or when `cancel` fires.
+    fn output<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        timeout: Duration,
+        cancel: &'a CancellationToken,
+    ) -> CastFuture<'a, Result<CastOutput, PtyError>>;
+
+    /// Run cast with `args` in a PTY, answering its prompts from `script`.
+    /// See `pty::run_script`.
+    fn run_script<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        script: &'a ExpectScript<'a>,
+        config: &'a PtyConfig,
+    ) -> CastFuture<'a, Result<PtyResult, PtyError>>;
+
+    /// Run forge with `args` in a PTY, answering its prompts from `script`,
+    /// e.g. `forge script --account` asking for the keystore password.
+    fn run_forge_script<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        script: &'a ExpectScript<'a>,
+        config: &'a PtyConfig,
+    ) -> CastFuture<'a, Result<PtyResult, PtyError>>;
+
+    /// Run cast with `args` in a PTY and answer `config.password_prompt_count`
+    /// prompts with `password`, so it never shows up in the process list.
+    fn run_with_password<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        password: &'a Password,
+        config: &'a PtyConfig,
+    ) -> CastFuture<'a, Result<PtyResult, PtyError>> {
+        Box::pin(async move {
+            let script = (0..config.password_prompt_count)
+                .fold(ExpectScript::new(), |script, _| script.expect("password", ANY_PROMPT, password));
+            self.run_script(args, &script, config).await
+        })
+    }
+}
+
+#[cfg(not(feature = "fake-cast"))]
+static EXECUTOR: LazyLock<Arc<dyn CastExecutor>> = LazyLock::new(|| Arc::new(SystemCast));
+
+#[cfg(feature = "fake-cast")]
+static EXECUTOR: LazyLock<Arc<dyn CastExecutor>> = LazyLock::new(|| Arc::new(fake::FakeCast::recorded()));
+
+/// The executor the Tauri commands run cast with
+pub fn executor() -> Arc<dyn CastExecutor> {
+    EXECUTOR.clone()
+}
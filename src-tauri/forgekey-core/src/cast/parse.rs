@@ -0,0 +1,252 @@
+use std::sync::LazyLock;
+use log::error;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::models::{SecretString, WalletInfo};
+use super::version::CastVersion;
+
+// Parsers for the output of the cast subcommands ForgeKey runs. They look for
+// values by shape and case-insensitive label rather than by line number or
+// exact wording, so small wording changes between cast releases don't break
+// them, and use JSON output where the detected version has it.
+
+static ADDRESS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b0x[0-9a-fA-F]{40}\b").unwrap());
+static PRIVATE_KEY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:0x)?[0-9a-fA-F]{64}\b").unwrap());
+
+/// Arguments for `cast wallet new`, asking for JSON output where supported
+pub fn wallet_new_args(version: &CastVersion) -> &'static [&'static str] {
+    if version.has_wallet_new_json() {
+        &["wallet", "new", "--json"]
+    } else {
+        &["wallet", "new"]
+    }
+}
+
+/// Entry of the `cast wallet new --json` array. Borrows from the output so
+/// the key is never copied out of locked memory.
+#[derive(Deserialize)]
+struct NewWallet<'a> {
+    address: &'a str,
+    private_key: &'a str,
+}
+
+/// Parse `cast wallet new`, run with `wallet_new_args(version)`
+pub fn wallet_new(version: &CastVersion, output: &str) -> Result<WalletInfo, String> {
+    if !version.has_wallet_new_json() {
+        return labelled_wallet(output);
+    }
+
+    let wallets: Vec<NewWallet> = serde_json::from_str(output.trim()).map_err(|e| {
+        // The serde error only carries a position, never the input
+        let err_msg = format!("Could not parse cast wallet new output: {}", e);
+        error!("{}", err_msg);
+        err_msg
+    })?;
+    let wallet = wallets.first().ok_or_else(|| parse_error("wallet"))?;
+
+    Ok(WalletInfo {
+        address: wallet.address.to_string(),
+        private_key: SecretString::new(wallet.private_key),
+    })
+}
+
+/// Parse `cast wallet vanity`, which has no JSON output
+pub fn vanity(output: &str) -> Result<WalletInfo, String> {
+    labelled_wallet(output)
+}
+
+/// Parse the key printed by `cast wallet decrypt-keystore`
+/// (`"<name>'s private key is: 0x..."`)
+pub fn decrypted_key(output: &str) -> Result<SecretString, String> {
+    static KEY: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?i)private key is:\s*((?:0x)?[0-9a-f]{64})\b").unwrap());
+
+    KEY.captures(output)
+        .map(|captures| SecretString::new(&captures[1]))
+        .ok_or_else(|| parse_error("private key"))
+}
+
+/// Parse the address printed by `cast wallet address`, or the one `cast wallet
+/// import` reports for the saved keystore
+pub fn address(output: &str) -> Result<String, String> {
+    ADDRESS
+        .find_iter(output)
+        .last()
+        .map(|address| address.as_str().to_string())
+        .ok_or_else(|| parse_error("address"))
+}
+
+/// Parse the 65 byte signature printed by `cast wallet sign`
+pub fn signature(output: &str) -> Result<String, String> {
+    static SIGNATURE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b0x[0-9a-fA-F]{130}\b").unwrap());
+
+    SIGNATURE
+        .find(output)
+        .map(|signature| signature.as_str().to_string())
+        .ok_or_else(|| parse_error("signature"))
+}
+
+/// Parse the signed transaction printed by `cast mktx`, the last line that is
+/// nothing but hex
+pub fn raw_transaction(output: &str) -> Result<String, String> {
+    output
+        .lines()
+        .map(str::trim)
+        .rfind(|line| {
+            line.strip_prefix("0x")
+                .is_some_and(|hex| !hex.is_empty() && hex.len() % 2 == 0 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        })
+        .map(str::to_string)
+        .ok_or_else(|| parse_error("signed transaction"))
+}
+
+/// Hashes of the transactions reported by `cast send` ("transactionHash 0x...")
+/// or `forge script --broadcast` ("Hash: 0x..."), in order. Only labelled
+/// values are taken, so nothing else 32 bytes long is mistaken for one.
+pub fn transaction_hashes(output: &str) -> Vec<String> {
+    static HASH: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?i)(?:transactionHash\s+|\bHash:\s*)(0x[0-9a-f]{64})\b").unwrap());
+
+    let mut hashes: Vec<String> = Vec::new();
+    for captures in HASH.captures_iter(output) {
+        let hash = captures[1].to_string();
+        if !hashes.contains(&hash) {
+            hashes.push(hash);
+        }
+    }
+    hashes
+}
+
+/// Parse `cast wallet list --dir` ("name (Local)" per line) into keystore names
+pub fn wallet_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| {
+            line.trim()
+                .split(" (")  // Split at " (" to remove "(Local)"
+                .next()       // Take the first part
+                .unwrap_or("") // Handle the case where split returns nothing
+                .to_string()
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Parse "Address: ..." and "Private key: ..." lines, in any order and case
+fn labelled_wallet(output: &str) -> Result<WalletInfo, String> {
+    let address = labelled(output, "address:")
+        .and_then(|value| ADDRESS.find(value))
+        .ok_or_else(|| parse_error("address"))?;
+    let private_key = labelled(output, "private key:")
+        .and_then(|value| PRIVATE_KEY.find(value))
+        .ok_or_else(|| parse_error("private key"))?;
+
+    Ok(WalletInfo {
+        address: address.as_str().to_string(),
+        private_key: SecretString::new(private_key.as_str()),
+    })
+}
+
+/// The rest of the first line that starts with `label`, ignoring case
+fn labelled<'a>(output: &'a str, label: &str) -> Option<&'a str> {
+    output.lines().map(str::trim_start).find_map(|line| {
+        let head = line.get(..label.len())?;
+        head.eq_ignore_ascii_case(label).then(|| &line[label.len()..])
+    })
+}
+
+fn parse_error(what: &str) -> String {
+    let err_msg = format!("Could not parse {} from cast output", what);
+    error!("{}", err_msg);
+    err_msg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    const KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const HASH: &str = "0x9d7e5c3a1f2b4d6e8a0c2e4f6a8b0d2f4a6c8e0b2d4f6a8c0e2a4b6d8f0a2c4e";
+
+    fn stable() -> CastVersion {
+        CastVersion::parse("cast Version: 1.2.3-stable\nBuild Timestamp: 2025-06-08T15:42:50.507050000Z\n").unwrap()
+    }
+
+    fn nightly() -> CastVersion {
+        CastVersion::parse("cast 0.2.0 (6f5e8c1 2024-05-01T00:17:42.044063000Z)\n").unwrap()
+    }
+
+    #[test]
+    fn wallet_new_per_version() {
+        assert_eq!(wallet_new_args(&stable()), ["wallet", "new", "--json"]);
+        let json = format!("[\n  {{\n    \"address\": \"{}\",\n    \"private_key\": \"{}\"\n  }}\n]\n", ADDRESS, KEY);
+        let wallet = wallet_new(&stable(), &json).unwrap();
+        assert_eq!((wallet.address.as_str(), wallet.private_key.as_str()), (ADDRESS, KEY));
+
+        assert_eq!(wallet_new_args(&nightly()), ["wallet", "new"]);
+        let labelled = format!("Successfully created new keypair.\nAddress:     {}\nPrivate key: {}\n", ADDRESS, KEY);
+        let wallet = wallet_new(&nightly(), &labelled).unwrap();
+        assert_eq!((wallet.address.as_str(), wallet.private_key.as_str()), (ADDRESS, KEY));
+
+        assert!(wallet_new(&stable(), &labelled).is_err());
+    }
+
+    #[test]
+    fn vanity_wallet() {
+        let output = format!(
+            "Starting to generate vanity address...\nSuccessfully found vanity address in 0.031 seconds.\nAddress: {}\nPrivate Key: {}\n",
+            ADDRESS, KEY,
+        );
+        let wallet = vanity(&output).unwrap();
+        assert_eq!((wallet.address.as_str(), wallet.private_key.as_str()), (ADDRESS, KEY));
+        assert!(vanity(&format!("Address: {}\n", ADDRESS)).is_err());
+    }
+
+    #[test]
+    fn decrypted_key_needs_its_label() {
+        let key = decrypted_key(&format!("fixture's private key is: {}\n", KEY)).unwrap();
+        assert_eq!(key.as_str(), KEY);
+        let key = decrypted_key(&format!("fixture's Private Key is: {}\n", &KEY[2..])).unwrap();
+        assert_eq!(key.as_str(), &KEY[2..]);
+
+        assert!(decrypted_key(KEY).is_err());
+        assert!(decrypted_key(&format!("transactionHash {}\nprivate key is: \n", HASH)).is_err());
+    }
+
+    #[test]
+    fn addresses_and_signatures() {
+        let imported = format!("`fixture` keystore was saved successfully. Address: {}\n", ADDRESS);
+        assert_eq!(address(&imported).unwrap(), ADDRESS);
+        assert_eq!(address(&format!("{}\n", ADDRESS)).unwrap(), ADDRESS);
+        assert!(address("Error: Mac Mismatch\n").is_err());
+
+        let sig = format!("0x{}{}1c", &KEY[2..], &KEY[2..]);
+        assert_eq!(signature(&format!("{}\n", sig)).unwrap(), sig);
+        assert!(signature(KEY).is_err());
+    }
+
+    #[test]
+    fn raw_transaction_is_the_last_hex_line() {
+        assert_eq!(raw_transaction("warning: something\n0x02f86b01\n").unwrap(), "0x02f86b01");
+        assert!(raw_transaction("0x\n0x123\n").is_err());
+    }
+
+    #[test]
+    fn transaction_hashes_from_send_and_script() {
+        let send = format!(
+            "\nblockHash            0x{}\nblockNumber          19\nstatus               1 (success)\ntransactionHash      {}\n",
+            "4c".repeat(32), HASH,
+        );
+        assert_eq!(transaction_hashes(&send), [HASH]);
+
+        let script = format!("##### anvil-hardhat\n✅  [Success] Hash: {}\n✅  [Success] Hash: {}\n", HASH, HASH);
+        assert_eq!(transaction_hashes(&script), [HASH]);
+    }
+
+    #[test]
+    fn wallet_list_names() {
+        assert_eq!(wallet_list("fixture (Local)\n\nother wallet (Local)\n"), ["fixture", "other wallet"]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+use log::error;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use zeroize::Zeroize;
+
+use crate::models::SecretString;
+use crate::pty::{run_script, ExpectScript, PtyConfig, PtyError, PtyResult};
+use super::locate::{cast_binary, forge_binary};
+use super::{CastExecutor, CastFuture, CastOutput};
+
+/// Runs the cast binary installed on this machine
+pub struct SystemCast;
+
+impl CastExecutor for SystemCast {
+    fn output<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        timeout: Duration,
+        cancel: &'a CancellationToken,
+    ) -> CastFuture<'a, Result<CastOutput, PtyError>> {
+        Box::pin(async move {
+            let cast_path = cast_binary().map_err(PtyError::SpawnFailed)?;
+
+            let child = Command::new(cast_path)
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| {
+                    let msg = format!("{}", e);
+                    error!("Failed to spawn cast: {}", msg);
+                    PtyError::SpawnFailed(msg)
+                })?;
+
+            // Wait for the process on a blocking thread so we don't block the async runtime
+            let pid = child.id();
+            let mut wait = tokio::task::spawn_blocking(move || child.wait_with_output());
+
+            let output = tokio::select! {
+                result = &mut wait => result,
+                _ = cancel.cancelled() => {
+                    abandon(pid, wait);
+                    return Err(PtyError::Cancelled);
+                }
+                _ = tokio::time::sleep(timeout) => {
+                    error!("cast {} timed out", args.join(" "));
+                    abandon(pid, wait);
+                    return Err(PtyError::Timeout);
+                }
+            };
+
+            let mut output = output
+                .map_err(|e| PtyError::CommandFailed(format!("Task join error: {}", e)))?
+                .map_err(|e| {
+                    let msg = format!("{}", e);
+                    error!("Failed to wait for cast: {}", msg);
+                    PtyError::IoError(msg)
+                })?;
+
+            // Copy stdout into locked memory and wipe the pipe buffer
+            let stdout = SecretString::new(&String::from_utf8_lossy(&output.stdout));
+            output.stdout.zeroize();
+
+            Ok(CastOutput {
+                stdout,
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                exit_code: output.status.code(),
+            })
+        })
+    }
+
+    fn run_script<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        script: &'a ExpectScript<'a>,
+        config: &'a PtyConfig,
+    ) -> CastFuture<'a, Result<PtyResult, PtyError>> {
+        Box::pin(async move {
+            let cast_path = cast_binary().map_err(PtyError::SpawnFailed)?;
+            run_script(&cast_path, args, script, config).await
+        })
+    }
+
+    fn run_forge_script<'a>(
+        &'a self,
+        args: &'a [&'a str],
+        script: &'a ExpectScript<'a>,
+        config: &'a PtyConfig,
+    ) -> CastFuture<'a, Result<PtyResult, PtyError>> {
+        Box::pin(async move {
+            let forge_path = forge_binary().map_err(PtyError::SpawnFailed)?;
+            run_script(&forge_path, args, script, config).await
+        })
+    }
+}
+
+/// Kill cast, then reap it and wipe whatever it printed in the background,
+/// anything it spawned may keep the pipes open for a while
+fn abandon(pid: u32, wait: JoinHandle<std::io::Result<Output>>) {
+    kill_process(pid);
+    tokio::spawn(async move {
+        if let Ok(Ok(mut output)) = wait.await {
+            output.stdout.zeroize();
+        }
+    });
+}
+
+fn kill_process(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(pid as i32, libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F"])
+            .output();
+    }
+}
@@ -0,0 +1,217 @@
+use std::cmp::Ordering;
+use std::sync::{LazyLock, Mutex};
+use log::{error, info, warn};
+use regex::Regex;
+use tokio_util::sync::CancellationToken;
+
+use crate::commands::pty_error_message;
+use super::{CastExecutor, OUTPUT_TIMEOUT};
+
+/// A cast release, as reported by `cast --version`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CastVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Build date as YYYYMMDD, the only thing that tells pre-1.0 nightlies
+    /// apart since they all report 0.2.0
+    pub build_date: Option<u32>,
+}
+
+impl CastVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch, build_date: None }
+    }
+
+    /// Parse the output of `cast --version`. Handles both the current format
+    /// ("cast Version: 1.2.3-stable" followed by the commit) and the pre-1.0
+    /// one ("cast 0.2.0 (6f5e8c1 2024-05-01T00:17:42.044063000Z)").
+    pub fn parse(output: &str) -> Option<Self> {
+        static VERSION: LazyLock<Regex> = LazyLock::new(|| version_regex("cast"));
+        Self::parse_with(&VERSION, output)
+    }
+
+    /// Parse the output of `forge --version`, which uses the same formats
+    pub fn parse_forge(output: &str) -> Option<Self> {
+        static VERSION: LazyLock<Regex> = LazyLock::new(|| version_regex("forge"));
+        Self::parse_with(&VERSION, output)
+    }
+
+    fn parse_with(regex: &Regex, output: &str) -> Option<Self> {
+        // "(6f5e8c1 2024-05-01T00:17:42...)" or "Build Timestamp: 2025-06-08T15:42:50..."
+        static BUILD_DATE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"\b(\d{4})-(\d{2})-(\d{2})T\d{2}:").unwrap());
+
+        let captures = regex.captures(output)?;
+        let part = |index: usize| captures[index].parse().ok();
+        let mut version = Self::new(part(1)?, part(2)?, part(3)?);
+
+        version.build_date = BUILD_DATE.captures(output).and_then(|date| {
+            let part = |index: usize| date[index].parse::<u32>().ok();
+            Some(part(1)? * 10_000 + part(2)? * 100 + part(3)?)
+        });
+        Some(version)
+    }
+
+    /// Whether ForgeKey can run this cast: any release after `MIN_SUPPORTED`,
+    /// or a `MIN_SUPPORTED` nightly built on or after `MIN_NIGHTLY_DATE`
+    pub fn is_supported(&self) -> bool {
+        let release = (self.major, self.minor, self.patch);
+        match release.cmp(&(MIN_SUPPORTED.major, MIN_SUPPORTED.minor, MIN_SUPPORTED.patch)) {
+            Ordering::Less => false,
+            Ordering::Greater => true,
+            Ordering::Equal => self.build_date.is_some_and(|date| date >= MIN_NIGHTLY_DATE),
+        }
+    }
+
+    /// Whether `cast wallet new` supports `--json`
+    pub fn has_wallet_new_json(&self) -> bool {
+        *self >= WALLET_NEW_JSON
+    }
+}
+
+fn version_regex(tool: &str) -> Regex {
+    Regex::new(&format!(r"(?m)^{}(?:\s+Version:)?\s+v?(\d+)\.(\d+)\.(\d+)", tool)).unwrap()
+}
+
+impl std::fmt::Display for CastVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Version every pre-1.0 nightly reports. Older releases are unsupported,
+/// nightlies of it only from `MIN_NIGHTLY_DATE` on.
+pub const MIN_SUPPORTED: CastVersion = CastVersion::new(0, 2, 0);
+
+/// Oldest nightly build date (YYYYMMDD) ForgeKey supports. Earlier nightlies
+/// lack some of the `wallet` subcommands it runs, such as `decrypt-keystore`
+/// and `list --dir`.
+pub const MIN_NIGHTLY_DATE: u32 = 20240401;
+
+/// First release the output parsers haven't been checked against. Newer
+/// versions are still used, with a warning.
+pub const SUPPORTED_BELOW: CastVersion = CastVersion::new(2, 0, 0);
+
+/// First release where `cast wallet new --json` can be relied on. 0.2.0
+/// nightlies all report the same version, so only some of them have it.
+const WALLET_NEW_JSON: CastVersion = CastVersion::new(1, 0, 0);
+
+/// Version of the cast binary in use, detected once and reused
+static DETECTED: Mutex<Option<CastVersion>> = Mutex::new(None);
+
+/// Run `cast --version` and remember the result.
+pub async fn detect(cast: &dyn CastExecutor) -> Result<CastVersion, String> {
+    let output = cast.output(&["--version"], OUTPUT_TIMEOUT, &CancellationToken::new())
+        .await
+        .map_err(|e| pty_error_message("cast --version", e))?;
+
+    let version = CastVersion::parse(&output.stdout).ok_or_else(|| {
+        let err_msg = format!("Could not determine the cast version from {:?}", output.stdout.trim());
+        error!("{}", err_msg);
+        err_msg
+    })?;
+
+    info!("Using cast {}", version);
+    *DETECTED.lock().unwrap() = Some(version);
+    Ok(version)
+}
+
+/// Forget the detected version, so the next `supported` call detects it again.
+pub fn forget() {
+    *DETECTED.lock().unwrap() = None;
+}
+
+/// Why `version` is outside the range ForgeKey supports, if it is.
+pub fn range_warning(version: &CastVersion) -> Option<String> {
+    if !version.is_supported() {
+        let built = version.build_date.map_or_else(|| "unknown".to_string(), format_date);
+        Some(format!(
+            "Unsupported cast version {} (built {}), ForgeKey needs a {} nightly from {} or newer. Run foundryup to update Foundry.",
+            version, built, MIN_SUPPORTED, format_date(MIN_NIGHTLY_DATE),
+        ))
+    } else if *version >= SUPPORTED_BELOW {
+        Some(format!(
+            "cast {} is newer than the releases ForgeKey supports (below {}), some of its output may not be recognized",
+            version, SUPPORTED_BELOW,
+        ))
+    } else {
+        None
+    }
+}
+
+/// The version of cast in use, detecting it on first use.
+/// Fails with a clear message if it isn't supported, and
+/// logs a warning if it is newer than the parsers were checked against.
+pub async fn supported(cast: &dyn CastExecutor) -> Result<CastVersion, String> {
+    let detected = *DETECTED.lock().unwrap();
+    let version = match detected {
+        Some(version) => version,
+        None => detect(cast).await?,
+    };
+
+    match range_warning(&version) {
+        Some(err_msg) if !version.is_supported() => {
+            error!("{}", err_msg);
+            Err(err_msg)
+        }
+        Some(warning) => {
+            warn!("{}", warning);
+            Ok(version)
+        }
+        None => Ok(version),
+    }
+}
+
+/// YYYYMMDD as YYYY-MM-DD
+fn format_date(date: u32) -> String {
+    format!("{:04}-{:02}-{:02}", date / 10_000, date / 100 % 100, date % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STABLE: &str = "cast Version: 1.2.3-stable\n\
+        Commit SHA: a813a2cee7dd4926e7c56fd8a785b54f32e0d10f\n\
+        Build Timestamp: 2025-06-08T15:42:50.507050000Z (1749397370)\n\
+        Build Profile: maxperf\n";
+    const NIGHTLY: &str = "cast 0.2.0 (6f5e8c1 2024-05-01T00:17:42.044063000Z)\n";
+    const OLD_NIGHTLY: &str = "cast 0.2.0 (f625d0f 2024-01-10T00:21:02.155929000Z)\n";
+
+    #[test]
+    fn parses_recorded_versions() {
+        let stable = CastVersion::parse(STABLE).unwrap();
+        assert_eq!((stable.major, stable.minor, stable.patch), (1, 2, 3));
+        assert_eq!(stable.build_date, Some(20250608));
+
+        let nightly = CastVersion::parse(NIGHTLY).unwrap();
+        assert_eq!((nightly.major, nightly.minor, nightly.patch), (0, 2, 0));
+        assert_eq!(nightly.build_date, Some(20240501));
+
+        let forge = CastVersion::parse_forge("forge Version: 1.2.3-stable\n").unwrap();
+        assert_eq!((forge.to_string(), forge.build_date), ("1.2.3".to_string(), None));
+        assert!(CastVersion::parse("forge Version: 1.2.3-stable\n").is_none());
+    }
+
+    #[test]
+    fn gates_nightlies_by_build_date() {
+        assert!(CastVersion::parse(STABLE).unwrap().is_supported());
+        assert!(CastVersion::parse(NIGHTLY).unwrap().is_supported());
+        assert!(CastVersion::new(0, 3, 0).is_supported());
+
+        for output in [OLD_NIGHTLY, "cast 0.2.0\n", "cast 0.1.0 (a1b2c3d 2024-06-01T00:00:00Z)\n"] {
+            let version = CastVersion::parse(output).unwrap();
+            assert!(!version.is_supported(), "{}", output);
+            assert!(range_warning(&version).unwrap().starts_with("Unsupported"));
+        }
+        let warning = range_warning(&CastVersion::parse(OLD_NIGHTLY).unwrap()).unwrap();
+        assert!(warning.contains("built 2024-01-10"), "{}", warning);
+    }
+
+    #[test]
+    fn warns_above_the_checked_range() {
+        assert!(range_warning(&CastVersion::parse(STABLE).unwrap()).is_none());
+        assert!(range_warning(&CastVersion::new(2, 0, 0)).unwrap().contains("newer than"));
+    }
+}
@@ -0,0 +1,311 @@
+use std::path::Path;
+use std::time::Duration;
+use crate::cast::{parse, version, CastExecutor};
+use crate::models::Password;
+use crate::pty::{ExpectScript, OnOutput, PtyConfig};
+use crate::session;
+use crate::utils::redact_secrets;
+use crate::commands::{locked_error, pty_error_message};
+use log::{error, info};
+use tokio_util::sync::CancellationToken;
+
+// Deployments and transactions sent by Foundry itself, signed with a keystore
+// unlocked in ForgeKey. forge and cast are run with `--account` inside the
+// project and its password is typed into their prompt through the PTY, so it
+// is neither on the command line nor asked for in a terminal.
+
+/// Deployments can compile, simulate and wait for confirmations on a slow
+/// chain, give them far longer than the wallet commands
+const BROADCAST_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Flags ForgeKey sets itself, or that would take a key or password from the
+/// command line or sign with something other than the selected keystore
+const RESERVED_FLAGS: [&str; 26] = [
+  "--account",
+  "--keystore",
+  "--keystores",
+  "--password",
+  "--password-file",
+  "--private-key",
+  "--private-keys",
+  "--mnemonic",
+  "--mnemonics",
+  "--mnemonic-passphrase",
+  "--mnemonic-passphrases",
+  "--mnemonic-derivation-path",
+  "--mnemonic-derivation-paths",
+  "--hd-path",
+  "--mnemonic-index",
+  "--mnemonic-indexes",
+  "--interactive",
+  "--interactives",
+  "-i",
+  "--unlocked",
+  "--ledger",
+  "--trezor",
+  "--aws",
+  "--gcp",
+  "--turnkey",
+  "--sender",
+];
+
+enum Tool {
+  Forge,
+  Cast,
+}
+
+impl Tool {
+  fn command(&self) -> &'static str {
+    match self {
+      Tool::Forge => "forge script",
+      Tool::Cast => "cast send",
+    }
+  }
+}
+
+/// Run `forge script <script> --account <keystore> --broadcast` in
+/// `project_dir`, e.g. `script/Deploy.s.sol:Deploy` with `--rpc-url sepolia`
+/// in `args`. Output is passed to `on_output` line by line while it runs.
+/// Returns the hashes of the transactions it sent.
+pub async fn run_forge_script(
+  cast: &dyn CastExecutor,
+  project_dir: &Path,
+  keystore_name: &str,
+  script: &str,
+  args: &[String],
+  on_output: OnOutput,
+  cancel: CancellationToken,
+) -> Result<Vec<String>, String> {
+  if script.is_empty() || script.starts_with('-') {
+    let err_msg = format!("Invalid script {:?}", script);
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+
+  let mut forge_args = vec!["script", script, "--account", keystore_name, "--broadcast"];
+  forge_args.extend(checked_args(args)?);
+
+  run_broadcast(cast, Tool::Forge, project_dir, keystore_name, &forge_args, on_output, cancel).await
+}
+
+/// Run `cast send --account <keystore> <args>` in `project_dir`, where `args`
+/// holds the recipient, function signature and arguments and any other flags.
+/// Output is passed to `on_output` line by line. Returns the transaction hash,
+/// or nothing with `--async`, which prints the bare hash without a label.
+pub async fn cast_send(
+  cast: &dyn CastExecutor,
+  project_dir: &Path,
+  keystore_name: &str,
+  args: &[String],
+  on_output: OnOutput,
+  cancel: CancellationToken,
+) -> Result<Vec<String>, String> {
+  let mut cast_args = vec!["send", "--account", keystore_name];
+  cast_args.extend(checked_args(args)?);
+
+  run_broadcast(cast, Tool::Cast, project_dir, keystore_name, &cast_args, on_output, cancel).await
+}
+
+async fn run_broadcast(
+  cast: &dyn CastExecutor,
+  tool: Tool,
+  project_dir: &Path,
+  keystore_name: &str,
+  args: &[&str],
+  on_output: OnOutput,
+  cancel: CancellationToken,
+) -> Result<Vec<String>, String> {
+  let password = session::with_unlocked(keystore_name, |session| Password::new(session.password.as_str()))
+    .ok_or_else(|| locked_error(keystore_name))?;
+
+  if !project_dir.is_dir() {
+    let err_msg = format!("Project directory {} does not exist", project_dir.display());
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+
+  version::supported(cast).await?;
+
+  let command = tool.command();
+  let config = PtyConfig {
+    timeout: BROADCAST_TIMEOUT,
+    cancel,
+    cwd: Some(project_dir.to_path_buf()),
+    on_output: Some(on_output),
+    ..Default::default()
+  };
+  let script = ExpectScript::new().expect("password", r"(?i)password[^\n]*:\s*$", &password);
+
+  info!("Running {} with keystore {} in {}", command, keystore_name, project_dir.display());
+  let result = match tool {
+    Tool::Forge => cast.run_forge_script(args, &script, &config).await,
+    Tool::Cast => cast.run_script(args, &script, &config).await,
+  }
+  .map_err(|e| pty_error_message(command, e))?;
+
+  if !result.success() {
+    // The whole output went to `on_output` already, repeat the error it ended with
+    let err_msg = result.output
+      .lines()
+      .rev()
+      .find(|line| line.trim_start().starts_with("Error"))
+      .map(|line| redact_secrets(line.trim()))
+      .unwrap_or_else(|| format!("{} failed with exit code {}", command, result.exit_code));
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+
+  Ok(parse::transaction_hashes(&result.output))
+}
+
+/// Reject the flags in `RESERVED_FLAGS`, both as `--flag value` and `--flag=value`
+fn checked_args(args: &[String]) -> Result<impl Iterator<Item = &str>, String> {
+  let reserved = args
+    .iter()
+    .map(|arg| arg.split('=').next().unwrap_or_default())
+    .find(|flag| RESERVED_FLAGS.contains(flag));
+
+  match reserved {
+    Some(flag) => {
+      let err_msg = format!("{} can't be passed, ForgeKey signs with the selected keystore", flag);
+      error!("{}", err_msg);
+      Err(err_msg)
+    }
+    None => Ok(args.iter().map(String::as_str)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn check(args: &[&str]) -> Result<Vec<String>, String> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    checked_args(&args).map(|args| args.map(str::to_string).collect())
+  }
+
+  #[test]
+  fn rejects_other_signers() {
+    for flag in RESERVED_FLAGS {
+      for args in [vec![flag.to_string(), "value".to_string()], vec![format!("{}=value", flag)]] {
+        let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+        args.splice(0..0, ["--rpc-url", "sepolia"]);
+        let err = check(&args).unwrap_err();
+        assert!(err.starts_with(&format!("{} can't be passed", flag)), "{}", err);
+      }
+    }
+
+    let cases: [(&[&str], &str); 6] = [
+      (&["--sender", "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"], "--sender"),
+      (&["--gcp"], "--gcp"),
+      (&["--turnkey=true"], "--turnkey"),
+      (&["--mnemonic-passphrase=secret"], "--mnemonic-passphrase"),
+      (&["--mnemonic-derivation-path", "m/44'/60'/0'/0/1"], "--mnemonic-derivation-path"),
+      (&["--mnemonic-indexes=0,1"], "--mnemonic-indexes"),
+    ];
+    for (args, flag) in cases {
+      assert!(check(args).unwrap_err().starts_with(flag), "{:?}", args);
+    }
+  }
+
+  #[test]
+  fn passes_other_args_through() {
+    let args = ["0x70997970C51812dc3A010C7d01b50e0d17dc79C8", "transfer(address,uint256)", "--rpc-url=sepolia", "--async"];
+    assert_eq!(check(&args).unwrap(), args);
+  }
+
+  #[cfg(feature = "fake-cast")]
+  mod with_fake_cast {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY, FIXTURE_TRANSACTION_HASH, FIXTURE_WRONG_PASSWORD};
+    use crate::commands::testing::{command_lines, recorded_versions, serial, temp_dir};
+    use crate::models::SecretString;
+
+    fn unlock(password: &str) {
+      session::unlock("deployer", FIXTURE_ADDRESS.to_string(), Password::new(password), SecretString::new(FIXTURE_PRIVATE_KEY));
+    }
+
+    /// `OnOutput` collecting the lines it is given
+    fn collect_output() -> (OnOutput, Arc<Mutex<Vec<String>>>) {
+      let lines = Arc::new(Mutex::new(Vec::new()));
+      let collected = lines.clone();
+      (Arc::new(move |line: &str| collected.lock().unwrap().push(line.to_string())), lines)
+    }
+
+    async fn deploy(cast: &FakeCast, project_dir: &Path, cancel: CancellationToken) -> Result<Vec<String>, String> {
+      let (on_output, _) = collect_output();
+      let args = ["--rpc-url".to_string(), "sepolia".to_string()];
+      run_forge_script(cast, project_dir, "deployer", "script/Deploy.s.sol:Deploy", &args, on_output, cancel).await
+    }
+
+    #[tokio::test]
+    async fn broadcasts_with_forge_and_cast() {
+      let _serial = serial().await;
+      let project_dir = temp_dir("broadcast");
+      for cast in recorded_versions() {
+        version::forget();
+        unlock("pw");
+        let (on_output, lines) = collect_output();
+        let script_args = ["--rpc-url".to_string(), "sepolia".to_string()];
+        let deployed = run_forge_script(&cast, &project_dir, "deployer", "script/Deploy.s.sol:Deploy", &script_args, on_output, CancellationToken::new()).await;
+        let (on_output, _) = collect_output();
+        let send_args = [FIXTURE_ADDRESS.to_string(), "--value".to_string(), "1ether".to_string()];
+        let sent = cast_send(&cast, &project_dir, "deployer", &send_args, on_output, CancellationToken::new()).await;
+        session::lock("deployer");
+
+        assert_eq!(deployed.unwrap(), [FIXTURE_TRANSACTION_HASH]);
+        assert_eq!(sent.unwrap(), [FIXTURE_TRANSACTION_HASH]);
+        assert!(lines.lock().unwrap().iter().any(|line| line == "ONCHAIN EXECUTION COMPLETE & SUCCESSFUL."));
+        assert_eq!(command_lines(&cast)[1..], [
+          "forge script script/Deploy.s.sol:Deploy --account deployer --broadcast --rpc-url sepolia".to_string(),
+          format!("send --account deployer {} --value 1ether", FIXTURE_ADDRESS),
+        ]);
+        assert_eq!(cast.calls()[1].answered, ["password"]);
+      }
+      let _ = std::fs::remove_dir_all(&project_dir);
+    }
+
+    #[tokio::test]
+    async fn needs_an_unlocked_keystore_and_a_project() {
+      let _serial = serial().await;
+      let cast = FakeCast::recorded();
+      let project_dir = temp_dir("broadcast-locked");
+      assert_eq!(deploy(&cast, &project_dir, CancellationToken::new()).await.unwrap_err(), "Keystore 'deployer' is locked");
+
+      unlock(FIXTURE_WRONG_PASSWORD);
+      let wrong_password = deploy(&cast, &project_dir, CancellationToken::new()).await;
+      let missing_dir = deploy(&cast, &project_dir.join("missing"), CancellationToken::new()).await;
+      session::lock("deployer");
+
+      assert_eq!(wrong_password.unwrap_err(), "Incorrect password");
+      assert!(missing_dir.unwrap_err().ends_with("does not exist"));
+      let _ = std::fs::remove_dir_all(&project_dir);
+    }
+
+    // The stuck run waits for the whole broadcast timeout, let it pass instantly
+    #[tokio::test(start_paused = true)]
+    async fn reports_errors_and_stops_when_cancelled_or_stuck() {
+      let _serial = serial().await;
+      let project_dir = temp_dir("broadcast-errors");
+      let failing = FakeCast::recorded().on(&["forge", "script"], FakeResponse::failure(
+        "[⠊] Compiling...\nError: script failed: revert: Ownable: caller is not the owner\n",
+        1,
+      ).prompt("Enter keystore password:"));
+      let stuck = FakeCast::recorded().on(&["forge", "script"], FakeResponse::output("").prompt("Waiting for the Ledger"));
+      let cancel = CancellationToken::new();
+      cancel.cancel();
+
+      unlock("pw");
+      let failed = deploy(&failing, &project_dir, CancellationToken::new()).await;
+      let timed_out = deploy(&stuck, &project_dir, CancellationToken::new()).await;
+      let cancelled = deploy(&FakeCast::recorded(), &project_dir, cancel).await;
+      session::lock("deployer");
+
+      assert_eq!(failed.unwrap_err(), "Error: script failed: revert: Ownable: caller is not the owner");
+      assert_eq!(timed_out.unwrap_err(), "Failed to execute forge script command: Operation timed out");
+      assert_eq!(cancelled.unwrap_err(), "Failed to execute forge script command: Operation was cancelled");
+      let _ = std::fs::remove_dir_all(&project_dir);
+    }
+  }
+}
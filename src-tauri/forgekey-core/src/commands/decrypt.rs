@@ -0,0 +1,100 @@
+use crate::cast::{parse, version, CastExecutor};
+use crate::utils::redact_secrets;
+use crate::models::Password;
+use crate::pty::PtyConfig;
+use crate::commands::pty_error_message;
+use log::error;
+use tokio_util::sync::CancellationToken;
+
+/// Decrypt a keystore and return its private key. Always takes the password,
+/// even while the keystore is unlocked.
+pub async fn decrypt_keystore(cast: &dyn CastExecutor, keystore_name: String, password: Password, cancel: CancellationToken) -> Result<String, String> {
+  version::supported(cast).await?;
+
+  // Use PTY-based password input for security (password not visible in process list)
+  let config = PtyConfig { cancel, ..Default::default() }; // Single password prompt
+  let result = cast.run_with_password(
+    &["wallet", "decrypt-keystore", &keystore_name],
+    &password,
+    &config,
+  ).await.map_err(|e| pty_error_message("cast wallet decrypt-keystore", e))?;
+
+  if !result.success() {
+    let err_msg = redact_secrets(&result.output);
+    error!("Failed to decrypt keystore {}: {}", keystore_name, err_msg);
+    return Err(err_msg);
+  }
+
+  // Parse the private key into locked memory to ensure it's zeroized when dropped
+  let private_key = parse::decrypted_key(&result.output)?;
+
+  // SECURITY WARNING: The returned private key is not automatically zeroized.
+  // The frontend MUST zeroize this value after use by overwriting it with zeros
+  // or using a secure zeroizing library.
+  let key_result = private_key.as_str().to_string();
+
+  // private_key will be automatically zeroized when dropped at the end of this function
+  Ok(key_result)
+}
+
+#[cfg(all(test, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY, FIXTURE_WRONG_PASSWORD};
+  use crate::commands::testing::{command_lines, recorded_versions, serial};
+  use crate::models::SecretString;
+  use crate::session;
+
+  async fn decrypt(cast: &FakeCast, password: &str, cancel: CancellationToken) -> Result<String, String> {
+    decrypt_keystore(cast, "decrypted".to_string(), Password::new(password), cancel).await
+  }
+
+  #[tokio::test]
+  async fn decrypts_with_a_password() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      assert_eq!(decrypt(&cast, "pw", CancellationToken::new()).await.unwrap(), FIXTURE_PRIVATE_KEY);
+      assert_eq!(command_lines(&cast), ["--version", "wallet decrypt-keystore decrypted"]);
+      assert_eq!(cast.calls()[1].answered, ["password"]);
+
+      let err = decrypt(&cast, FIXTURE_WRONG_PASSWORD, CancellationToken::new()).await.unwrap_err();
+      assert_eq!(err, "Incorrect password");
+    }
+  }
+
+  #[tokio::test]
+  async fn asks_for_the_password_while_unlocked() {
+    let _serial = serial().await;
+    let cast = FakeCast::recorded();
+    session::unlock("decrypted", FIXTURE_ADDRESS.to_string(), Password::new("pw"), SecretString::new(FIXTURE_PRIVATE_KEY));
+    let err = decrypt(&cast, FIXTURE_WRONG_PASSWORD, CancellationToken::new()).await;
+    session::lock("decrypted");
+    assert_eq!(err.unwrap_err(), "Incorrect password");
+    assert_eq!(command_lines(&cast), ["--version", "wallet decrypt-keystore decrypted"]);
+  }
+
+  #[tokio::test]
+  async fn reports_cast_errors() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      let cast = cast.on(&["wallet", "decrypt-keystore"], FakeResponse::failure("Error: Keystore file `decrypted` not found\n", 1));
+      let err = decrypt(&cast, "pw", CancellationToken::new()).await.unwrap_err();
+      assert_eq!(err, "Error: Keystore file `decrypted` not found");
+    }
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn stops_when_cancelled_or_stuck() {
+    let _serial = serial().await;
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let err = decrypt(&FakeCast::recorded(), "pw", cancel).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet decrypt-keystore command: Operation was cancelled");
+
+    let cast = FakeCast::recorded().on(&["wallet", "decrypt-keystore"], FakeResponse::output("").prompt("Touch your security key"));
+    let err = decrypt(&cast, "pw", CancellationToken::new()).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet decrypt-keystore command: Operation timed out");
+  }
+}
@@ -0,0 +1,304 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::cast::CastExecutor;
+use crate::commands::import::import_wallet_into;
+use crate::commands::{keystore_address, locked_error, write_new_keystore};
+use crate::models::Password;
+use crate::session;
+use crate::utils::get_keystore_dir;
+use log::{error, info};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tokio_util::sync::CancellationToken;
+
+// Keystores handed to geth and the tools that follow its layout: a directory
+// of `UTC--<timestamp>--<address>` files, each with the `address` field
+// Foundry leaves out. The encrypted JSON is copied as it is, the key is only
+// decrypted to re-encrypt it under another password.
+
+/// A keystore written by `export_keystores`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedKeystore {
+  pub keystore: String,
+  pub address: String,
+  /// Path of the written file
+  pub path: String,
+}
+
+/// Write `keystore_names` to `target_dir` as geth keystore files. With
+/// `ethers` the files also carry the `x-ethers` metadata ethers writes. With
+/// `new_password` each key is re-encrypted, which needs the keystores to be
+/// unlocked. Otherwise only their addresses are needed, which are known once
+/// they have been unlocked or when the keystore file has one.
+pub async fn export_keystores(
+  cast: &dyn CastExecutor,
+  keystore_names: &[String],
+  target_dir: &Path,
+  ethers: bool,
+  new_password: Option<Password>,
+  cancel: CancellationToken,
+) -> Result<Vec<ExportedKeystore>, String> {
+  let keystore_dir = get_keystore_dir()?;
+  if target_dir.canonicalize().ok() == keystore_dir.canonicalize().ok() {
+    let err_msg = "Choose a directory other than Foundry's keystore directory".to_string();
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+
+  let mut exported = Vec::new();
+  for keystore_name in keystore_names {
+    let exported_keystore = match &new_password {
+      Some(new_password) => reencrypt(cast, keystore_name, target_dir, ethers, new_password, cancel.clone()).await?,
+      None => copy(&keystore_dir, keystore_name, target_dir, ethers)?,
+    };
+    info!("Exported keystore {} to {}", keystore_name, exported_keystore.path);
+    exported.push(exported_keystore);
+  }
+  Ok(exported)
+}
+
+/// Copy the encrypted keystore without decrypting it
+fn copy(keystore_dir: &Path, keystore_name: &str, target_dir: &Path, ethers: bool) -> Result<ExportedKeystore, String> {
+  let Some(address) = keystore_address(keystore_name) else {
+    let err_msg = format!("The address of keystore '{}' is unknown, unlock it first", keystore_name);
+    error!("{}", err_msg);
+    return Err(err_msg);
+  };
+
+  let source = keystore_dir.join(keystore_name);
+  let keystore = fs::read_to_string(&source)
+    .ok()
+    .and_then(|json| serde_json::from_str::<Map<String, Value>>(&json).ok())
+    .ok_or_else(|| {
+      let err_msg = format!("Failed to read keystore {}", source.display());
+      error!("{}", err_msg);
+      err_msg
+    })?;
+
+  let target = target_dir.join(geth_filename(&address, SystemTime::now()));
+  let json = with_metadata(keystore, &address, &target, ethers);
+  write_new_keystore(&target, json.as_bytes()).map_err(|e| write_error(&target, e))?;
+
+  Ok(ExportedKeystore {
+    keystore: keystore_name.to_string(),
+    address,
+    path: target.display().to_string(),
+  })
+}
+
+/// Import the key of an unlocked keystore into `target_dir` under
+/// `new_password`, then add the metadata cast doesn't write
+async fn reencrypt(
+  cast: &dyn CastExecutor,
+  keystore_name: &str,
+  target_dir: &Path,
+  ethers: bool,
+  new_password: &Password,
+  cancel: CancellationToken,
+) -> Result<ExportedKeystore, String> {
+  let address = session::with_unlocked(keystore_name, |session| session.address.clone())
+    .ok_or_else(|| locked_error(keystore_name))?;
+  let private_key = session::private_key(keystore_name).ok_or_else(|| locked_error(keystore_name))?;
+
+  let filename = geth_filename(&address, SystemTime::now());
+  let target = target_dir.join(&filename);
+  if target.exists() {
+    let err_msg = format!("{} already exists", target.display());
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+  fs::create_dir_all(target_dir).map_err(|e| write_error(&target, e))?;
+
+  let password = Password::new(new_password.as_str());
+  import_wallet_into(cast, private_key, filename, password, Some(target_dir), cancel).await?;
+
+  let keystore = fs::read_to_string(&target)
+    .ok()
+    .and_then(|json| serde_json::from_str::<Map<String, Value>>(&json).ok())
+    .ok_or_else(|| {
+      let err_msg = format!("cast did not write the keystore {}", target.display());
+      error!("{}", err_msg);
+      err_msg
+    })?;
+  let json = with_metadata(keystore, &address, &target, ethers);
+  fs::write(&target, json).map_err(|e| write_error(&target, e))?;
+
+  Ok(ExportedKeystore {
+    keystore: keystore_name.to_string(),
+    address,
+    path: target.display().to_string(),
+  })
+}
+
+/// The keystore JSON with geth's `address` field, lowercase without `0x`,
+/// and with `ethers` the `x-ethers` block ethers v5 adds
+fn with_metadata(mut keystore: Map<String, Value>, address: &str, target: &Path, ethers: bool) -> String {
+  let address = address.trim_start_matches("0x").to_ascii_lowercase();
+  keystore.insert("address".to_string(), Value::String(address));
+
+  if ethers {
+    let filename = target.file_name().unwrap_or_default().to_string_lossy();
+    keystore.insert(
+      "x-ethers".to_string(),
+      json!({ "client": "forgekey", "gethFilename": filename, "version": "0.1" }),
+    );
+  }
+  Value::Object(keystore).to_string()
+}
+
+fn write_error(target: &Path, e: std::io::Error) -> String {
+  let err_msg = format!("Failed to write {}: {}", target.display(), e);
+  error!("{}", err_msg);
+  err_msg
+}
+
+/// `UTC--2006-01-02T15-04-05.000000000Z--<address>`, the name geth gives
+/// keystore files
+fn geth_filename(address: &str, time: SystemTime) -> String {
+  let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+  let seconds = since_epoch.as_secs();
+  let (year, month, day) = civil_date((seconds / 86_400) as i64);
+  let time_of_day = seconds % 86_400;
+
+  format!(
+    "UTC--{:04}-{:02}-{:02}T{:02}-{:02}-{:02}.{:09}Z--{}",
+    year,
+    month,
+    day,
+    time_of_day / 3600,
+    time_of_day % 3600 / 60,
+    time_of_day % 60,
+    since_epoch.subsec_nanos(),
+    address.trim_start_matches("0x").to_ascii_lowercase(),
+  )
+}
+
+/// Year, month and day of a count of days since 1970-01-01, see
+/// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_date(days: i64) -> (i64, u32, u32) {
+  let days = days + 719_468;
+  let era = days.div_euclid(146_097);
+  let day_of_era = days.rem_euclid(146_097);
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month_index = (5 * day_of_year + 2) / 153;
+  let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+  let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+  let year = year_of_era + era * 400 + i64::from(month <= 2);
+  (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+  #[test]
+  fn names_files_like_geth() {
+    let time = UNIX_EPOCH + std::time::Duration::new(1_714_521_600 + 3661, 42);
+    assert_eq!(geth_filename(ADDRESS, time), "UTC--2024-05-01T01-01-01.000000042Z--f39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+    assert_eq!(civil_date(0), (1970, 1, 1));
+    assert_eq!(civil_date(11_016), (2000, 2, 29));
+  }
+
+  #[test]
+  fn adds_the_address_and_ethers_metadata() {
+    let keystore = Map::from_iter([("version".to_string(), json!(3))]);
+    let target = Path::new("/tmp/UTC--x");
+    let plain: Value = serde_json::from_str(&with_metadata(keystore.clone(), ADDRESS, target, false)).unwrap();
+    assert_eq!(plain, json!({ "version": 3, "address": "f39fd6e51aad88f6f4ce6ab8827279cfffb92266" }));
+
+    let ethers: Value = serde_json::from_str(&with_metadata(keystore, ADDRESS, target, true)).unwrap();
+    assert_eq!(ethers["x-ethers"], json!({ "client": "forgekey", "gethFilename": "UTC--x", "version": "0.1" }));
+  }
+
+  #[cfg(feature = "fake-cast")]
+  mod with_fake_cast {
+    use super::*;
+    use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_KEYSTORE, FIXTURE_PRIVATE_KEY};
+    use crate::cast::version;
+    use crate::commands::testing::{command_lines, recorded_versions, serial, temp_dir};
+    use crate::models::SecretString;
+
+    async fn export(cast: &FakeCast, target_dir: &Path, new_password: Option<&str>, cancel: CancellationToken) -> Result<Vec<ExportedKeystore>, String> {
+      export_keystores(cast, &["exported".to_string()], target_dir, true, new_password.map(Password::new), cancel).await
+    }
+
+    #[tokio::test]
+    async fn needs_the_address_or_an_unlocked_keystore() {
+      let _serial = serial().await;
+      let target = temp_dir("export-locked");
+      let cast = FakeCast::recorded();
+      let err = export(&cast, &target, None, CancellationToken::new()).await.unwrap_err();
+      assert_eq!(err, "The address of keystore 'exported' is unknown, unlock it first");
+      let err = export(&cast, &target, Some("new"), CancellationToken::new()).await.unwrap_err();
+      assert_eq!(err, "Keystore 'exported' is locked");
+      assert!(cast.calls().is_empty());
+      let _ = fs::remove_dir_all(&target);
+    }
+
+    #[tokio::test]
+    async fn reencrypts_through_cast_wallet_import() {
+      let _serial = serial().await;
+      for (index, cast) in recorded_versions().into_iter().enumerate() {
+        version::forget();
+        let target = temp_dir(&format!("export-reencrypt-{}", index));
+        let cast = cast.on(&["wallet", "import"], FakeResponse::output(&format!("`fixture` keystore was saved successfully. Address: {}\n", ADDRESS))
+          .prompt("Enter private key:")
+          .prompt("Enter password:")
+          .writing_keystore());
+        session::unlock("exported", ADDRESS.to_string(), Password::new("pw"), SecretString::new(FIXTURE_PRIVATE_KEY));
+        let result = export(&cast, &target, Some("new"), CancellationToken::new()).await;
+        session::lock("exported");
+
+        // The key comes from the session, only the import runs cast
+        let lines = command_lines(&cast);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("wallet import UTC--"), "{}", lines[1]);
+        assert!(lines[1].ends_with(&format!("--f39fd6e51aad88f6f4ce6ab8827279cfffb92266 --interactive --keystore-dir {}", target.display())));
+        assert_eq!(cast.calls()[1].answered, ["private key", "password"]);
+
+        let exported = result.unwrap();
+        assert_eq!((exported.len(), exported[0].keystore.as_str(), exported[0].address.as_str()), (1, "exported", ADDRESS));
+        let path = Path::new(&exported[0].path);
+        assert_eq!(path.parent(), Some(target.as_path()));
+
+        // cast's keystore, with the metadata it doesn't write
+        let json: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        let expected: Value = serde_json::from_str(FIXTURE_KEYSTORE).unwrap();
+        assert_eq!(json["crypto"], expected["crypto"]);
+        assert_eq!(json["address"], "f39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+        assert_eq!(json["x-ethers"]["gethFilename"], path.file_name().unwrap().to_str().unwrap());
+        #[cfg(unix)]
+        {
+          use std::os::unix::fs::PermissionsExt;
+          assert_eq!(fs::metadata(path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let _ = fs::remove_dir_all(&target);
+      }
+    }
+
+    #[tokio::test]
+    async fn reports_cast_errors_and_cancellation() {
+      let _serial = serial().await;
+      let target = temp_dir("export-errors");
+      let failing = FakeCast::recorded().on(&["wallet", "import"], FakeResponse::output("")
+        .prompt("Enter private key:")
+        .rejecting(FIXTURE_PRIVATE_KEY, "Error: Keystore file already exists\n"));
+      let cancel = CancellationToken::new();
+      cancel.cancel();
+
+      session::unlock("exported", ADDRESS.to_string(), Password::new("pw"), SecretString::new(FIXTURE_PRIVATE_KEY));
+      let failed = export(&failing, &target, Some("new"), CancellationToken::new()).await;
+      let cancelled = export(&FakeCast::recorded(), &target, Some("new"), cancel).await;
+      session::lock("exported");
+
+      assert_eq!(failed.unwrap_err(), "Error: Keystore file already exists");
+      assert_eq!(cancelled.unwrap_err(), "Failed to execute cast wallet import command: Operation was cancelled");
+      let _ = fs::remove_dir_all(&target);
+    }
+  }
+}
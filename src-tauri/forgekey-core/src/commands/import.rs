@@ -0,0 +1,175 @@
+use std::path::Path;
+use std::time::Duration;
+use crate::cast::{parse, version, CastExecutor};
+use crate::utils::redact_secrets;
+use crate::models::{Password, SecretString};
+use crate::commands::password_pipe::SecretPipe;
+use crate::commands::pty_error_message;
+use crate::pty::{ExpectScript, PtyConfig};
+use log::error;
+use tokio_util::sync::CancellationToken;
+
+// Add a new function that accepts a Password object directly
+pub async fn import_wallet(cast: &dyn CastExecutor, private_key: SecretString, address_label: String, password: Password, cancel: CancellationToken) -> Result<String, String> {
+  import_wallet_into(cast, private_key, address_label, password, None, cancel).await
+}
+
+/// `import_wallet` into `keystore_dir` instead of Foundry's keystore directory
+pub(crate) async fn import_wallet_into(
+  cast: &dyn CastExecutor,
+  private_key: SecretString,
+  address_label: String,
+  password: Password,
+  keystore_dir: Option<&Path>,
+  cancel: CancellationToken,
+) -> Result<String, String> {
+  version::supported(cast).await?;
+
+  // Use PTY-based input for security: neither the private key nor the password
+  // is visible in the process list. With --interactive cast prompts for the
+  // private key first, then for the password and its confirmation
+  let config = PtyConfig {
+    timeout: Duration::from_secs(30),
+    cancel,
+    ..Default::default()
+  };
+
+  let script = ExpectScript::new()
+    .expect("private key", r"(?i)private key:\s*$", &private_key)
+    .expect("password", r"(?i)password[^\n]*:\s*$", &password)
+    .expect_optional("password confirmation", r"(?i)password[^\n]*:\s*$", &password)
+    .fail_on(r"(?m)^Error:");
+
+  let mut args = vec!["wallet", "import", &address_label, "--interactive"];
+  let keystore_dir = keystore_dir.map(|dir| dir.to_string_lossy());
+  if let Some(dir) = &keystore_dir {
+    args.extend(["--keystore-dir", dir]);
+  }
+
+  let result = cast.run_script(&args, &script, &config).await;
+
+  // Zeroize the private key as soon as we don't need it anymore
+  drop(private_key);
+
+  let pty_result = result.map_err(|e| pty_error_message("cast wallet import", e))?;
+
+  if !pty_result.success() {
+    let err_msg = redact_secrets(&pty_result.output);
+    error!("Failed to import wallet for {}: {}", address_label, err_msg);
+    return Err(err_msg);
+  }
+
+  parse::address(&pty_result.output)
+}
+
+/// Import the first account of `mnemonic` (`m/44'/60'/0'/0/0`). cast only
+/// reads a mnemonic from the command line or a file, so it gets a
+/// `SecretPipe` and the password through the PTY.
+pub(crate) async fn import_mnemonic(
+  cast: &dyn CastExecutor,
+  mnemonic: &SecretString,
+  address_label: String,
+  password: Password,
+  cancel: CancellationToken,
+) -> Result<String, String> {
+  version::supported(cast).await?;
+
+  let config = PtyConfig {
+    timeout: Duration::from_secs(30),
+    cancel,
+    ..Default::default()
+  };
+  let script = ExpectScript::new()
+    .expect("password", r"(?i)password[^\n]*:\s*$", &password)
+    .expect_optional("password confirmation", r"(?i)password[^\n]*:\s*$", &password)
+    .fail_on(r"(?m)^Error:");
+
+  let mnemonic_pipe = SecretPipe::create(mnemonic)?;
+  let mnemonic_path = mnemonic_pipe.path().to_string_lossy();
+  let args = ["wallet", "import", "--mnemonic", &mnemonic_path, "--mnemonic-index", "0", &address_label];
+  let result = cast.run_script(&args, &script, &config).await;
+  drop(mnemonic_pipe);
+
+  let pty_result = result.map_err(|e| pty_error_message("cast wallet import", e))?;
+
+  if !pty_result.success() {
+    let err_msg = redact_secrets(&pty_result.output);
+    error!("Failed to import mnemonic for {}: {}", address_label, err_msg);
+    return Err(err_msg);
+  }
+
+  parse::address(&pty_result.output)
+}
+
+#[cfg(all(test, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS, FIXTURE_MNEMONIC, FIXTURE_PRIVATE_KEY};
+  use crate::commands::testing::{command_lines, recorded_versions, serial};
+
+  async fn import(cast: &FakeCast, cancel: CancellationToken) -> Result<String, String> {
+    import_wallet(cast, SecretString::new(FIXTURE_PRIVATE_KEY), "imported".to_string(), Password::new("pw"), cancel).await
+  }
+
+  #[tokio::test]
+  async fn imports_through_the_prompts() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      assert_eq!(import(&cast, CancellationToken::new()).await.unwrap(), FIXTURE_ADDRESS);
+      assert_eq!(command_lines(&cast), ["--version", "wallet import imported --interactive"]);
+      assert_eq!(cast.calls()[1].answered, ["private key", "password"]);
+
+      let dir = Path::new("/tmp/keystores");
+      let address = import_wallet_into(&cast, SecretString::new(FIXTURE_PRIVATE_KEY), "copy".to_string(), Password::new("pw"), Some(dir), CancellationToken::new())
+        .await
+        .unwrap();
+      assert_eq!(address, FIXTURE_ADDRESS);
+      assert_eq!(command_lines(&cast)[2], "wallet import copy --interactive --keystore-dir /tmp/keystores");
+    }
+  }
+
+  #[tokio::test]
+  async fn imports_mnemonics_from_a_file() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      let address = import_mnemonic(&cast, &SecretString::new(FIXTURE_MNEMONIC), "seeded".to_string(), Password::new("pw"), CancellationToken::new())
+        .await
+        .unwrap();
+      assert_eq!(address, FIXTURE_ADDRESS);
+
+      let call = cast.calls().pop().unwrap();
+      assert_eq!(call.args[..3], ["wallet", "import", "--mnemonic"]);
+      assert_eq!(call.args[4..], ["--mnemonic-index", "0", "seeded"]);
+      assert_eq!(call.answered, ["password"]);
+      assert!(!Path::new(&call.args[3]).exists());
+    }
+  }
+
+  #[tokio::test]
+  async fn reports_cast_errors() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      let cast = cast.on(&["wallet", "import"], FakeResponse::output("`imported` keystore was saved successfully.\n")
+        .prompt("Enter private key:")
+        .rejecting(FIXTURE_PRIVATE_KEY, "Error: Keystore file `imported` already exists\n"));
+      let err = import(&cast, CancellationToken::new()).await.unwrap_err();
+      assert_eq!(err, "Error: Keystore file `imported` already exists");
+    }
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn stops_when_cancelled_or_stuck() {
+    let _serial = serial().await;
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let err = import(&FakeCast::recorded(), cancel).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet import command: Operation was cancelled");
+
+    let cast = FakeCast::recorded().on(&["wallet", "import"], FakeResponse::output("").prompt("Enter the secret phrase"));
+    let err = import(&cast, CancellationToken::new()).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet import command: Operation timed out");
+  }
+}
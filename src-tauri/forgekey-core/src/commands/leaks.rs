@@ -0,0 +1,589 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use crate::cast::{parse, version, CastExecutor};
+use crate::commands::import::import_mnemonic;
+use crate::commands::password_pipe::SecretPipe;
+use crate::commands::{import_wallet, keystore_address, list_wallets, pty_error_message};
+use crate::models::{Password, SecretString};
+use crate::pty::{ExpectScript, PtyConfig};
+use crate::utils::redact_secrets;
+use log::{error, info, warn};
+use regex::Regex;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+use zeroize::Zeroize;
+
+// Finds private keys and mnemonics left in plain text in a project, the
+// `PRIVATE_KEY=0x...` in .env that should be a keystore instead. Files
+// ignored by git are skipped, except .env files since that is where keys
+// usually end up. Keys never leave the backend: findings carry the derived
+// address and the line with the secret redacted, and `import_leaked_key`
+// reads the key from the file again.
+
+/// Larger files are generated or data, not configuration
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Directories skipped when the project is not a git repository
+const SKIPPED_DIRS: [&str; 8] = [".git", "node_modules", "lib", "out", "cache", "target", "artifacts", "broadcast"];
+
+const SCANNED_EXTENSIONS: [&str; 17] = [
+  "env", "toml", "json", "yaml", "yml", "ini", "cfg", "conf", "js", "cjs", "mjs", "ts", "sol", "py", "sh", "bash", "zsh",
+];
+
+const SCANNED_NAMES: [&str; 6] = ["Makefile", "makefile", "GNUmakefile", "justfile", "Justfile", ".justfile"];
+
+pub(crate) static PRIVATE_KEY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:0x)?[0-9a-fA-F]{64}\b").unwrap());
+/// Outside .env files 32 byte hex values are mostly hashes and salts, only
+/// lines that look like they hold a key count
+static KEY_LABEL: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"(?i)priv|secret|key|\bpk\b|signer|deployer|wallet|account|owner|admin").unwrap());
+static MNEMONIC_LABEL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)mnemonic|seed|phrase").unwrap());
+static MNEMONIC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[a-z]{3,8}(?:[ \t]+[a-z]{3,8}){11,23}\b").unwrap());
+/// Flags picking an account of a mnemonic, `--mnemonic-index 0` picks the
+/// first one like ForgeKey does
+static MNEMONIC_ACCOUNT_FLAG: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r#"\s+--(mnemonic-indexes|mnemonic-index|mnemonic-derivation-path|hd-path)(?:\s+|=)(?:"([^"]*)"|'([^']*)'|(\S+))"#).unwrap()
+});
+static VARIABLE: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r#"^\s*(?:export\s+)?["']?([A-Za-z_][A-Za-z0-9_.-]*)["']?\s*[:=]"#).unwrap());
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LeakKind {
+  PrivateKey,
+  Mnemonic,
+}
+
+/// A secret found in plain text. The secret itself is never included.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeakedKey {
+  /// Path relative to the scanned directory
+  pub path: String,
+  /// 1-based line number
+  pub line: usize,
+  /// Index of the secret among those on the same line
+  pub position: usize,
+  /// The line with the secret replaced by `[REDACTED]`
+  pub excerpt: String,
+  /// Variable or key the secret is assigned to, e.g. `PRIVATE_KEY`
+  pub variable: Option<String>,
+  pub kind: LeakKind,
+  /// Address of the private key, or of the first account of the mnemonic,
+  /// derived by cast
+  pub address: Option<String>,
+  /// Keystore with the same address, if ForgeKey already has the key
+  pub keystore: Option<String>,
+}
+
+/// Outcome of `import_leaked_key`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeakFix {
+  /// Keystore now holding the key, newly imported or already there
+  pub keystore: String,
+  pub address: String,
+  pub imported: bool,
+  /// Whether the line was replaced with a reference to the keystore. Only
+  /// `KEY=0x...` in .env files and `--private-key 0x...` are rewritten.
+  pub rewritten: bool,
+}
+
+/// Scan `dir` for plain text private keys and mnemonics
+pub async fn scan_for_plaintext_keys(cast: &dyn CastExecutor, dir: &Path, cancel: CancellationToken) -> Result<Vec<LeakedKey>, String> {
+  if !dir.is_dir() {
+    let err_msg = format!("{} is not a directory", dir.display());
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+
+  let keystores = keystore_addresses(cast).await?;
+  let mut findings = Vec::new();
+
+  for path in candidate_files(dir) {
+    if cancel.is_cancelled() {
+      let err_msg = "Scan was cancelled".to_string();
+      error!("{}", err_msg);
+      return Err(err_msg);
+    }
+    let Some(mut content) = read_small_file(&dir.join(&path)) else {
+      continue;
+    };
+    let env_file = is_env_file(&path);
+
+    for (index, line) in content.lines().enumerate() {
+      for (position, secret) in find_secrets(line, env_file).into_iter().enumerate() {
+        // The first account's address would be misleading for another one
+        let address = match secret {
+          Secret::Mnemonic(_) if picks_other_account(line) => None,
+          _ => secret_address(cast, secret, cancel.clone()).await.ok(),
+        };
+        let excerpt = match secret {
+          Secret::PrivateKey(_) => redact_secrets(line),
+          Secret::Mnemonic(mnemonic) => line.replace(mnemonic, "[REDACTED]"),
+        };
+        findings.push(LeakedKey {
+          path: path.to_string_lossy().into_owned(),
+          line: index + 1,
+          position,
+          excerpt: excerpt.trim().to_string(),
+          variable: VARIABLE.captures(line).map(|captures| captures[1].to_string()),
+          kind: secret.kind(),
+          keystore: address.as_deref().and_then(|address| keystore_for(&keystores, address)),
+          address,
+        });
+      }
+    }
+    content.zeroize();
+  }
+
+  info!("Found {} plain text keys in {}", findings.len(), dir.display());
+  Ok(findings)
+}
+
+/// Import the private key or mnemonic at `position` on `line` of `path` (as
+/// reported by `scan_for_plaintext_keys`) as `keystore_name`, or reuse the
+/// keystore that already has it, then replace the line with a reference to
+/// the keystore: `ETH_KEYSTORE_ACCOUNT=<keystore>` in .env files,
+/// `--account <keystore>` in place of `--private-key <key>` or
+/// `--mnemonic <phrase>` elsewhere. Lines with several secrets are left as
+/// they are. A mnemonic is imported as its first account, lines picking
+/// another one are refused.
+#[allow(clippy::too_many_arguments)]
+pub async fn import_leaked_key(
+  cast: &dyn CastExecutor,
+  dir: &Path,
+  path: &str,
+  line: usize,
+  position: usize,
+  keystore_name: String,
+  password: Password,
+  cancel: CancellationToken,
+) -> Result<LeakFix, String> {
+  let relative = Path::new(path);
+  if relative.components().any(|component| !matches!(component, Component::Normal(_))) {
+    let err_msg = format!("Invalid path {:?}", path);
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+  let file = dir.join(relative);
+
+  let mut content = fs::read_to_string(&file).map_err(|e| {
+    let err_msg = format!("Failed to read {}: {}", file.display(), e);
+    error!("{}", err_msg);
+    err_msg
+  })?;
+  let result = fix_line(cast, &file, &content, line, position, keystore_name, password, cancel).await;
+  content.zeroize();
+  result
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn fix_line(
+  cast: &dyn CastExecutor,
+  file: &Path,
+  content: &str,
+  line: usize,
+  position: usize,
+  keystore_name: String,
+  password: Password,
+  cancel: CancellationToken,
+) -> Result<LeakFix, String> {
+  let text = line.checked_sub(1).and_then(|index| content.lines().nth(index)).unwrap_or_default();
+  let secrets = find_secrets(text, is_env_file(file));
+  let Some(secret) = secrets.get(position).copied() else {
+    let err_msg = format!("No private key or mnemonic on line {} of {}, was the file changed?", line, file.display());
+    error!("{}", err_msg);
+    return Err(err_msg);
+  };
+  if matches!(secret, Secret::Mnemonic(_)) && picks_other_account(text) {
+    let err_msg = format!(
+      "Line {} of {} uses another account of the mnemonic than the first, import it with cast instead",
+      line,
+      file.display()
+    );
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+
+  let address = secret_address(cast, secret, cancel.clone()).await?;
+  let keystores = keystore_addresses(cast).await?;
+  let (keystore, imported) = match keystore_for(&keystores, &address) {
+    Some(existing) => (existing, false),
+    None => {
+      match secret {
+        Secret::PrivateKey(key) => import_wallet(cast, SecretString::new(key), keystore_name.clone(), password, cancel).await?,
+        Secret::Mnemonic(mnemonic) => {
+          import_mnemonic(cast, &SecretString::new(mnemonic), keystore_name.clone(), password, cancel).await?
+        }
+      };
+      (keystore_name, true)
+    }
+  };
+
+  // Rewriting a line with several secrets would lose the others
+  let replacement = if secrets.len() == 1 { rewrite_line(text, secret.as_str(), &keystore, is_env_file(file)) } else { None };
+  let Some(mut replacement) = replacement else {
+    warn!("Imported the key on line {} of {}, but left the line as is", line, file.display());
+    return Ok(LeakFix { keystore, address, imported, rewritten: false });
+  };
+
+  // Keep every other line, including its line ending, as it was
+  let mut rewritten = String::with_capacity(content.len());
+  for (index, original) in content.split_inclusive('\n').enumerate() {
+    if index + 1 == line {
+      rewritten.push_str(&replacement);
+      rewritten.push_str(&original[original.trim_end_matches(['\r', '\n']).len()..]);
+    } else {
+      rewritten.push_str(original);
+    }
+  }
+  let written = fs::write(file, &rewritten);
+  rewritten.zeroize();
+  replacement.zeroize();
+
+  written.map_err(|e| {
+    let err_msg = format!("Imported the key as {}, but failed to update {}: {}", keystore, file.display(), e);
+    error!("{}", err_msg);
+    err_msg
+  })?;
+
+  info!("Replaced the private key on line {} of {} with keystore {}", line, file.display(), keystore);
+  Ok(LeakFix { keystore, address, imported, rewritten: true })
+}
+
+/// The line with the key replaced by a reference to `keystore`, if it has a
+/// form we know how to rewrite
+fn rewrite_line(text: &str, key: &str, keystore: &str, env_file: bool) -> Option<String> {
+  let start = text.find(key)?;
+  let end = start + key.len();
+
+  // --private-key 0x..., --private-key=0x... or --mnemonic "word ..."
+  let before = text[..start].trim_end_matches(['"', '\'']).trim_end_matches([' ', '=']);
+  if let Some(flag) = before.strip_suffix("--private-key") {
+    let after = text[end..].strip_prefix(['"', '\'']).unwrap_or(&text[end..]);
+    return Some(format!("{}--account {}{}", flag, keystore, after));
+  }
+  // The keystore is the first account, so `--mnemonic-index 0` goes too
+  if let Some(flag) = before.strip_suffix("--mnemonic") {
+    let after = text[end..].strip_prefix(['"', '\'']).unwrap_or(&text[end..]);
+    let prefix = MNEMONIC_ACCOUNT_FLAG.replace_all(flag, "");
+    let after = MNEMONIC_ACCOUNT_FLAG.replace_all(after, "");
+    return Some(format!("{}--account {}{}", prefix, keystore, after));
+  }
+
+  // PRIVATE_KEY=0x... in .env files, the whole assignment is replaced
+  if env_file && VARIABLE.is_match(text) {
+    let export = if text.trim_start().starts_with("export ") { "export " } else { "" };
+    return Some(format!("{}ETH_KEYSTORE_ACCOUNT={}", export, keystore));
+  }
+
+  None
+}
+
+/// Whether `text` picks an account of its mnemonic other than the first,
+/// which is the only one ForgeKey imports
+fn picks_other_account(text: &str) -> bool {
+  MNEMONIC_ACCOUNT_FLAG.captures_iter(text).any(|captures| {
+    let value = captures.get(2).or(captures.get(3)).or(captures.get(4)).map_or("", |value| value.as_str());
+    !(captures[1].starts_with("mnemonic-index") && value == "0")
+  })
+}
+
+/// Address of a private key, using cast's prompt so it isn't on the command line
+pub(crate) async fn derive_address(cast: &dyn CastExecutor, private_key: &SecretString, cancel: CancellationToken) -> Result<String, String> {
+  version::supported(cast).await?;
+
+  let config = PtyConfig { cancel, ..Default::default() };
+  let script = ExpectScript::new()
+    .expect("private key", r"(?i)private key:\s*$", private_key)
+    .fail_on(r"(?m)^Error:");
+
+  let result = cast
+    .run_script(&["wallet", "address", "--interactive"], &script, &config)
+    .await
+    .map_err(|e| pty_error_message("cast wallet address", e))?;
+
+  if !result.success() {
+    let err_msg = redact_secrets(&result.output);
+    error!("Failed to derive the address of a private key: {}", err_msg);
+    return Err(err_msg);
+  }
+
+  parse::address(&result.output)
+}
+
+pub(crate) async fn keystore_addresses(cast: &dyn CastExecutor) -> Result<Vec<(String, String)>, String> {
+  Ok(list_wallets(cast)
+    .await?
+    .into_iter()
+    .filter_map(|name| keystore_address(&name).map(|address| (name, address)))
+    .collect())
+}
+
+pub(crate) fn keystore_for(keystores: &[(String, String)], address: &str) -> Option<String> {
+  keystores
+    .iter()
+    .find(|(_, keystore_address)| keystore_address.eq_ignore_ascii_case(address))
+    .map(|(name, _)| name.clone())
+}
+
+/// Address of the first account of a mnemonic. cast only reads a mnemonic
+/// from the command line or a file, so it gets a `SecretPipe`.
+async fn mnemonic_address(cast: &dyn CastExecutor, mnemonic: &SecretString, cancel: CancellationToken) -> Result<String, String> {
+  version::supported(cast).await?;
+
+  let config = PtyConfig { cancel, ..Default::default() };
+  let script = ExpectScript::new().fail_on(r"(?m)^Error:");
+
+  let mnemonic_pipe = SecretPipe::create(mnemonic)?;
+  let mnemonic_path = mnemonic_pipe.path().to_string_lossy();
+  let result = cast
+    .run_script(&["wallet", "address", "--mnemonic", &mnemonic_path, "--mnemonic-index", "0"], &script, &config)
+    .await;
+  drop(mnemonic_pipe);
+
+  let result = result.map_err(|e| pty_error_message("cast wallet address", e))?;
+  if !result.success() {
+    let err_msg = redact_secrets(&result.output);
+    error!("Failed to derive the address of a mnemonic: {}", err_msg);
+    return Err(err_msg);
+  }
+
+  parse::address(&result.output)
+}
+
+/// A secret found on a line, borrowed from the file content
+#[derive(Clone, Copy)]
+enum Secret<'a> {
+  PrivateKey(&'a str),
+  Mnemonic(&'a str),
+}
+
+impl<'a> Secret<'a> {
+  fn kind(&self) -> LeakKind {
+    match self {
+      Secret::PrivateKey(_) => LeakKind::PrivateKey,
+      Secret::Mnemonic(_) => LeakKind::Mnemonic,
+    }
+  }
+
+  fn as_str(&self) -> &'a str {
+    match self {
+      Secret::PrivateKey(secret) | Secret::Mnemonic(secret) => secret,
+    }
+  }
+}
+
+async fn secret_address(cast: &dyn CastExecutor, secret: Secret<'_>, cancel: CancellationToken) -> Result<String, String> {
+  match secret {
+    Secret::PrivateKey(key) => derive_address(cast, &SecretString::new(key), cancel).await,
+    Secret::Mnemonic(mnemonic) => mnemonic_address(cast, &SecretString::new(mnemonic), cancel).await,
+  }
+}
+
+/// Every private key on a line, in order, or else its mnemonic
+fn find_secrets(line: &str, env_file: bool) -> Vec<Secret<'_>> {
+  let keys: Vec<Secret> = PRIVATE_KEY
+    .find_iter(line)
+    .filter(|key| env_file || KEY_LABEL.is_match(&line[..key.start()]))
+    .map(|key| Secret::PrivateKey(key.as_str()))
+    .collect();
+  if !keys.is_empty() {
+    return keys;
+  }
+  find_mnemonic(line).map(Secret::Mnemonic).into_iter().collect()
+}
+
+/// 12 to 24 lowercase words on a line that mentions a mnemonic or seed
+fn find_mnemonic(line: &str) -> Option<&str> {
+  if !MNEMONIC_LABEL.is_match(line) {
+    return None;
+  }
+  MNEMONIC
+    .find_iter(line)
+    .map(|mnemonic| mnemonic.as_str())
+    .find(|mnemonic| [12, 15, 18, 21, 24].contains(&mnemonic.split_whitespace().count()))
+}
+
+pub(crate) fn is_env_file(path: &Path) -> bool {
+  path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name == ".env" || name.starts_with(".env."))
+}
+
+fn is_scanned(path: &Path) -> bool {
+  let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+  is_env_file(path)
+    || SCANNED_NAMES.contains(&name)
+    || path.extension().and_then(|extension| extension.to_str()).is_some_and(|extension| SCANNED_EXTENSIONS.contains(&extension))
+}
+
+/// UTF-8 files up to `MAX_FILE_SIZE`
+pub(crate) fn read_small_file(path: &Path) -> Option<String> {
+  let metadata = fs::metadata(path).ok()?;
+  if !metadata.is_file() || metadata.len() > MAX_FILE_SIZE {
+    return None;
+  }
+  fs::read_to_string(path).ok()
+}
+
+/// Files to scan, relative to `dir`. In a git repository these are the files
+/// git doesn't ignore plus ignored .env files, elsewhere everything outside
+/// `SKIPPED_DIRS`.
+fn candidate_files(dir: &Path) -> Vec<PathBuf> {
+  let files = match git_files(dir) {
+    Some(files) => files,
+    None => {
+      let mut files = Vec::new();
+      walk(dir, Path::new(""), &mut files);
+      files
+    }
+  };
+
+  let mut files: Vec<PathBuf> = files.into_iter().filter(|path| is_scanned(path)).collect();
+  files.sort();
+  files.dedup();
+  files
+}
+
+fn git_files(dir: &Path) -> Option<Vec<PathBuf>> {
+  let list = |args: &[&str]| -> Option<Vec<PathBuf>> {
+    let output = Command::new("git")
+      .arg("-C")
+      .arg(dir)
+      .args(["ls-files", "-z"])
+      .args(args)
+      .stdin(Stdio::null())
+      .stderr(Stdio::null())
+      .output()
+      .ok()
+      .filter(|output| output.status.success())?;
+    Some(
+      output
+        .stdout
+        .split(|b| *b == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| PathBuf::from(String::from_utf8_lossy(path).into_owned()))
+        .collect(),
+    )
+  };
+
+  let mut files = list(&["--cached", "--others", "--exclude-standard"])?;
+  // Ignored directories are listed as a whole, only top-level ignored files
+  // of any directory come through individually
+  files.extend(list(&["--others", "--ignored", "--exclude-standard", "--directory"])?.into_iter().filter(|path| is_env_file(path)));
+  Some(files)
+}
+
+fn walk(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) {
+  let Ok(entries) = fs::read_dir(root.join(relative)) else {
+    return;
+  };
+  for entry in entries.flatten() {
+    let path = relative.join(entry.file_name());
+    match entry.file_type() {
+      Ok(file_type) if file_type.is_dir() && !SKIPPED_DIRS.iter().any(|skipped| entry.file_name() == *skipped) => {
+        walk(root, &path, files);
+      }
+      Ok(file_type) if file_type.is_file() => files.push(path),
+      _ => {}
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+  const OTHER_KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+  const MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+  #[test]
+  fn finds_every_key_on_a_line() {
+    let line = format!("deployer: {}, owner: {}", KEY, OTHER_KEY);
+    let secrets: Vec<&str> = find_secrets(&line, false).iter().map(|secret| secret.as_str()).collect();
+    assert_eq!(secrets, [KEY, OTHER_KEY]);
+
+    // Unlabelled values outside .env are hashes, not keys
+    assert!(find_secrets(&format!("salt = {}", KEY), false).is_empty());
+    assert_eq!(find_secrets(&format!("SALT={}", KEY), true).len(), 1);
+  }
+
+  #[test]
+  fn finds_mnemonics() {
+    let line = format!("MNEMONIC=\"{}\"", MNEMONIC);
+    let secrets: Vec<(LeakKind, &str)> = find_secrets(&line, true).iter().map(|secret| (secret.kind(), secret.as_str())).collect();
+    assert_eq!(secrets, [(LeakKind::Mnemonic, MNEMONIC)]);
+    assert!(find_secrets(MNEMONIC, true).is_empty());
+  }
+
+  #[test]
+  fn rewrites_flags_and_env_assignments() {
+    let line = format!("cast send --private-key {} $TO", KEY);
+    assert_eq!(rewrite_line(&line, KEY, "dev", false).unwrap(), "cast send --account dev $TO");
+    let line = format!("forge script --mnemonic \"{}\" --broadcast", MNEMONIC);
+    assert_eq!(rewrite_line(&line, MNEMONIC, "dev", false).unwrap(), "forge script --account dev --broadcast");
+    let line = format!("export PRIVATE_KEY={}", KEY);
+    assert_eq!(rewrite_line(&line, KEY, "dev", true).unwrap(), "export ETH_KEYSTORE_ACCOUNT=dev");
+    assert!(rewrite_line(&format!("key = {}", KEY), KEY, "dev", false).is_none());
+
+    let line = format!("forge script --mnemonic-index 0 --mnemonic \"{}\" --mnemonic-index=0 --broadcast", MNEMONIC);
+    assert_eq!(rewrite_line(&line, MNEMONIC, "dev", false).unwrap(), "forge script --account dev --broadcast");
+  }
+
+  #[test]
+  fn spots_other_mnemonic_accounts() {
+    assert!(!picks_other_account(&format!("--mnemonic \"{}\"", MNEMONIC)));
+    assert!(!picks_other_account(&format!("--mnemonic \"{}\" --mnemonic-index 0", MNEMONIC)));
+    assert!(picks_other_account(&format!("--mnemonic \"{}\" --mnemonic-index 3", MNEMONIC)));
+    assert!(picks_other_account(&format!("--mnemonic \"{}\" --mnemonic-indexes=0,1", MNEMONIC)));
+    assert!(picks_other_account(&format!("--mnemonic \"{}\" --mnemonic-derivation-path \"m/44'/60'/0'/0/0\"", MNEMONIC)));
+  }
+
+  #[cfg(feature = "fake-cast")]
+  #[tokio::test]
+  async fn scans_and_imports_mnemonics() {
+    use crate::cast::fake::{FakeCast, FIXTURE_ADDRESS};
+    use crate::commands::testing::{serial, temp_dir};
+
+    let _serial = serial().await;
+    let dir = temp_dir("leaks");
+    fs::write(dir.join(".env"), format!("RPC_URL=http://localhost:8545\nMNEMONIC=\"{}\"\nKEYS={} {}\n", MNEMONIC, KEY, OTHER_KEY)).unwrap();
+
+    let cast = FakeCast::recorded();
+    let findings = scan_for_plaintext_keys(&cast, &dir, CancellationToken::new()).await.unwrap();
+    let found: Vec<(usize, usize, LeakKind)> = findings.iter().map(|leak| (leak.line, leak.position, leak.kind)).collect();
+    assert_eq!(found, [(2, 0, LeakKind::Mnemonic), (3, 0, LeakKind::PrivateKey), (3, 1, LeakKind::PrivateKey)]);
+    assert_eq!(findings[0].address.as_deref(), Some(FIXTURE_ADDRESS));
+    assert_eq!(findings[0].excerpt, "MNEMONIC=\"[REDACTED]\"");
+
+    let fix = import_leaked_key(&cast, &dir, ".env", 2, 0, "leaked".to_string(), Password::new("pw"), CancellationToken::new())
+      .await
+      .unwrap();
+    assert!(fix.imported && fix.rewritten);
+    assert_eq!(fix.address, FIXTURE_ADDRESS);
+
+    let calls = cast.calls();
+    let import = calls.iter().find(|call| call.args.starts_with(&["wallet".to_string(), "import".to_string()])).unwrap();
+    assert_eq!(import.args[2], "--mnemonic");
+    assert_eq!(import.args.last().unwrap(), "leaked");
+    assert_eq!(import.answered, ["password"]);
+    // The phrase was only ever in a one-shot pipe, gone by now
+    assert!(!Path::new(&import.args[3]).exists());
+    assert!(calls.iter().all(|call| !call.args.iter().any(|arg| arg.contains("junk"))));
+
+    let content = fs::read_to_string(dir.join(".env")).unwrap();
+    assert_eq!(content.lines().nth(1), Some("ETH_KEYSTORE_ACCOUNT=leaked"));
+
+    // The key is already a keystore, but the line keeps the other key
+    let fix = import_leaked_key(&cast, &dir, ".env", 3, 0, "other".to_string(), Password::new("pw"), CancellationToken::new())
+      .await
+      .unwrap();
+    assert!(!fix.rewritten);
+    let content = fs::read_to_string(dir.join(".env")).unwrap();
+    assert_eq!(content.lines().nth(2), Some(format!("KEYS={} {}", KEY, OTHER_KEY).as_str()));
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
@@ -0,0 +1,122 @@
+use crate::cast::{parse, version, CastExecutor, OUTPUT_TIMEOUT};
+use crate::utils::redact_secrets;
+use crate::models::Password;
+use crate::pty::PtyConfig;
+use crate::session;
+use crate::commands::{locked_error, pty_error_message};
+use log::error;
+use tokio_util::sync::CancellationToken;
+
+pub async fn list_wallets(cast: &dyn CastExecutor) -> Result<Vec<String>, String> {
+  version::supported(cast).await?;
+
+  let output = cast.output(
+    &["wallet", "list", "--dir"], // todo: should we use --all in the future?
+    OUTPUT_TIMEOUT,
+    &CancellationToken::new(),
+  ).await.map_err(|e| pty_error_message("cast wallet list", e))?;
+
+  if !output.success() {
+    let err_msg = output.stderr;
+    error!("Failed to list wallets: {}", err_msg);
+    return Err(err_msg);
+  }
+
+  Ok(parse::wallet_list(&output.stdout))
+}
+
+/// Get the address of a keystore.
+/// Without a password the keystore must already be unlocked (see `unlock_keystore`).
+pub async fn get_wallet_address(cast: &dyn CastExecutor, keystore_name: &str, password: Option<&str>, cancel: CancellationToken) -> Result<String, String> {
+  let Some(password) = password else {
+    return session::with_unlocked(keystore_name, |session| session.address.clone())
+      .ok_or_else(|| locked_error(keystore_name));
+  };
+
+  // Convert the password to our secure Password type
+  let password = Password::new(password);
+
+  version::supported(cast).await?;
+
+  // Use PTY-based password input for security (password not visible in process list)
+  let config = PtyConfig { cancel, ..Default::default() }; // Single password prompt
+  let result = cast.run_with_password(
+    &["wallet", "address", "--account", keystore_name],
+    &password,
+    &config,
+  ).await.map_err(|e| pty_error_message("cast wallet address", e))?;
+
+  if !result.success() {
+    let err_msg = redact_secrets(&result.output);
+    error!("Failed to get wallet address: {}", err_msg);
+    return Err(err_msg);
+  }
+
+  parse::address(&result.output)
+}
+
+#[cfg(all(test, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY, FIXTURE_WRONG_PASSWORD};
+  use crate::commands::testing::{command_lines, recorded_versions, serial};
+  use crate::models::SecretString;
+
+  #[tokio::test]
+  async fn lists_keystores() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      assert_eq!(list_wallets(&cast).await.unwrap(), ["fixture"]);
+      assert_eq!(command_lines(&cast), ["--version", "wallet list --dir"]);
+
+      let cast = cast.on(&["wallet", "list"], FakeResponse::failure("Error: failed to read the keystore directory\n", 1));
+      assert_eq!(list_wallets(&cast).await.unwrap_err(), "Error: failed to read the keystore directory\n");
+    }
+  }
+
+  #[tokio::test]
+  async fn gets_addresses_with_a_password() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      let address = get_wallet_address(&cast, "listed", Some("pw"), CancellationToken::new()).await.unwrap();
+      assert_eq!(address, FIXTURE_ADDRESS);
+      assert_eq!(command_lines(&cast)[1], "wallet address --account listed");
+
+      let err = get_wallet_address(&cast, "listed", Some(FIXTURE_WRONG_PASSWORD), CancellationToken::new()).await.unwrap_err();
+      assert_eq!(err, "Incorrect password");
+
+      let cast = cast.on(&["wallet", "address"], FakeResponse::failure("Error: Keystore file `listed` not found\n", 1));
+      let err = get_wallet_address(&cast, "listed", Some("pw"), CancellationToken::new()).await.unwrap_err();
+      assert_eq!(err, "Error: Keystore file `listed` not found");
+    }
+  }
+
+  #[tokio::test]
+  async fn gets_addresses_of_unlocked_keystores() {
+    let _serial = serial().await;
+    let cast = FakeCast::recorded();
+    let err = get_wallet_address(&cast, "listed", None, CancellationToken::new()).await.unwrap_err();
+    assert_eq!(err, "Keystore 'listed' is locked");
+
+    session::unlock("listed", FIXTURE_ADDRESS.to_string(), Password::new("pw"), SecretString::new(FIXTURE_PRIVATE_KEY));
+    let address = get_wallet_address(&cast, "listed", None, CancellationToken::new()).await;
+    session::lock("listed");
+    assert_eq!(address.unwrap(), FIXTURE_ADDRESS);
+    assert!(cast.calls().is_empty());
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn stops_when_cancelled_or_stuck() {
+    let _serial = serial().await;
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let err = get_wallet_address(&FakeCast::recorded(), "listed", Some("pw"), cancel).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet address command: Operation was cancelled");
+
+    let cast = FakeCast::recorded().on(&["wallet", "address"], FakeResponse::output("").prompt("Touch your security key"));
+    let err = get_wallet_address(&cast, "listed", Some("pw"), CancellationToken::new()).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet address command: Operation timed out");
+  }
+}
@@ -0,0 +1,638 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use crate::cast::CastExecutor;
+use crate::commands::{import_wallet, keystore_address, list_wallets, write_new_keystore};
+use crate::commands::leaks::{derive_address, is_env_file, read_small_file, PRIVATE_KEY};
+use crate::models::{Password, SecretString};
+use crate::utils::get_keystore_dir;
+use dirs::home_dir;
+use log::{debug, error, info};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use zeroize::Zeroize;
+
+// Accounts kept by other tools, brought over as Foundry keystores. geth, ape
+// and brownie store the same encrypted JSON keystores Foundry does, those are
+// copied as they are and keep their password. Hardhat configs and .env files
+// hold plain private keys, those are imported with `cast wallet import` under
+// a new password. Previews carry locations and addresses, never keys:
+// `import_account` reads a plain key from its file again.
+
+static HARDHAT_ACCOUNTS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\baccounts\s*:\s*\[").unwrap());
+/// `process.env.NAME`, `process.env["NAME"]`, `vars.get("NAME")` and
+/// `configVariable("NAME")`
+static HARDHAT_VARIABLE: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r#"process\.env\.([A-Za-z_][A-Za-z0-9_]*)|process\.env\[\s*["']([^"']+)["']\s*\]|(?:vars\.get|configVariable)\(\s*["']([^"']+)["']"#).unwrap()
+});
+
+const HARDHAT_CONFIGS: [&str; 4] = ["hardhat.config.ts", "hardhat.config.js", "hardhat.config.cjs", "hardhat.config.mjs"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccountFormat {
+  /// `UTC--<timestamp>--<address>` files of a geth keystore directory
+  Geth,
+  /// `~/.ape/accounts/<alias>.json`
+  Ape,
+  /// `~/.brownie/accounts/<id>.json`
+  Brownie,
+  /// Private keys in the `accounts` arrays of hardhat.config.*, written out
+  /// or taken from .env. Mnemonic accounts are not supported.
+  Hardhat,
+  /// `*PRIVATE_KEY*` entries of .env files
+  Env,
+}
+
+impl AccountFormat {
+  /// Where the tool keeps its accounts by default, for the formats that have
+  /// a global location
+  pub fn default_dir(self) -> Option<PathBuf> {
+    let home = home_dir()?;
+    match self {
+      AccountFormat::Geth if cfg!(target_os = "macos") => Some(home.join("Library").join("Ethereum").join("keystore")),
+      AccountFormat::Geth => Some(home.join(".ethereum").join("keystore")),
+      AccountFormat::Ape => Some(home.join(".ape").join("accounts")),
+      AccountFormat::Brownie => Some(home.join(".brownie").join("accounts")),
+      AccountFormat::Hardhat | AccountFormat::Env => None,
+    }
+  }
+}
+
+/// Whether a keystore already holds an account
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum DuplicateStatus {
+  /// `keystore` holds the same address
+  Duplicate { keystore: String },
+  /// No keystore holds the address
+  New,
+  /// No keystore with a known address holds it, but the addresses of the
+  /// `locked` keystores are unknown until they are unlocked: cast doesn't
+  /// write one into the keystores it imports
+  Unknown { locked: Vec<String> },
+}
+
+/// An account that can be imported, as listed before importing it
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCandidate {
+  pub format: AccountFormat,
+  /// Where it was found, e.g. "accounts/deployer.json" or ".env:3"
+  pub source: String,
+  /// File holding the keystore or the private key
+  pub path: String,
+  /// Line of a plain private key, with `position` telling keys on the same
+  /// line apart. None for encrypted keystores.
+  pub line: Option<usize>,
+  pub position: usize,
+  pub address: String,
+  /// Encrypted keystores keep their password, plain keys need a new one
+  pub encrypted: bool,
+  pub suggested_name: String,
+  pub duplicate: DuplicateStatus,
+  /// Whether a keystore named `suggested_name` exists already
+  pub name_taken: bool,
+}
+
+/// List the accounts `format` has under `path`, a directory or a single
+/// file. geth, ape and brownie default to their usual directory.
+pub async fn preview_account_import(
+  cast: &dyn CastExecutor,
+  format: AccountFormat,
+  path: Option<&Path>,
+  cancel: CancellationToken,
+) -> Result<Vec<ImportCandidate>, String> {
+  let path = match path.map(Path::to_path_buf).or_else(|| format.default_dir()) {
+    Some(path) if path.exists() => path,
+    Some(path) => {
+      let err_msg = format!("{} does not exist", path.display());
+      error!("{}", err_msg);
+      return Err(err_msg);
+    }
+    None => {
+      let err_msg = format!("A directory is needed to import {:?} accounts", format);
+      error!("{}", err_msg);
+      return Err(err_msg);
+    }
+  };
+
+  let keystore_dir = get_keystore_dir()?;
+  let keystores = known_addresses(cast).await?;
+  let mut candidates = match format {
+    AccountFormat::Geth | AccountFormat::Ape | AccountFormat::Brownie => encrypted_candidates(format, &path),
+    AccountFormat::Hardhat => plain_candidates(cast, format, hardhat_keys(&path)?, &cancel).await?,
+    AccountFormat::Env => plain_candidates(cast, format, env_keys(&path), &cancel).await?,
+  };
+
+  for candidate in &mut candidates {
+    candidate.duplicate = duplicate_status(&keystores, &candidate.address);
+    candidate.name_taken = keystore_dir.join(&candidate.suggested_name).exists();
+  }
+
+  info!("Found {} {:?} accounts in {}", candidates.len(), format, path.display());
+  Ok(candidates)
+}
+
+/// Import a candidate from `preview_account_import` as `keystore_name`.
+/// Encrypted keystores are copied into the keystore directory, plain keys
+/// are imported with `password`. Returns the address.
+pub async fn import_account(
+  cast: &dyn CastExecutor,
+  path: &Path,
+  line: Option<usize>,
+  position: usize,
+  keystore_name: String,
+  password: Option<Password>,
+  cancel: CancellationToken,
+) -> Result<String, String> {
+  let keystore_dir = get_keystore_dir()?;
+  let valid_name = !keystore_name.is_empty()
+    && !keystore_name.starts_with('.')
+    && !keystore_name.contains(['/', '\\']);
+  if !valid_name {
+    let err_msg = format!("Invalid keystore name {:?}", keystore_name);
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+  if keystore_dir.join(&keystore_name).exists() {
+    let err_msg = format!("A keystore named '{}' already exists", keystore_name);
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+
+  let Some(line) = line else {
+    return copy_keystore(cast, path, &keystore_dir, &keystore_name).await;
+  };
+
+  let Some(password) = password else {
+    let err_msg = "A password is needed to import a private key".to_string();
+    error!("{}", err_msg);
+    return Err(err_msg);
+  };
+  let mut content = fs::read_to_string(path).map_err(|e| {
+    let err_msg = format!("Failed to read {}: {}", path.display(), e);
+    error!("{}", err_msg);
+    err_msg
+  })?;
+  let key = line
+    .checked_sub(1)
+    .and_then(|index| content.lines().nth(index))
+    .and_then(|text| PRIVATE_KEY.find_iter(text).nth(position))
+    .map(|key| SecretString::new(key.as_str()));
+  content.zeroize();
+
+  let Some(key) = key else {
+    let err_msg = format!("No private key on line {} of {}, was the file changed?", line, path.display());
+    error!("{}", err_msg);
+    return Err(err_msg);
+  };
+
+  let address = derive_address(cast, &key, cancel.clone()).await?;
+  check_not_imported(cast, &address).await?;
+
+  import_wallet(cast, key, keystore_name, password, cancel).await
+}
+
+/// Fail unless no keystore holds `address`, so an account isn't imported
+/// twice. Locked keystores of unknown address have to be unlocked first.
+async fn check_not_imported(cast: &dyn CastExecutor, address: &str) -> Result<(), String> {
+  let err_msg = match duplicate_status(&known_addresses(cast).await?, address) {
+    DuplicateStatus::New => return Ok(()),
+    DuplicateStatus::Duplicate { keystore } => format!("{} is already in keystore '{}'", address, keystore),
+    DuplicateStatus::Unknown { locked } => format!(
+      "Can't tell whether {} is already imported, unlock {} first",
+      address,
+      locked.iter().map(|name| format!("'{}'", name)).collect::<Vec<_>>().join(", "),
+    ),
+  };
+  error!("{}", err_msg);
+  Err(err_msg)
+}
+
+/// Every keystore with its address, None where it is unknown (see `keystore_address`)
+async fn known_addresses(cast: &dyn CastExecutor) -> Result<Vec<(String, Option<String>)>, String> {
+  Ok(list_wallets(cast).await?.into_iter().map(|name| {
+    let address = keystore_address(&name);
+    (name, address)
+  }).collect())
+}
+
+fn duplicate_status(keystores: &[(String, Option<String>)], address: &str) -> DuplicateStatus {
+  if let Some((name, _)) = keystores.iter().find(|(_, known)| known.as_deref().is_some_and(|known| known.eq_ignore_ascii_case(address))) {
+    return DuplicateStatus::Duplicate { keystore: name.clone() };
+  }
+  let locked: Vec<String> = keystores.iter().filter(|(_, known)| known.is_none()).map(|(name, _)| name.clone()).collect();
+  if locked.is_empty() {
+    DuplicateStatus::New
+  } else {
+    DuplicateStatus::Unknown { locked }
+  }
+}
+
+/// Copy an encrypted keystore without decrypting it. geth's older
+/// capitalized `Crypto` field is renamed to the `crypto` Foundry reads.
+async fn copy_keystore(cast: &dyn CastExecutor, path: &Path, keystore_dir: &Path, keystore_name: &str) -> Result<String, String> {
+  let (mut keystore, address) = read_keystore(path).ok_or_else(|| {
+    let err_msg = format!("{} is not an encrypted keystore", path.display());
+    error!("{}", err_msg);
+    err_msg
+  })?;
+  check_not_imported(cast, &address).await?;
+  if let Some(crypto) = keystore.remove("Crypto") {
+    keystore.insert("crypto".to_string(), crypto);
+  }
+  let json = serde_json::to_string(&keystore).map_err(|e| e.to_string())?;
+
+  let target = keystore_dir.join(keystore_name);
+  write_new_keystore(&target, json.as_bytes()).map_err(|e| {
+    let err_msg = format!("Failed to write keystore {}: {}", target.display(), e);
+    error!("{}", err_msg);
+    err_msg
+  })?;
+
+  info!("Copied {} to keystore {}", path.display(), keystore_name);
+  Ok(address)
+}
+
+/// A version 3 keystore with an address, as geth, ape and brownie write them
+fn read_keystore(path: &Path) -> Option<(serde_json::Map<String, serde_json::Value>, String)> {
+  let keystore: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&read_small_file(path)?).ok()?;
+  let encrypted = keystore.get("version").and_then(|version| version.as_u64()) == Some(3)
+    && keystore.contains_key("id")
+    && (keystore.contains_key("crypto") || keystore.contains_key("Crypto"));
+  let address = keystore.get("address")?.as_str()?;
+  let hex = address.strip_prefix("0x").unwrap_or(address);
+  let valid = hex.len() == 40 && hex.bytes().all(|b| b.is_ascii_hexdigit());
+  let address = format!("0x{}", hex);
+  (encrypted && valid).then_some((keystore, address))
+}
+
+fn encrypted_candidates(format: AccountFormat, path: &Path) -> Vec<ImportCandidate> {
+  let files = match fs::read_dir(path) {
+    Ok(entries) => {
+      let mut files: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+      files.sort();
+      files
+    }
+    Err(_) => vec![path.to_path_buf()],
+  };
+
+  files
+    .into_iter()
+    .filter(|file| {
+      let name = file.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+      match format {
+        AccountFormat::Geth => name.starts_with("UTC--"),
+        _ => name.ends_with(".json"),
+      }
+    })
+    .filter_map(|file| {
+      let Some((_, address)) = read_keystore(&file) else {
+        debug!("Skipping {}, not an encrypted keystore", file.display());
+        return None;
+      };
+      let name = file.file_name()?.to_string_lossy().into_owned();
+      let suggested_name = match format {
+        AccountFormat::Geth => format!("geth-{}", &address[..10]),
+        _ => name.trim_end_matches(".json").to_string(),
+      };
+      Some(ImportCandidate {
+        format,
+        source: name,
+        path: file.display().to_string(),
+        line: None,
+        position: 0,
+        address,
+        encrypted: true,
+        suggested_name,
+        duplicate: DuplicateStatus::New,
+        name_taken: false,
+      })
+    })
+    .collect()
+}
+
+/// A plain private key in a file, before its address is derived
+struct KeyLocation {
+  path: PathBuf,
+  line: usize,
+  position: usize,
+  source: String,
+  suggested_name: String,
+}
+
+async fn plain_candidates(
+  cast: &dyn CastExecutor,
+  format: AccountFormat,
+  locations: Vec<KeyLocation>,
+  cancel: &CancellationToken,
+) -> Result<Vec<ImportCandidate>, String> {
+  let mut candidates: Vec<ImportCandidate> = Vec::new();
+
+  for location in locations {
+    if cancel.is_cancelled() {
+      let err_msg = "Import preview was cancelled".to_string();
+      error!("{}", err_msg);
+      return Err(err_msg);
+    }
+    let Some(mut content) = read_small_file(&location.path) else {
+      continue;
+    };
+    let key = content
+      .lines()
+      .nth(location.line - 1)
+      .and_then(|text| PRIVATE_KEY.find_iter(text).nth(location.position))
+      .map(|key| SecretString::new(key.as_str()));
+    content.zeroize();
+    let Some(key) = key else {
+      continue;
+    };
+
+    let address = derive_address(cast, &key, cancel.clone()).await?;
+    // The same key listed twice, e.g. in .env and .env.local
+    if candidates.iter().any(|candidate| candidate.address.eq_ignore_ascii_case(&address)) {
+      continue;
+    }
+    candidates.push(ImportCandidate {
+      format,
+      source: location.source,
+      path: location.path.display().to_string(),
+      line: Some(location.line),
+      position: location.position,
+      address,
+      encrypted: false,
+      suggested_name: location.suggested_name,
+      duplicate: DuplicateStatus::New,
+      name_taken: false,
+    });
+  }
+  Ok(candidates)
+}
+
+/// `.env` and `.env.*` files in `path`, or `path` itself if it is a file
+fn env_file_paths(path: &Path) -> Vec<PathBuf> {
+  let Ok(entries) = fs::read_dir(path) else {
+    return vec![path.to_path_buf()];
+  };
+  let mut files: Vec<PathBuf> = entries
+    .flatten()
+    .map(|entry| entry.path())
+    .filter(|file| is_env_file(file))
+    .filter(|file| !["example", "sample", "template"].iter().any(|suffix| file.to_string_lossy().ends_with(suffix)))
+    .collect();
+  files.sort();
+  files
+}
+
+/// Private keys assigned in .env files, by variable name, with where they are
+fn env_assignments(path: &Path) -> Vec<(String, KeyLocation)> {
+  let mut assignments = Vec::new();
+  for file in env_file_paths(path) {
+    let Some(mut content) = read_small_file(&file) else {
+      continue;
+    };
+    let file_name = file.file_name().unwrap_or_default().to_string_lossy().into_owned();
+
+    for (index, line) in content.lines().enumerate() {
+      let line = line.trim();
+      let Some((variable, value)) = line.strip_prefix("export ").unwrap_or(line).split_once('=') else {
+        continue;
+      };
+      let variable = variable.trim();
+      if variable.starts_with('#') || !PRIVATE_KEY.is_match(value) {
+        continue;
+      }
+      let name = variable.to_ascii_lowercase().replace("private_key", "").trim_matches('_').replace('_', "-");
+      assignments.push((variable.to_string(), KeyLocation {
+        path: file.clone(),
+        line: index + 1,
+        position: 0,
+        source: format!("{}:{}", file_name, index + 1),
+        suggested_name: if name.is_empty() { project_name(&file) } else { name },
+      }));
+    }
+    content.zeroize();
+  }
+  assignments
+}
+
+fn env_keys(path: &Path) -> Vec<KeyLocation> {
+  env_assignments(path)
+    .into_iter()
+    .filter(|(variable, _)| variable.to_ascii_uppercase().contains("PRIVATE_KEY"))
+    .map(|(_, location)| location)
+    .collect()
+}
+
+/// Keys in the `accounts` arrays of a Hardhat config, written out in the
+/// config or referenced from the .env files next to it
+fn hardhat_keys(path: &Path) -> Result<Vec<KeyLocation>, String> {
+  let config = match path.is_dir() {
+    true => HARDHAT_CONFIGS.iter().map(|name| path.join(name)).find(|config| config.is_file()),
+    false => Some(path.to_path_buf()),
+  };
+  let Some(config) = config else {
+    let err_msg = format!("No hardhat.config in {}", path.display());
+    error!("{}", err_msg);
+    return Err(err_msg);
+  };
+  let Some(mut content) = read_small_file(&config) else {
+    let err_msg = format!("Failed to read {}", config.display());
+    error!("{}", err_msg);
+    return Err(err_msg);
+  };
+
+  let config_dir = config.parent().unwrap_or(Path::new("."));
+  let config_name = config.file_name().unwrap_or_default().to_string_lossy().into_owned();
+  let env: HashMap<String, KeyLocation> = env_assignments(config_dir).into_iter().rev().collect();
+  let mut locations = Vec::new();
+
+  for accounts in HARDHAT_ACCOUNTS.find_iter(&content) {
+    let line_start = content[..accounts.start()].rfind('\n').map_or(0, |index| index + 1);
+    if content[line_start..accounts.start()].contains("//") {
+      continue;
+    }
+    let end = array_end(&content, accounts.end()).unwrap_or(content.len());
+
+    for key in PRIVATE_KEY.find_iter(&content[accounts.end()..end]) {
+      let start = accounts.end() + key.start();
+      let line_start = content[..start].rfind('\n').map_or(0, |index| index + 1);
+      let line = content[..start].matches('\n').count() + 1;
+      locations.push(KeyLocation {
+        path: config.clone(),
+        line,
+        position: PRIVATE_KEY.find_iter(&content[line_start..start]).count(),
+        source: format!("{}:{}", config_name, line),
+        suggested_name: format!("hardhat-{}", locations.len()),
+      });
+    }
+
+    for variable in HARDHAT_VARIABLE.captures_iter(&content[accounts.end()..end]) {
+      let name = variable.iter().skip(1).flatten().next().map(|name| name.as_str()).unwrap_or_default();
+      match env.get(name) {
+        Some(location) => locations.push(KeyLocation {
+          path: location.path.clone(),
+          source: location.source.clone(),
+          suggested_name: location.suggested_name.clone(),
+          ..*location
+        }),
+        None => debug!("Skipping {}, not set to a private key in .env", name),
+      }
+    }
+  }
+  content.zeroize();
+  Ok(locations)
+}
+
+/// Index of the `]` closing the array that starts at `start`
+fn array_end(content: &str, start: usize) -> Option<usize> {
+  let mut depth = 1;
+  for (index, c) in content[start..].char_indices() {
+    match c {
+      '[' => depth += 1,
+      ']' => {
+        depth -= 1;
+        if depth == 0 {
+          return Some(start + index);
+        }
+      }
+      _ => {}
+    }
+  }
+  None
+}
+
+/// Name of the directory holding `file`, for a key that has no better name
+fn project_name(file: &Path) -> String {
+  file
+    .canonicalize()
+    .ok()
+    .and_then(|file| Some(file.parent()?.file_name()?.to_string_lossy().into_owned()))
+    .unwrap_or_else(|| "imported".to_string())
+}
+
+#[cfg(all(test, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY};
+  use crate::cast::version;
+  use crate::commands::testing::{command_lines, recorded_versions, serial, temp_dir};
+  use crate::session;
+
+  fn env_project() -> PathBuf {
+    let dir = temp_dir("migrate-env");
+    fs::write(dir.join(".env"), format!("RPC_URL=http://localhost:8545\nDEPLOYER_PRIVATE_KEY={}\n", FIXTURE_PRIVATE_KEY)).unwrap();
+    dir
+  }
+
+  fn geth_keystore() -> PathBuf {
+    let dir = temp_dir("migrate-geth");
+    let keystore = serde_json::json!({
+      "address": FIXTURE_ADDRESS.trim_start_matches("0x").to_ascii_lowercase(),
+      "Crypto": { "cipher": "aes-128-ctr" },
+      "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+      "version": 3,
+    });
+    fs::write(dir.join("UTC--2024-05-01T00-00-00.000000000Z--f39fd6e51aad88f6f4ce6ab8827279cfffb92266"), keystore.to_string()).unwrap();
+    dir
+  }
+
+  fn unlock_fixture() {
+    session::unlock("fixture", FIXTURE_ADDRESS.to_string(), Password::new("pw"), SecretString::new(FIXTURE_PRIVATE_KEY));
+  }
+
+  #[tokio::test]
+  async fn previews_and_imports_plain_keys() {
+    let _serial = serial().await;
+    let dir = env_project();
+    for cast in recorded_versions() {
+      version::forget();
+      // The recorded `fixture` keystore is locked, and has no address on disk
+      let candidates = preview_account_import(&cast, AccountFormat::Env, Some(&dir), CancellationToken::new()).await.unwrap();
+      assert_eq!(candidates[0].duplicate, DuplicateStatus::Unknown { locked: vec!["fixture".to_string()] });
+      let err = import_account(&cast, &dir.join(".env"), Some(2), 0, "migrated".to_string(), Some(Password::new("pw")), CancellationToken::new()).await;
+      assert_eq!(err.unwrap_err(), format!("Can't tell whether {} is already imported, unlock 'fixture' first", FIXTURE_ADDRESS));
+
+      let cast = cast.on(&["wallet", "list"], FakeResponse::output(""));
+      let candidates = preview_account_import(&cast, AccountFormat::Env, Some(&dir), CancellationToken::new()).await.unwrap();
+      let [candidate] = &candidates[..] else { panic!("expected one candidate, got {:?}", candidates) };
+      assert_eq!((candidate.source.as_str(), candidate.line, candidate.position), (".env:2", Some(2), 0));
+      assert_eq!((candidate.address.as_str(), candidate.suggested_name.as_str()), (FIXTURE_ADDRESS, "deployer"));
+      assert!(!candidate.encrypted);
+      assert_eq!(candidate.duplicate, DuplicateStatus::New);
+
+      let address = import_account(&cast, &dir.join(".env"), Some(2), 0, "migrated".to_string(), Some(Password::new("pw")), CancellationToken::new())
+        .await
+        .unwrap();
+      assert_eq!(address, FIXTURE_ADDRESS);
+      assert_eq!(command_lines(&cast).last().unwrap(), "wallet import migrated --interactive");
+    }
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[tokio::test]
+  async fn previews_encrypted_keystores() {
+    let _serial = serial().await;
+    let dir = geth_keystore();
+    let cast = FakeCast::recorded();
+    let candidates = preview_account_import(&cast, AccountFormat::Geth, Some(&dir), CancellationToken::new()).await.unwrap();
+    assert_eq!(candidates.len(), 1);
+    assert!(candidates[0].encrypted && candidates[0].line.is_none());
+    assert_eq!(candidates[0].suggested_name, "geth-0xf39fd6e5");
+    assert!(candidates[0].address.eq_ignore_ascii_case(FIXTURE_ADDRESS));
+    // Nothing is decrypted to list them
+    assert_eq!(command_lines(&cast), ["--version", "wallet list --dir"]);
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[tokio::test]
+  async fn refuses_accounts_already_imported() {
+    let _serial = serial().await;
+    let (env_dir, geth_dir) = (env_project(), geth_keystore());
+    let cast = FakeCast::recorded();
+    let keystore = fs::read_dir(&geth_dir).unwrap().next().unwrap().unwrap().path();
+
+    unlock_fixture();
+    let preview = preview_account_import(&cast, AccountFormat::Env, Some(&env_dir), CancellationToken::new()).await;
+    let plain = import_account(&cast, &env_dir.join(".env"), Some(2), 0, "again".to_string(), Some(Password::new("pw")), CancellationToken::new()).await;
+    let encrypted = import_account(&cast, &keystore, None, 0, "again".to_string(), None, CancellationToken::new()).await;
+    session::lock("fixture");
+
+    assert_eq!(preview.unwrap()[0].duplicate, DuplicateStatus::Duplicate { keystore: "fixture".to_string() });
+    assert_eq!(plain.unwrap_err(), format!("{} is already in keystore 'fixture'", FIXTURE_ADDRESS));
+    let err = encrypted.unwrap_err();
+    assert!(err.ends_with("is already in keystore 'fixture'"), "{}", err);
+    assert!(!command_lines(&cast).iter().any(|line| line.starts_with("wallet import")));
+    let _ = fs::remove_dir_all(&env_dir);
+    let _ = fs::remove_dir_all(&geth_dir);
+  }
+
+  #[tokio::test]
+  async fn reports_bad_input_cast_errors_and_cancellation() {
+    let _serial = serial().await;
+    let dir = env_project();
+    let cast = FakeCast::recorded();
+    let env = dir.join(".env");
+
+    let err = import_account(&cast, &env, Some(2), 0, "../outside".to_string(), Some(Password::new("pw")), CancellationToken::new()).await;
+    assert_eq!(err.unwrap_err(), "Invalid keystore name \"../outside\"");
+    let err = import_account(&cast, &env, Some(2), 0, "migrated".to_string(), None, CancellationToken::new()).await;
+    assert_eq!(err.unwrap_err(), "A password is needed to import a private key");
+    let err = import_account(&cast, &env, Some(1), 0, "migrated".to_string(), Some(Password::new("pw")), CancellationToken::new()).await;
+    assert!(err.unwrap_err().starts_with("No private key on line 1"));
+
+    let failing = FakeCast::recorded().on(&["wallet", "address", "--interactive"], FakeResponse::output("Error: invalid private key\n")
+      .prompt("Enter private key:"));
+    let err = preview_account_import(&failing, AccountFormat::Env, Some(&dir), CancellationToken::new()).await;
+    assert_eq!(err.unwrap_err(), "Error: invalid private key");
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let err = preview_account_import(&cast, AccountFormat::Env, Some(&dir), cancel).await;
+    assert_eq!(err.unwrap_err(), "Import preview was cancelled");
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
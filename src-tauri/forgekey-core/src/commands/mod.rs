@@ -0,0 +1,125 @@
+mod import;
+mod new;
+mod vanity;
+mod list;
+mod decrypt;
+mod remove;
+mod unlock;
+mod sign;
+mod broadcast;
+mod password_pipe;
+mod project;
+mod leaks;
+mod migrate;
+mod export;
+
+pub use import::import_wallet;
+pub use new::create_new_wallet; 
+pub use vanity::create_vanity_wallet;
+pub use vanity::cancel_vanity_wallet;
+pub use list::list_wallets;
+pub use list::get_wallet_address;
+pub use decrypt::decrypt_keystore;
+pub use remove::remove_keystore;
+pub use sign::{sign_message, sign_typed_data, sign_transaction};
+pub use broadcast::{run_forge_script, cast_send};
+pub use password_pipe::create_password_pipe;
+pub use project::{detect_project_accounts, AccountStatus, ProjectAccount};
+pub use leaks::{scan_for_plaintext_keys, import_leaked_key, LeakFix, LeakKind, LeakedKey};
+pub use migrate::{preview_account_import, import_account, AccountFormat, DuplicateStatus, ImportCandidate};
+pub use export::{export_keystores, ExportedKeystore};
+pub use unlock::{unlock_keystore, lock_keystore, lock_all, list_unlocked_keystores, set_auto_lock_timeout};
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use log::error;
+use crate::pty::PtyError;
+use crate::session;
+use crate::utils::get_keystore_dir;
+
+/// Error returned when an operation needs an unlocked keystore but no password was given.
+pub(crate) fn locked_error(keystore_name: &str) -> String {
+  let err_msg = format!("Keystore '{}' is locked", keystore_name);
+  error!("{}", err_msg);
+  err_msg
+}
+
+/// Turn a PTY error into the message returned to the UI. A rejected password
+/// becomes a plain "Incorrect password" and cast's own error output is passed
+/// through, anything else names the command that failed.
+pub(crate) fn pty_error_message(command: &str, e: PtyError) -> String {
+  let err_msg = match e {
+    e if e.is_wrong_password() => PtyError::WrongPassword.to_string(),
+    PtyError::UnexpectedEof { output, .. } if !output.is_empty() => output,
+    PtyError::FailurePattern(line) => line,
+    e => format!("Failed to execute {} command: {}", command, e),
+  };
+  error!("{}", err_msg);
+  err_msg
+}
+
+/// Address of a keystore from its unlocked session, or from the `address`
+/// field keystore files written by some tools carry
+pub(crate) fn keystore_address(keystore_name: &str) -> Option<String> {
+  if let Some(address) = session::with_unlocked(keystore_name, |session| session.address.clone()) {
+    return Some(address);
+  }
+
+  let path = get_keystore_dir().ok()?.join(keystore_name);
+  let keystore: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+  let address = keystore.get("address")?.as_str()?;
+  let hex = address.strip_prefix("0x").unwrap_or(address);
+  (hex.len() == 40 && hex.bytes().all(|b| b.is_ascii_hexdigit())).then(|| format!("0x{}", hex))
+}
+
+/// Write a keystore file readable only by the current user, creating its
+/// directory if needed. Existing files are never overwritten.
+pub(crate) fn write_new_keystore(path: &Path, contents: &[u8]) -> io::Result<()> {
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+  let mut options = OpenOptions::new();
+  options.write(true).create_new(true);
+  #[cfg(unix)]
+  std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+  options.open(path)?.write_all(contents)
+}
+
+/// Shared by the command tests, which run cast through `FakeCast`
+#[cfg(all(test, feature = "fake-cast"))]
+pub(crate) mod testing {
+  use std::fs;
+  use std::path::PathBuf;
+  use tokio::sync::{Mutex, MutexGuard};
+  use crate::cast::fake::FakeCast;
+  use crate::cast::version;
+
+  /// The detected cast version and the unlocked keystores are process wide,
+  /// so tests relying on either take turns
+  pub(crate) async fn serial() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::const_new(());
+    let guard = LOCK.lock().await;
+    version::forget();
+    guard
+  }
+
+  /// Recorded output of every supported cast version. Call `version::forget`
+  /// before running the next one, so its version is detected.
+  pub(crate) fn recorded_versions() -> [FakeCast; 2] {
+    [FakeCast::recorded(), FakeCast::recorded_legacy()]
+  }
+
+  /// Every command line `cast` was run with so far, oldest first
+  pub(crate) fn command_lines(cast: &FakeCast) -> Vec<String> {
+    cast.calls().iter().map(|call| call.args.join(" ")).collect()
+  }
+
+  /// An empty directory for `test`, removed first if a previous run left it
+  pub(crate) fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("forgekey-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+}
@@ -0,0 +1,89 @@
+use crate::cast::{parse, version, CastExecutor, OUTPUT_TIMEOUT};
+use crate::commands::pty_error_message;
+use crate::models::Password;
+use log::error;
+use tokio_util::sync::CancellationToken;
+
+pub async fn create_new_wallet(cast: &dyn CastExecutor, address_label: String, password: Password, cancel: CancellationToken) -> Result<String, String> {
+  let version = version::supported(cast).await?;
+
+  // password will be automatically zeroized when dropped on error
+  let output = cast.output(parse::wallet_new_args(&version), OUTPUT_TIMEOUT, &cancel)
+    .await
+    .map_err(|e| pty_error_message("cast wallet new", e))?;
+
+  if !output.success() {
+    let err_msg = output.stderr;
+    error!("Failed to create new wallet for {}: {}", address_label, err_msg);
+    return Err(err_msg);
+  }
+
+  // stdout is already in locked memory
+  let wallet_info = parse::wallet_new(&version, &output.stdout)?;
+  
+  // Store the address before we zeroize the wallet_info
+  let address = wallet_info.address.clone();
+  
+  // Import the wallet (this will handle zeroizing the private_key internally)
+  let result = crate::commands::import_wallet(
+    cast,
+    wallet_info.private_key, // Move the private key instead of cloning
+    address_label,
+    password,
+    cancel,
+  ).await;
+
+  // Return the result or the address if successful
+  match result {
+    Ok(_) => Ok(address),
+    Err(e) => Err(e),
+  }
+}
+
+#[cfg(all(test, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS};
+  use crate::commands::testing::{command_lines, recorded_versions, serial};
+
+  async fn create(cast: &FakeCast, cancel: CancellationToken) -> Result<String, String> {
+    create_new_wallet(cast, "created".to_string(), Password::new("new password"), cancel).await
+  }
+
+  #[tokio::test]
+  async fn creates_and_imports_with_each_version() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      assert_eq!(create(&cast, CancellationToken::new()).await.unwrap(), FIXTURE_ADDRESS);
+
+      let new_args = parse::wallet_new_args(&version::supported(&cast).await.unwrap()).join(" ");
+      assert_eq!(command_lines(&cast), ["--version", &new_args, "wallet import created --interactive"]);
+      assert_eq!(cast.calls()[2].answered, ["private key", "password"]);
+    }
+  }
+
+  #[tokio::test]
+  async fn reports_cast_errors() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      let cast = cast.on(&["wallet", "new"], FakeResponse::failure("Error: failed to generate a key\n", 1));
+      assert_eq!(create(&cast, CancellationToken::new()).await.unwrap_err(), "Error: failed to generate a key\n");
+      assert!(!command_lines(&cast).iter().any(|line| line.starts_with("wallet import")));
+    }
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn stops_when_cancelled_or_stuck() {
+    let _serial = serial().await;
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let err = create(&FakeCast::recorded(), cancel).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet new command: Operation was cancelled");
+
+    let cast = FakeCast::recorded().on(&["wallet", "import"], FakeResponse::output("").prompt("Enter the secret phrase"));
+    let err = create(&cast, CancellationToken::new()).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet import command: Operation timed out");
+  }
+}
@@ -0,0 +1,198 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::SecretString;
+use crate::session;
+use crate::commands::locked_error;
+
+// One-shot password pipes, for Foundry commands run by the user rather than
+// by ForgeKey: `cast send --account dev --password-file <pipe>`. The password
+// of an unlocked keystore is written once to whoever opens the pipe first and
+// never touches the disk.
+
+/// Create a named pipe only the current user can open and return its path.
+/// The first reader gets the password of the unlocked `keystore_name` without
+/// a trailing newline, then the pipe is deleted. Unread pipes are deleted
+/// after a minute.
+pub fn create_password_pipe(keystore_name: &str) -> Result<String, String> {
+  let password = session::with_unlocked(keystore_name, |session| SecretString::new(session.password.as_str()))
+    .ok_or_else(|| locked_error(keystore_name))?;
+
+  fifo::create(password, "password").map(|path| path.display().to_string())
+}
+
+/// A one-shot pipe like `create_password_pipe`'s, for a secret cast only
+/// reads from a file such as the phrase for `--mnemonic`. Deleted when
+/// dropped if cast didn't read it.
+pub(crate) struct SecretPipe {
+  path: PathBuf,
+}
+
+impl SecretPipe {
+  pub(crate) fn create(secret: &SecretString) -> Result<Self, String> {
+    fifo::create(SecretString::new(secret), "secret").map(|path| Self { path })
+  }
+
+  pub(crate) fn path(&self) -> &Path {
+    &self.path
+  }
+}
+
+impl Drop for SecretPipe {
+  fn drop(&mut self) {
+    let _ = fs::remove_file(&self.path);
+    if let Some(dir) = self.path.parent() {
+      let _ = fs::remove_dir(dir);
+    }
+  }
+}
+
+#[cfg(unix)]
+mod fifo {
+  use std::ffi::{CString, OsString};
+  use std::fs::{self, File, OpenOptions};
+  use std::io::{self, Write};
+  use std::os::unix::ffi::{OsStrExt, OsStringExt};
+  use std::os::unix::fs::OpenOptionsExt;
+  use std::os::unix::io::AsRawFd;
+  use std::path::{Path, PathBuf};
+  use std::thread;
+  use std::time::{Duration, Instant};
+  use log::{debug, error, warn};
+
+  use crate::models::SecretString;
+
+  const READER_TIMEOUT: Duration = Duration::from_secs(60);
+  const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+  /// Pipe named `name` in a new private directory, handing out `secret` once
+  pub fn create(secret: SecretString, name: &str) -> Result<PathBuf, String> {
+    let dir = private_dir().map_err(|e| {
+      let err_msg = format!("Failed to create a private directory for the {} pipe: {}", name, e);
+      error!("{}", err_msg);
+      err_msg
+    })?;
+    let path = dir.join(name);
+
+    if let Err(e) = mkfifo(&path) {
+      let _ = fs::remove_dir(&dir);
+      let err_msg = format!("Failed to create the {} pipe: {}", name, e);
+      error!("{}", err_msg);
+      return Err(err_msg);
+    }
+
+    let pipe = path.clone();
+    thread::spawn(move || serve(&dir, &path, secret));
+    Ok(pipe)
+  }
+
+  /// Wait for the first reader, hand it the secret and delete the pipe
+  fn serve(dir: &Path, path: &Path, secret: SecretString) {
+    let writer = wait_for_reader(path);
+
+    // Gone before anything is written, so nobody else can open it
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_dir(dir);
+
+    match writer {
+      Ok(mut writer) => match writer.write_all(secret.as_bytes()) {
+        Ok(()) => debug!("Secret pipe {} was read", path.display()),
+        Err(e) => error!("Failed to write to the secret pipe: {}", e),
+      },
+      Err(e) if e.kind() == io::ErrorKind::TimedOut => warn!("Nobody read the secret pipe, deleted it"),
+      // Deleted by its `SecretPipe`, cast didn't need it
+      Err(e) if e.kind() == io::ErrorKind::NotFound => debug!("Secret pipe {} was removed unread", path.display()),
+      Err(e) => error!("Failed to open the secret pipe: {}", e),
+    }
+  }
+
+  /// Opening a FIFO for writing blocks until someone opens it for reading.
+  /// Non-blocking it fails with ENXIO instead, so poll to be able to give up.
+  fn wait_for_reader(path: &Path) -> io::Result<File> {
+    let deadline = Instant::now() + READER_TIMEOUT;
+    loop {
+      match OpenOptions::new().write(true).custom_flags(libc::O_NONBLOCK).open(path) {
+        Ok(file) => {
+          // Back to blocking writes, a slow reader shouldn't fail them
+          let fd = file.as_raw_fd();
+          unsafe { libc::fcntl(fd, libc::F_SETFL, libc::fcntl(fd, libc::F_GETFL) & !libc::O_NONBLOCK) };
+          return Ok(file);
+        }
+        Err(e) if e.raw_os_error() == Some(libc::ENXIO) => {
+          if Instant::now() >= deadline {
+            return Err(io::ErrorKind::TimedOut.into());
+          }
+          thread::sleep(POLL_INTERVAL);
+        }
+        Err(e) => return Err(e),
+      }
+    }
+  }
+
+  /// A new mode-0700 directory in the temp dir, see mkdtemp(3)
+  fn private_dir() -> io::Result<PathBuf> {
+    let template = std::env::temp_dir().join("forgekey-XXXXXX");
+    let mut bytes = CString::new(template.as_os_str().as_bytes())?.into_bytes_with_nul();
+    if unsafe { libc::mkdtemp(bytes.as_mut_ptr() as *mut libc::c_char) }.is_null() {
+      return Err(io::Error::last_os_error());
+    }
+    bytes.pop();
+    Ok(PathBuf::from(OsString::from_vec(bytes)))
+  }
+
+  fn mkfifo(path: &Path) -> io::Result<()> {
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(())
+  }
+}
+
+#[cfg(not(unix))]
+mod fifo {
+  use std::path::PathBuf;
+  use log::error;
+
+  use crate::models::SecretString;
+
+  pub fn create(_secret: SecretString, name: &str) -> Result<PathBuf, String> {
+    let err_msg = format!("The {} pipe is only available on macOS and Linux", name);
+    error!("{}", err_msg);
+    Err(err_msg)
+  }
+}
+
+#[cfg(all(test, unix, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY};
+  use crate::commands::testing::serial;
+  use crate::models::Password;
+
+  #[tokio::test]
+  async fn hands_out_the_password_once() {
+    let _serial = serial().await;
+    assert_eq!(create_password_pipe("piped").unwrap_err(), "Keystore 'piped' is locked");
+
+    session::unlock("piped", FIXTURE_ADDRESS.to_string(), Password::new("pipe password"), SecretString::new(FIXTURE_PRIVATE_KEY));
+    let pipe = create_password_pipe("piped");
+    session::lock("piped");
+
+    let pipe = pipe.unwrap();
+    assert_eq!(std::fs::read_to_string(&pipe).unwrap(), "pipe password");
+    // Deleted as soon as it was opened
+    assert!(!std::path::Path::new(&pipe).exists());
+  }
+
+  #[test]
+  fn secret_pipes_are_read_once_or_removed() {
+    let pipe = SecretPipe::create(&SecretString::new("word word")).unwrap();
+    assert_eq!(std::fs::read_to_string(pipe.path()).unwrap(), "word word");
+
+    let unread = SecretPipe::create(&SecretString::new("word word")).unwrap();
+    let path = unread.path().to_path_buf();
+    assert!(path.exists());
+    drop(unread);
+    assert!(!path.exists() && !path.parent().unwrap().exists());
+  }
+}
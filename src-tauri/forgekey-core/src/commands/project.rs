@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::cast::CastExecutor;
+use crate::commands::keystore_address;
+use log::{debug, error};
+use serde::Serialize;
+
+// Which keystores a Foundry project expects, found in the places Foundry and
+// its users configure them: `sender` in foundry.toml profiles,
+// `ETH_KEYSTORE_ACCOUNT` (the environment variable behind `--account`) and
+// `ETH_FROM` in .env files, and `--account`/`--sender`/`--from` in the forge
+// and cast invocations of Makefiles and justfiles. Files are read line by
+// line, nothing is evaluated.
+
+const TASK_FILES: [&str; 6] = ["Makefile", "makefile", "GNUmakefile", "justfile", "Justfile", ".justfile"];
+
+/// An account a project refers to, and whether ForgeKey has it
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectAccount {
+  /// Where it is referenced, e.g. "Makefile:12" or "foundry.toml [profile.ci]"
+  pub source: String,
+  /// Keystore named by `--account` or `ETH_KEYSTORE_ACCOUNT`
+  pub account: Option<String>,
+  /// Address given by `--sender`, `--from`, `ETH_FROM` or `sender`
+  pub sender: Option<String>,
+  /// The keystore it resolves to, by name or by sender address
+  pub keystore: Option<String>,
+  /// Address of that keystore, when known
+  pub keystore_address: Option<String>,
+  pub status: AccountStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccountStatus {
+  /// The keystore exists, and matches the sender if there is one
+  Ok,
+  /// No keystore has the referenced name
+  Missing,
+  /// The sender is not the address of the named keystore
+  Mismatch,
+  /// The keystore exists but its address is unknown until it is unlocked,
+  /// so the sender couldn't be checked
+  Unverified,
+  /// Only a sender is given and no known keystore has that address
+  Unknown,
+}
+
+/// An account reference as written in the project, before matching it
+struct Reference {
+  source: String,
+  account: Option<String>,
+  sender: Option<String>,
+}
+
+/// Find the accounts `project_dir` refers to and match them with the
+/// keystores from `list_wallets`
+pub async fn detect_project_accounts(cast: &dyn CastExecutor, project_dir: &Path) -> Result<Vec<ProjectAccount>, String> {
+  if !project_dir.join("foundry.toml").is_file() {
+    let err_msg = format!("{} is not a Foundry project, it has no foundry.toml", project_dir.display());
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+
+  let keystores: Vec<(String, Option<String>)> = crate::commands::list_wallets(cast)
+    .await?
+    .into_iter()
+    .map(|name| {
+      let address = keystore_address(&name);
+      (name, address)
+    })
+    .collect();
+
+  let mut variables = HashMap::new();
+  let mut references = foundry_toml(project_dir);
+  references.extend(env_files(project_dir, &mut variables));
+  references.extend(task_files(project_dir, &mut variables));
+
+  Ok(references.into_iter().map(|reference| resolve(reference, &keystores)).collect())
+}
+
+fn resolve(reference: Reference, keystores: &[(String, Option<String>)]) -> ProjectAccount {
+  let same_address = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+
+  let (keystore, keystore_address, status) = match (&reference.account, &reference.sender) {
+    (Some(account), sender) => match keystores.iter().find(|(name, _)| name == account) {
+      None => (None, None, AccountStatus::Missing),
+      Some((name, address)) => {
+        let status = match (sender, address) {
+          (Some(sender), Some(address)) if !same_address(sender, address) => AccountStatus::Mismatch,
+          (Some(_), None) => AccountStatus::Unverified,
+          _ => AccountStatus::Ok,
+        };
+        (Some(name.clone()), address.clone(), status)
+      }
+    },
+    (None, Some(sender)) => {
+      match keystores.iter().find(|(_, address)| address.as_deref().is_some_and(|address| same_address(sender, address))) {
+        Some((name, address)) => (Some(name.clone()), address.clone(), AccountStatus::Ok),
+        None => (None, None, AccountStatus::Unknown),
+      }
+    }
+    (None, None) => (None, None, AccountStatus::Unknown),
+  };
+
+  ProjectAccount {
+    source: reference.source,
+    account: reference.account,
+    sender: reference.sender,
+    keystore,
+    keystore_address,
+    status,
+  }
+}
+
+/// `sender` keys of foundry.toml, per profile
+fn foundry_toml(project_dir: &Path) -> Vec<Reference> {
+  let Ok(content) = fs::read_to_string(project_dir.join("foundry.toml")) else {
+    return Vec::new();
+  };
+
+  let mut section = String::new();
+  let mut references = Vec::new();
+  for line in content.lines().map(str::trim) {
+    if let Some(name) = line.strip_prefix('[').and_then(|line| line.split(']').next()) {
+      section = name.trim().to_string();
+    } else if let Some(("sender", value)) = assignment(line) {
+      references.push(Reference {
+        source: format!("foundry.toml [{}]", section),
+        account: None,
+        sender: Some(value),
+      });
+    }
+  }
+  references
+}
+
+/// `.env` and `.env.*` files next to foundry.toml, except examples. Their
+/// variables are collected in `variables` to resolve Makefile references.
+fn env_files(project_dir: &Path, variables: &mut HashMap<String, String>) -> Vec<Reference> {
+  let Ok(entries) = fs::read_dir(project_dir) else {
+    return Vec::new();
+  };
+
+  let mut names: Vec<String> = entries
+    .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+    .filter(|name| name == ".env" || name.starts_with(".env."))
+    .filter(|name| !["example", "sample", "template"].iter().any(|suffix| name.ends_with(suffix)))
+    .collect();
+  names.sort();
+
+  let mut references = Vec::new();
+  for name in names {
+    let Ok(content) = fs::read_to_string(project_dir.join(&name)) else {
+      continue;
+    };
+    let mut reference = Reference { source: name.clone(), account: None, sender: None };
+
+    for line in content.lines() {
+      let line = line.trim();
+      let Some((key, value)) = assignment(line.strip_prefix("export ").unwrap_or(line)) else {
+        continue;
+      };
+      match key {
+        "ETH_KEYSTORE_ACCOUNT" => reference.account = Some(value.clone()),
+        "ETH_FROM" => reference.sender = Some(value.clone()),
+        _ => {}
+      }
+      variables.entry(key.to_string()).or_insert(value);
+    }
+
+    if reference.account.is_some() || reference.sender.is_some() {
+      references.push(reference);
+    }
+  }
+  references
+}
+
+/// forge and cast invocations in Makefiles and justfiles
+fn task_files(project_dir: &Path, variables: &mut HashMap<String, String>) -> Vec<Reference> {
+  let mut references = Vec::new();
+
+  for name in TASK_FILES {
+    let Ok(content) = fs::read_to_string(project_dir.join(name)) else {
+      continue;
+    };
+    let lines = joined_lines(&content);
+
+    // Variables first, recipes can use them before they are defined. Values
+    // from .env files take precedence, like with `include .env`.
+    for (_, line) in &lines {
+      if line.starts_with(char::is_whitespace) {
+        continue;
+      }
+      if let Some((key, value)) = assignment(line.trim_start_matches("export ")) {
+        variables.entry(key.to_string()).or_insert(value);
+      }
+    }
+
+    for (number, line) in &lines {
+      if !line.contains("forge ") && !line.contains("cast ") {
+        continue;
+      }
+      let (account, sender) = invocation_flags(line, variables);
+      if account.is_some() || sender.is_some() {
+        references.push(Reference { source: format!("{}:{}", name, number), account, sender });
+      }
+    }
+  }
+  references
+}
+
+/// `--account` and `--sender`/`--from` of a command line, with variables
+/// resolved. Flags whose variables can't be resolved are skipped.
+fn invocation_flags(line: &str, variables: &HashMap<String, String>) -> (Option<String>, Option<String>) {
+  let mut account = None;
+  let mut sender = None;
+  let mut tokens = line.split_whitespace();
+
+  while let Some(token) = tokens.next() {
+    let (flag, inline) = match token.split_once('=') {
+      Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+      _ => (token, None),
+    };
+    let target = match flag {
+      "--account" => &mut account,
+      "--sender" | "--from" => &mut sender,
+      _ => continue,
+    };
+    let Some(value) = inline.or_else(|| tokens.next()) else {
+      break;
+    };
+    match substitute(&unquote(value), variables) {
+      Some(value) if !value.is_empty() => *target = Some(value),
+      _ => debug!("Skipping unresolved {} {}", flag, value),
+    }
+  }
+  (account, sender)
+}
+
+/// Resolve `$(NAME)`, `${NAME}`, `$NAME` and just's `{{NAME}}`
+fn substitute(value: &str, variables: &HashMap<String, String>) -> Option<String> {
+  let name = value
+    .strip_prefix("$(").and_then(|rest| rest.strip_suffix(')'))
+    .or_else(|| value.strip_prefix("${").and_then(|rest| rest.strip_suffix('}')))
+    .or_else(|| value.strip_prefix("{{").and_then(|rest| rest.strip_suffix("}}")))
+    .or_else(|| value.strip_prefix('$'));
+
+  match name {
+    Some(name) => variables.get(name.trim()).cloned(),
+    None if value.contains('$') || value.contains("{{") => None,
+    None => Some(value.to_string()),
+  }
+}
+
+/// `KEY = value`, `KEY := value`, `KEY ?= value` or `KEY=value`, with quotes
+/// and trailing comments removed
+fn assignment(line: &str) -> Option<(&str, String)> {
+  if line.starts_with('#') {
+    return None;
+  }
+  let (key, value) = line.split_once('=')?;
+  let key = key.trim_end_matches([':', '?', '+']).trim();
+  if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'.' || b == b'-') {
+    return None;
+  }
+
+  let value = value.trim();
+  let value = match value.chars().next() {
+    Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+    _ => value.split(" #").next().unwrap_or_default().trim(),
+  };
+  Some((key, value.to_string()))
+}
+
+fn unquote(value: &str) -> String {
+  let quoted = |quote: char| value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote);
+  if quoted('"') || quoted('\'') {
+    value[1..value.len() - 1].to_string()
+  } else {
+    value.to_string()
+  }
+}
+
+/// Lines of a Makefile or justfile with `\` continuations joined, each with
+/// the number of the line it starts on
+fn joined_lines(content: &str) -> Vec<(usize, String)> {
+  let mut lines = Vec::new();
+  let mut current: Option<(usize, String)> = None;
+
+  for (index, line) in content.lines().enumerate() {
+    let (number, mut joined) = current.take().unwrap_or((index + 1, String::new()));
+    match line.strip_suffix('\\') {
+      Some(line) => {
+        joined.push_str(line);
+        joined.push(' ');
+        current = Some((number, joined));
+      }
+      None => {
+        joined.push_str(line);
+        lines.push((number, joined));
+      }
+    }
+  }
+  lines.extend(current);
+  lines
+}
+
+#[cfg(all(test, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY};
+  use crate::cast::version;
+  use crate::commands::testing::{recorded_versions, serial, temp_dir};
+  use crate::models::{Password, SecretString};
+  use crate::session;
+
+  const OTHER_ADDRESS: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+  fn project() -> std::path::PathBuf {
+    let dir = temp_dir("project");
+    fs::write(dir.join("foundry.toml"), format!("[profile.ci]\nsender = \"{}\"\n", FIXTURE_ADDRESS)).unwrap();
+    fs::write(dir.join(".env"), format!("ETH_KEYSTORE_ACCOUNT=fixture\nETH_FROM={}\n", OTHER_ADDRESS)).unwrap();
+    fs::write(dir.join("Makefile"), "ACCOUNT := missing\n\ndeploy:\n\tforge script script/Deploy.s.sol --account $(ACCOUNT)\n").unwrap();
+    dir
+  }
+
+  fn statuses(accounts: &[ProjectAccount]) -> Vec<(&str, Option<&str>, AccountStatus)> {
+    accounts.iter().map(|account| (account.source.as_str(), account.keystore.as_deref(), account.status)).collect()
+  }
+
+  #[tokio::test]
+  async fn matches_references_with_keystores() {
+    let _serial = serial().await;
+    let dir = project();
+    for cast in recorded_versions() {
+      version::forget();
+      let locked = detect_project_accounts(&cast, &dir).await.unwrap();
+      assert_eq!(statuses(&locked), [
+        ("foundry.toml [profile.ci]", None, AccountStatus::Unknown),
+        (".env", Some("fixture"), AccountStatus::Unverified),
+        ("Makefile:4", None, AccountStatus::Missing),
+      ]);
+
+      // Unlocking tells the address of `fixture`
+      session::unlock("fixture", FIXTURE_ADDRESS.to_string(), Password::new("pw"), SecretString::new(FIXTURE_PRIVATE_KEY));
+      let unlocked = detect_project_accounts(&cast, &dir).await;
+      session::lock("fixture");
+      assert_eq!(statuses(&unlocked.unwrap()), [
+        ("foundry.toml [profile.ci]", Some("fixture"), AccountStatus::Ok),
+        (".env", Some("fixture"), AccountStatus::Mismatch),
+        ("Makefile:4", None, AccountStatus::Missing),
+      ]);
+    }
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[tokio::test]
+  async fn needs_a_foundry_project_and_the_keystore_list() {
+    let _serial = serial().await;
+    let dir = project();
+    let err = detect_project_accounts(&FakeCast::recorded(), &dir.join("missing")).await.unwrap_err();
+    assert!(err.ends_with("is not a Foundry project, it has no foundry.toml"), "{}", err);
+
+    let cast = FakeCast::recorded().on(&["wallet", "list"], FakeResponse::failure("Error: permission denied\n", 1));
+    assert_eq!(detect_project_accounts(&cast, &dir).await.unwrap_err(), "Error: permission denied\n");
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
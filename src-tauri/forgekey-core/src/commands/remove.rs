@@ -1,15 +1,10 @@
 use std::fs;
-use dirs::home_dir;
 use log::error;
+use crate::utils::get_keystore_dir;
+use crate::session;
 
 pub fn remove_keystore(keystore_name: String) -> Result<(), String> {
-	let home = home_dir().ok_or_else(|| {
-		let err_msg = "Could not find home directory".to_string();
-		error!("{}", err_msg);
-		err_msg
-	})?;
-
-	let keystore_path = home.join(".foundry").join("keystores");
+	let keystore_path = get_keystore_dir()?;
 
 	let full_path = keystore_path.join(&keystore_name);
 
@@ -26,5 +21,19 @@ pub fn remove_keystore(keystore_name: String) -> Result<(), String> {
 			err_msg
 		})?;
 
+	// Don't keep the decrypted key of a keystore that no longer exists
+	session::lock(&keystore_name);
+
 	Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fails_for_missing_keystores() {
+		let err = remove_keystore("forgekey-test-missing-keystore".to_string()).unwrap_err();
+		assert_eq!(err, "Keystore file 'forgekey-test-missing-keystore' does not exist");
+	}
+}
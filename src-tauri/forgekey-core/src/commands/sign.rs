@@ -0,0 +1,243 @@
+use crate::cast::{parse, version, CastExecutor};
+use crate::utils::redact_secrets;
+use crate::models::{Password, SecretString, TransactionRequest};
+use crate::pty::PtyConfig;
+use crate::session;
+use crate::commands::{locked_error, pty_error_message};
+use log::error;
+use tokio_util::sync::CancellationToken;
+
+// Signing runs cast with the password of the unlocked session, so the key
+// itself never leaves the keystore. Keystores have to be unlocked first
+// (see `unlock_keystore`).
+
+/// Sign `message` the `personal_sign` way (EIP-191). A `0x` prefixed message
+/// is signed as the bytes it encodes, anything else as UTF-8 text.
+pub async fn sign_message(cast: &dyn CastExecutor, keystore_name: &str, message: &str, cancel: CancellationToken) -> Result<String, String> {
+  // "--" keeps a message starting with a dash from being read as a flag
+  let output = run_signing(cast, keystore_name, "cast wallet sign", &["wallet", "sign", "--account", keystore_name, "--", message], cancel).await?;
+  parse::signature(&output)
+}
+
+/// Sign EIP-712 typed data, given as the JSON `eth_signTypedData_v4` takes
+pub async fn sign_typed_data(cast: &dyn CastExecutor, keystore_name: &str, typed_data: &str, cancel: CancellationToken) -> Result<String, String> {
+  let output = run_signing(cast, keystore_name, "cast wallet sign", &["wallet", "sign", "--account", keystore_name, "--data", "--", typed_data], cancel).await?;
+  parse::signature(&output)
+}
+
+/// Sign a transaction without sending it and return it RLP encoded.
+/// Nonce, gas limit, fees and chain id have to be set, cast is run offline
+/// and can't look them up.
+pub async fn sign_transaction(cast: &dyn CastExecutor, keystore_name: &str, tx: &TransactionRequest, cancel: CancellationToken) -> Result<String, String> {
+  let mut args = vec!["mktx".to_string(), "--account".to_string(), keystore_name.to_string()];
+
+  let required = |name: &str, value: &Option<String>| {
+    value.as_deref().map(|value| quantity(name, value)).unwrap_or_else(|| {
+      let err_msg = format!("Transaction is missing {}", name);
+      error!("{}", err_msg);
+      Err(err_msg)
+    })
+  };
+  args.extend(["--nonce".to_string(), required("nonce", &tx.nonce)?]);
+  args.extend(["--gas-limit".to_string(), required("gas", &tx.gas)?]);
+  args.extend(["--chain".to_string(), required("chainId", &tx.chain_id)?]);
+  if let Some(value) = &tx.value {
+    args.extend(["--value".to_string(), quantity("value", value)?]);
+  }
+
+  match (&tx.max_fee_per_gas, &tx.gas_price) {
+    (Some(max_fee), _) => {
+      args.extend(["--gas-price".to_string(), quantity("maxFeePerGas", max_fee)?]);
+      args.extend(["--priority-gas-price".to_string(), required("maxPriorityFeePerGas", &tx.max_priority_fee_per_gas)?]);
+    }
+    (None, Some(gas_price)) => {
+      args.extend(["--legacy".to_string(), "--gas-price".to_string(), quantity("gasPrice", gas_price)?]);
+    }
+    (None, None) => {
+      let err_msg = "Transaction is missing gasPrice or maxFeePerGas".to_string();
+      error!("{}", err_msg);
+      return Err(err_msg);
+    }
+  }
+
+  let data = tx.data.as_deref().filter(|data| !data.is_empty() && *data != "0x");
+  match (&tx.to, data) {
+    // Raw calldata in place of a function signature
+    (Some(to), data) => {
+      if to.len() != 42 {
+        return Err(invalid("to", to));
+      }
+      args.push(hex_data("to", to)?);
+      if let Some(data) = data {
+        args.push(hex_data("data", data)?);
+      }
+    }
+    (None, Some(init_code)) => args.extend(["--create".to_string(), hex_data("data", init_code)?]),
+    (None, None) => {
+      let err_msg = "Transaction has neither a recipient nor contract code".to_string();
+      error!("{}", err_msg);
+      return Err(err_msg);
+    }
+  }
+
+  let args: Vec<&str> = args.iter().map(String::as_str).collect();
+  let output = run_signing(cast, keystore_name, "cast mktx", &args, cancel).await?;
+  parse::raw_transaction(&output)
+}
+
+/// Run a signing command with the password of the unlocked session
+async fn run_signing(cast: &dyn CastExecutor, keystore_name: &str, command: &str, args: &[&str], cancel: CancellationToken) -> Result<SecretString, String> {
+  let password = session::with_unlocked(keystore_name, |session| Password::new(session.password.as_str()))
+    .ok_or_else(|| locked_error(keystore_name))?;
+
+  version::supported(cast).await?;
+
+  // Use PTY-based password input for security (password not visible in process list)
+  let config = PtyConfig { cancel, ..Default::default() }; // Single password prompt
+  let result = cast.run_with_password(args, &password, &config)
+    .await
+    .map_err(|e| pty_error_message(command, e))?;
+
+  if !result.success() {
+    let err_msg = redact_secrets(&result.output);
+    error!("Failed to sign with keystore {}: {}", keystore_name, err_msg);
+    return Err(err_msg);
+  }
+
+  Ok(result.output)
+}
+
+/// Turn a JSON-RPC quantity ("0x2a") into the decimal cast expects
+fn quantity(name: &str, value: &str) -> Result<String, String> {
+  let parsed = match value.strip_prefix("0x") {
+    Some(hex) => u128::from_str_radix(hex, 16),
+    None => value.parse(),
+  };
+  parsed.map(|value| value.to_string()).map_err(|_| invalid(name, value))
+}
+
+/// Check that `value` is `0x` prefixed hex, which also keeps it from being
+/// taken for a cast flag
+fn hex_data(name: &str, value: &str) -> Result<String, String> {
+  match value.strip_prefix("0x") {
+    Some(hex) if hex.len() % 2 == 0 && hex.bytes().all(|b| b.is_ascii_hexdigit()) => Ok(value.to_string()),
+    _ => Err(invalid(name, value)),
+  }
+}
+
+fn invalid(name: &str, value: &str) -> String {
+  let err_msg = format!("Invalid {} in transaction: {:?}", name, value);
+  error!("{}", err_msg);
+  err_msg
+}
+
+#[cfg(all(test, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY, FIXTURE_RAW_TRANSACTION, FIXTURE_SIGNATURE, FIXTURE_WRONG_PASSWORD};
+  use crate::commands::testing::{command_lines, recorded_versions, serial};
+
+  const TO: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+  fn unlock(password: &str) {
+    session::unlock("signer", FIXTURE_ADDRESS.to_string(), Password::new(password), SecretString::new(FIXTURE_PRIVATE_KEY));
+  }
+
+  fn transfer() -> TransactionRequest {
+    TransactionRequest {
+      to: Some(TO.to_string()),
+      value: Some("0xde0b6b3a7640000".to_string()),
+      nonce: Some("0x0".to_string()),
+      gas: Some("0x5208".to_string()),
+      max_fee_per_gas: Some("0x77359400".to_string()),
+      max_priority_fee_per_gas: Some("0x3b9aca00".to_string()),
+      chain_id: Some("0x1".to_string()),
+      ..Default::default()
+    }
+  }
+
+  #[tokio::test]
+  async fn signs_messages_and_typed_data() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      unlock("pw");
+      let message = sign_message(&cast, "signer", "-hello", CancellationToken::new()).await;
+      let typed_data = sign_typed_data(&cast, "signer", "{}", CancellationToken::new()).await;
+      session::lock("signer");
+
+      assert_eq!(message.unwrap(), FIXTURE_SIGNATURE);
+      assert_eq!(typed_data.unwrap(), FIXTURE_SIGNATURE);
+      assert_eq!(command_lines(&cast)[1..], ["wallet sign --account signer -- -hello", "wallet sign --account signer --data -- {}"]);
+      assert_eq!(cast.calls()[1].answered, ["password"]);
+    }
+  }
+
+  #[tokio::test]
+  async fn signs_transactions() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      unlock("pw");
+      let dynamic_fee = sign_transaction(&cast, "signer", &transfer(), CancellationToken::new()).await;
+      let legacy = TransactionRequest { gas_price: Some("1000000000".to_string()), max_fee_per_gas: None, ..transfer() };
+      let legacy = sign_transaction(&cast, "signer", &legacy, CancellationToken::new()).await;
+      session::lock("signer");
+
+      assert_eq!(dynamic_fee.unwrap(), FIXTURE_RAW_TRANSACTION);
+      assert_eq!(legacy.unwrap(), FIXTURE_RAW_TRANSACTION);
+      assert_eq!(command_lines(&cast)[1..], [
+        format!("mktx --account signer --nonce 0 --gas-limit 21000 --chain 1 --value 1000000000000000000 --gas-price 2000000000 --priority-gas-price 1000000000 {}", TO),
+        format!("mktx --account signer --nonce 0 --gas-limit 21000 --chain 1 --value 1000000000000000000 --legacy --gas-price 1000000000 {}", TO),
+      ]);
+    }
+  }
+
+  #[tokio::test]
+  async fn rejects_incomplete_transactions() {
+    let _serial = serial().await;
+    let cast = FakeCast::recorded();
+    unlock("pw");
+    let missing_nonce = sign_transaction(&cast, "signer", &TransactionRequest { nonce: None, ..transfer() }, CancellationToken::new()).await;
+    let flag_as_data = sign_transaction(&cast, "signer", &TransactionRequest { data: Some("--help".to_string()), ..transfer() }, CancellationToken::new()).await;
+    let short_to = sign_transaction(&cast, "signer", &TransactionRequest { to: Some("0x1234".to_string()), ..transfer() }, CancellationToken::new()).await;
+    session::lock("signer");
+
+    assert_eq!(missing_nonce.unwrap_err(), "Transaction is missing nonce");
+    assert_eq!(flag_as_data.unwrap_err(), "Invalid data in transaction: \"--help\"");
+    assert_eq!(short_to.unwrap_err(), "Invalid to in transaction: \"0x1234\"");
+    assert!(cast.calls().is_empty());
+  }
+
+  #[tokio::test]
+  async fn needs_an_unlocked_keystore_and_the_right_password() {
+    let _serial = serial().await;
+    let cast = FakeCast::recorded();
+    let err = sign_message(&cast, "signer", "hello", CancellationToken::new()).await.unwrap_err();
+    assert_eq!(err, "Keystore 'signer' is locked");
+
+    unlock(FIXTURE_WRONG_PASSWORD);
+    let err = sign_message(&cast, "signer", "hello", CancellationToken::new()).await;
+    session::lock("signer");
+    assert_eq!(err.unwrap_err(), "Incorrect password");
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn reports_cast_errors_and_stops_when_cancelled_or_stuck() {
+    let _serial = serial().await;
+    let failing = FakeCast::recorded().on(&["mktx"], FakeResponse::failure("Error: invalid chain id\n", 1).prompt("Enter keystore password:"));
+    let stuck = FakeCast::recorded().on(&["wallet", "sign"], FakeResponse::output("").prompt("Touch your security key"));
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+
+    unlock("pw");
+    let failed = sign_transaction(&failing, "signer", &transfer(), CancellationToken::new()).await;
+    let timed_out = sign_message(&stuck, "signer", "hello", CancellationToken::new()).await;
+    let cancelled = sign_message(&FakeCast::recorded(), "signer", "hello", cancel).await;
+    session::lock("signer");
+
+    assert_eq!(failed.unwrap_err(), "Error: invalid chain id\n");
+    assert_eq!(timed_out.unwrap_err(), "Failed to execute cast wallet sign command: Operation timed out");
+    assert_eq!(cancelled.unwrap_err(), "Failed to execute cast wallet sign command: Operation was cancelled");
+  }
+}
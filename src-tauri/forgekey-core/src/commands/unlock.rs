@@ -0,0 +1,115 @@
+use std::time::Duration;
+use crate::cast::CastExecutor;
+use crate::models::{Password, SecretString};
+use crate::session;
+use log::error;
+use tokio_util::sync::CancellationToken;
+
+/// Decrypt a keystore once and keep it unlocked until the auto-lock timeout,
+/// so later operations on it do not have to ask for the password again.
+pub async fn unlock_keystore(cast: &dyn CastExecutor, keystore_name: String, password: String, cancel: CancellationToken) -> Result<String, String> {
+  let password = Password::from_string(password);
+
+  let address = crate::commands::get_wallet_address(cast, &keystore_name, Some(password.as_str()), cancel.clone()).await?;
+  let private_key = SecretString::from_string(
+    crate::commands::decrypt_keystore(cast, keystore_name.clone(), Password::new(password.as_str()), cancel).await?,
+  );
+
+  session::unlock(&keystore_name, address.clone(), password, private_key);
+
+  Ok(address)
+}
+
+/// Lock a keystore. Returns whether it was unlocked.
+pub fn lock_keystore(keystore_name: &str) -> Result<bool, String> {
+  Ok(session::lock(keystore_name))
+}
+
+/// Lock every keystore and return the names of those that were unlocked
+pub fn lock_all() -> Result<Vec<String>, String> {
+  Ok(session::lock_all())
+}
+
+pub fn list_unlocked_keystores() -> Result<Vec<String>, String> {
+  Ok(session::unlocked_keystores())
+}
+
+pub fn set_auto_lock_timeout(seconds: u64) -> Result<(), String> {
+  if seconds == 0 {
+    let err_msg = "Auto-lock timeout must be at least one second".to_string();
+    error!("{}", err_msg);
+    return Err(err_msg);
+  }
+
+  session::set_auto_lock_timeout(Duration::from_secs(seconds));
+  Ok(())
+}
+
+#[cfg(all(test, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS, FIXTURE_PRIVATE_KEY, FIXTURE_WRONG_PASSWORD};
+  use crate::cast::version;
+  use crate::commands::testing::{command_lines, recorded_versions, serial};
+
+  async fn unlock(cast: &FakeCast, name: &str, password: &str, cancel: CancellationToken) -> Result<String, String> {
+    unlock_keystore(cast, name.to_string(), password.to_string(), cancel).await
+  }
+
+  #[tokio::test]
+  async fn unlocks_and_locks() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      assert_eq!(unlock(&cast, "unlocked", "pw", CancellationToken::new()).await.unwrap(), FIXTURE_ADDRESS);
+      assert_eq!(command_lines(&cast), ["--version", "wallet address --account unlocked", "wallet decrypt-keystore unlocked"]);
+      assert!(list_unlocked_keystores().unwrap().contains(&"unlocked".to_string()));
+      let key = session::with_unlocked("unlocked", |session| session.private_key.as_str().to_string());
+      assert_eq!(key.as_deref(), Some(FIXTURE_PRIVATE_KEY));
+
+      assert!(lock_keystore("unlocked").unwrap());
+      assert!(!lock_keystore("unlocked").unwrap());
+      assert!(!list_unlocked_keystores().unwrap().contains(&"unlocked".to_string()));
+    }
+  }
+
+  #[tokio::test]
+  async fn locks_everything() {
+    let _serial = serial().await;
+    let cast = FakeCast::recorded();
+    for name in ["first", "second"] {
+      unlock(&cast, name, "pw", CancellationToken::new()).await.unwrap();
+    }
+    let mut locked = lock_all().unwrap();
+    locked.sort();
+    assert_eq!(locked, ["first", "second"]);
+    assert!(list_unlocked_keystores().unwrap().is_empty());
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn stays_locked_on_failure() {
+    let _serial = serial().await;
+    let cast = FakeCast::recorded();
+    assert_eq!(unlock(&cast, "unlocked", FIXTURE_WRONG_PASSWORD, CancellationToken::new()).await.unwrap_err(), "Incorrect password");
+
+    let cast = cast.on(&["wallet", "decrypt-keystore"], FakeResponse::failure("Error: unsupported keystore version\n", 1));
+    let err = unlock(&cast, "unlocked", "pw", CancellationToken::new()).await.unwrap_err();
+    assert_eq!(err, "Error: unsupported keystore version");
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let err = unlock(&cast, "unlocked", "pw", cancel).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet address command: Operation was cancelled");
+
+    let cast = FakeCast::recorded().on(&["wallet", "address"], FakeResponse::output("").prompt("Touch your security key"));
+    let err = unlock(&cast, "unlocked", "pw", CancellationToken::new()).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet address command: Operation timed out");
+
+    assert!(!list_unlocked_keystores().unwrap().contains(&"unlocked".to_string()));
+  }
+
+  #[test]
+  fn rejects_a_zero_auto_lock_timeout() {
+    assert!(set_auto_lock_timeout(0).is_err());
+  }
+}
@@ -0,0 +1,135 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::cast::{parse, version, CastExecutor};
+use crate::commands::pty_error_message;
+use crate::models::Password;
+use crate::pty::PtyError;
+use log::error;
+use tokio_util::sync::CancellationToken;
+
+/// Long patterns can take cast hours to find, give up after this
+const VANITY_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Cancellation token of the running vanity generation, if any.
+static VANITY_CANCEL: Mutex<Option<CancellationToken>> = Mutex::new(None);
+
+/// Runs `cast wallet vanity` without blocking the UI, then imports the result.
+/// The run can be stopped through `cancel` or with `cancel_vanity_wallet`,
+/// both kill cast.
+pub async fn create_vanity_wallet(
+  cast: &dyn CastExecutor,
+  starts_with: Option<String>,
+  ends_with: Option<String>,
+  address_label: String,
+  password: Password,
+  cancel: CancellationToken,
+) -> Result<String, String> {
+  version::supported(cast).await?;
+
+  let mut args = vec!["wallet", "vanity"];
+  if let Some(prefix) = &starts_with {
+    args.extend(["--starts-with", prefix.as_str()]);
+  }
+  if let Some(suffix) = &ends_with {
+    args.extend(["--ends-with", suffix.as_str()]);
+  }
+
+  // Store the token for cancel_vanity_wallet
+  *VANITY_CANCEL.lock().unwrap() = Some(cancel.clone());
+
+  let output_result = cast.output(&args, VANITY_TIMEOUT, &cancel).await;
+
+  // Always clear the stored token after the process exits or errors
+  *VANITY_CANCEL.lock().unwrap() = None;
+
+  let output = output_result.map_err(|e| match e {
+    PtyError::Cancelled => "Vanity address generation was cancelled".to_string(),
+    e => pty_error_message("cast wallet vanity", e),
+  })?;
+
+  if !output.success() {
+    let err_msg = output.stderr;
+    // If the process was killed (signal), treat it as cancellation
+    if err_msg.is_empty() || output.exit_code.is_none() {
+      return Err("Vanity address generation was cancelled".to_string());
+    }
+    error!("Failed to create vanity wallet for {}: {}", address_label, err_msg);
+    return Err(err_msg);
+  }
+
+  let wallet_info = parse::vanity(&output.stdout)?;
+  let address = wallet_info.address.clone();
+
+  crate::commands::import_wallet(
+    cast,
+    wallet_info.private_key,
+    address_label,
+    password,
+    cancel,
+  ).await?;
+
+  Ok(address)
+}
+
+/// Kills the running vanity generation process, if any.
+pub fn cancel_vanity_wallet() -> Result<(), String> {
+  if let Some(cancel) = VANITY_CANCEL.lock().unwrap().take() {
+    cancel.cancel();
+  }
+  Ok(())
+}
+
+#[cfg(all(test, feature = "fake-cast"))]
+mod tests {
+  use super::*;
+  use crate::cast::fake::{FakeCast, FakeResponse, FIXTURE_ADDRESS};
+  use crate::commands::testing::{command_lines, recorded_versions, serial};
+
+  async fn vanity(cast: &FakeCast, cancel: CancellationToken) -> Result<String, String> {
+    let (prefix, suffix) = (Some("f39".to_string()), Some("266".to_string()));
+    create_vanity_wallet(cast, prefix, suffix, "vanity".to_string(), Password::new("pw"), cancel).await
+  }
+
+  #[tokio::test]
+  async fn generates_and_imports() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      assert_eq!(vanity(&cast, CancellationToken::new()).await.unwrap(), FIXTURE_ADDRESS);
+      assert_eq!(command_lines(&cast), [
+        "--version",
+        "wallet vanity --starts-with f39 --ends-with 266",
+        "wallet import vanity --interactive",
+      ]);
+    }
+  }
+
+  #[tokio::test]
+  async fn reports_cast_errors() {
+    let _serial = serial().await;
+    for cast in recorded_versions() {
+      version::forget();
+      let cast = cast.on(&["wallet", "vanity"], FakeResponse::failure("Error: invalid hex prefix\n", 1));
+      assert_eq!(vanity(&cast, CancellationToken::new()).await.unwrap_err(), "Error: invalid hex prefix\n");
+
+      // Killed without a word, as cancel_vanity_wallet does
+      let cast = cast.on(&["wallet", "vanity"], FakeResponse::failure("", 1));
+      assert_eq!(vanity(&cast, CancellationToken::new()).await.unwrap_err(), "Vanity address generation was cancelled");
+    }
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn stops_when_cancelled_or_stuck() {
+    let _serial = serial().await;
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let err = vanity(&FakeCast::recorded(), cancel).await.unwrap_err();
+    assert_eq!(err, "Vanity address generation was cancelled");
+    assert!(VANITY_CANCEL.lock().unwrap().is_none());
+    assert!(cancel_vanity_wallet().is_ok());
+
+    let cast = FakeCast::recorded().on(&["wallet", "import"], FakeResponse::output("").prompt("Enter the secret phrase"));
+    let err = vanity(&cast, CancellationToken::new()).await.unwrap_err();
+    assert_eq!(err, "Failed to execute cast wallet import command: Operation timed out");
+  }
+}
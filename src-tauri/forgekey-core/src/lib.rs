@@ -0,0 +1,43 @@
+//! Keystore management on top of Foundry's `cast`, shared by the ForgeKey app,
+//! its headless mode and the local signer. Nothing here depends on Tauri, so
+//! other tools can drive the same keystores the same way.
+//!
+//! - [`commands`]: the operations the app offers (create, import, vanity,
+//!   list, decrypt, remove, unlock and sign). Each takes the
+//!   [`cast::CastExecutor`] to run, usually [`cast::executor()`].
+//! - [`cast`]: running cast, finding it ([`cast::locate`]), checking its
+//!   version ([`cast::version`]) and parsing its output ([`cast::parse`]).
+//!   With the `fake-cast` feature, [`cast::executor()`] replays recorded
+//!   output instead, see `cast::fake`.
+//! - [`pty`]: runs cast in a pseudo-terminal to answer its password prompts,
+//!   so secrets never show up in the process list.
+//! - [`models`]: `Password` and `SecretString`, kept in locked memory and
+//!   zeroized on drop.
+//! - [`session`]: keystores unlocked for a while, with auto-lock.
+//! - [`setup`]: installing and updating Foundry, process hardening and
+//!   environment diagnostics. Progress is reported through callbacks.
+//! - [`settings`] and [`utils`]: the persisted settings, the keystore
+//!   directory and the socket paths.
+//!
+//! Errors are returned as `String`s ready to show to the user, and are
+//! logged through the `log` crate.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), String> {
+//! use forgekey_core::{cast, commands};
+//!
+//! for name in commands::list_wallets(&*cast::executor()).await? {
+//!   println!("{}", name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod cast;
+pub mod commands;
+pub mod models;
+pub mod pty;
+pub mod session;
+pub mod settings;
+pub mod setup;
+pub mod utils;
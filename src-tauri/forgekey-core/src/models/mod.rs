@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+use std::ops::Deref;
+
+mod secret;
+
+pub use secret::{SecretBuffer, SecretString};
+
+/// Transaction to sign, as passed to `eth_signTransaction`.
+/// Quantities are hex strings ("0x2a") as in the JSON-RPC spec.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TransactionRequest {
+  pub from: Option<String>,
+  /// None deploys a contract with `data` as its init code
+  pub to: Option<String>,
+  pub value: Option<String>,
+  /// Also accepted under its newer name, `input`
+  #[serde(alias = "input")]
+  pub data: Option<String>,
+  pub nonce: Option<String>,
+  #[serde(alias = "gasLimit")]
+  pub gas: Option<String>,
+  pub gas_price: Option<String>,
+  pub max_fee_per_gas: Option<String>,
+  pub max_priority_fee_per_gas: Option<String>,
+  pub chain_id: Option<String>,
+}
+
+pub struct WalletInfo {
+  pub address: String,
+  // The address is public information, only the key needs locked memory
+  pub private_key: SecretString,
+}
+
+/// A secure password container backed by locked memory that automatically
+/// zeroizes when dropped and prevents accidental logging or display
+pub struct Password {
+  inner: SecretString,
+}
+
+impl Password {
+  /// Create a new password from a string
+  /// The input string will be cloned, so the caller should zeroize their copy
+  pub fn new(password: &str) -> Self {
+    Self {
+      inner: SecretString::new(password),
+    }
+  }
+    
+  /// Take ownership of an existing String as a password
+  /// The String is copied into locked memory and its original allocation zeroized
+  pub fn from_string(password: String) -> Self {
+    Self {
+      inner: SecretString::from_string(password),
+    }
+  }
+    
+  /// Get a reference to the underlying string
+  pub fn as_str(&self) -> &str {
+    self.inner.as_str()
+  }
+}
+
+impl Deref for Password {
+  type Target = str;
+  
+  fn deref(&self) -> &Self::Target {
+    self.inner.as_str()
+  }
+}
+
+impl Zeroize for Password {
+  fn zeroize(&mut self) {
+    self.inner.zeroize();
+  }
+}
+
+impl Drop for Password {
+  fn drop(&mut self) {
+    self.zeroize();
+  }
+}
+
+// Prevent passwords from being accidentally displayed or logged
+impl std::fmt::Debug for Password {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("[REDACTED]")
+  }
+}
+
+impl std::fmt::Display for Password {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("[REDACTED]")
+  }
+} 
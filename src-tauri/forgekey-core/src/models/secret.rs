@@ -0,0 +1,282 @@
+use std::ops::Deref;
+use zeroize::Zeroize;
+
+/// A fixed-capacity byte buffer for secrets.
+///
+/// On unix the bytes live in their own anonymous mapping that is `mlock`ed
+/// (never swapped to disk), excluded from core dumps where supported, and
+/// surrounded by `PROT_NONE` guard pages so overruns fault instead of reading
+/// neighbouring heap memory. Growing the buffer moves the data into a new
+/// region and wipes the old one, so reallocations never leave copies behind.
+/// The contents are zeroized on drop on every platform.
+pub struct SecretBuffer {
+  region: Region,
+  len: usize,
+}
+
+impl SecretBuffer {
+  /// Create an empty buffer able to hold at least `capacity` bytes without growing.
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self {
+      region: Region::new(capacity),
+      len: 0,
+    }
+  }
+
+  /// Create a zero-filled buffer of `len` bytes, e.g. as a read buffer.
+  pub fn zeroed(len: usize) -> Self {
+    let mut buffer = Self::with_capacity(len);
+    buffer.len = len;
+    buffer
+  }
+
+  /// Copy `bytes` into a new buffer. The caller is responsible for wiping the source.
+  pub fn from_bytes(bytes: &[u8]) -> Self {
+    let mut buffer = Self::with_capacity(bytes.len());
+    buffer.extend_from_slice(bytes);
+    buffer
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.region.as_slice()[..self.len]
+  }
+
+  pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+    let len = self.len;
+    &mut self.region.as_mut_slice()[..len]
+  }
+
+  /// Append `bytes`, moving to a larger locked region if needed.
+  pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+    let needed = self.len + bytes.len();
+    if needed > self.region.capacity() {
+      let mut region = Region::new(needed.max(self.region.capacity() * 2));
+      region.as_mut_slice()[..self.len].copy_from_slice(self.as_bytes());
+      // The old region is zeroized and unmapped when it is dropped here
+      self.region = region;
+    }
+    self.region.as_mut_slice()[self.len..needed].copy_from_slice(bytes);
+    self.len = needed;
+  }
+
+  /// Wipe the contents and reset the length, keeping the locked region.
+  pub fn clear(&mut self) {
+    self.region.as_mut_slice().zeroize();
+    self.len = 0;
+  }
+}
+
+impl Zeroize for SecretBuffer {
+  fn zeroize(&mut self) {
+    self.clear();
+  }
+}
+
+// Prevent secrets from being accidentally displayed or logged
+impl std::fmt::Debug for SecretBuffer {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("[REDACTED]")
+  }
+}
+
+/// A UTF-8 string stored in a `SecretBuffer`.
+pub struct SecretString {
+  inner: SecretBuffer,
+}
+
+impl SecretString {
+  /// Copy `value` into locked memory. The caller should zeroize their copy.
+  pub fn new(value: &str) -> Self {
+    Self {
+      inner: SecretBuffer::from_bytes(value.as_bytes()),
+    }
+  }
+
+  /// Move an existing String into locked memory and wipe the original allocation.
+  pub fn from_string(mut value: String) -> Self {
+    let secret = Self::new(&value);
+    value.zeroize();
+    secret
+  }
+
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self {
+      inner: SecretBuffer::with_capacity(capacity),
+    }
+  }
+
+  pub fn push_str(&mut self, value: &str) {
+    self.inner.extend_from_slice(value.as_bytes());
+  }
+
+  pub fn as_str(&self) -> &str {
+    // Only ever filled from &str, so the bytes are always valid UTF-8
+    std::str::from_utf8(self.inner.as_bytes()).unwrap_or_default()
+  }
+}
+
+impl Deref for SecretString {
+  type Target = str;
+
+  fn deref(&self) -> &Self::Target {
+    self.as_str()
+  }
+}
+
+impl Zeroize for SecretString {
+  fn zeroize(&mut self) {
+    self.inner.zeroize();
+  }
+}
+
+impl std::fmt::Debug for SecretString {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("[REDACTED]")
+  }
+}
+
+impl std::fmt::Display for SecretString {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("[REDACTED]")
+  }
+}
+
+#[cfg(unix)]
+use unix::Region;
+
+#[cfg(not(unix))]
+use fallback::Region;
+
+#[cfg(unix)]
+mod unix {
+  use std::ptr;
+  use std::sync::Once;
+  use log::warn;
+  use zeroize::Zeroize;
+
+  static MLOCK_WARNING: Once = Once::new();
+
+  /// An anonymous mapping laid out as `[guard page][data pages][guard page]`.
+  pub struct Region {
+    base: *mut u8,
+    mapped_len: usize,
+    data: *mut u8,
+    capacity: usize,
+    locked: bool,
+  }
+
+  // The region is uniquely owned and only reachable through &/&mut SecretBuffer
+  unsafe impl Send for Region {}
+  unsafe impl Sync for Region {}
+
+  fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+  }
+
+  impl Region {
+    pub fn new(min_capacity: usize) -> Self {
+      let page = page_size();
+      let capacity = min_capacity.max(1).div_ceil(page) * page;
+      let mapped_len = capacity + 2 * page;
+
+      unsafe {
+        let base = libc::mmap(
+          ptr::null_mut(),
+          mapped_len,
+          libc::PROT_READ | libc::PROT_WRITE,
+          libc::MAP_PRIVATE | libc::MAP_ANON,
+          -1,
+          0,
+        );
+        if base == libc::MAP_FAILED {
+          panic!("Failed to map secret memory: {}", std::io::Error::last_os_error());
+        }
+        let base = base as *mut u8;
+        let data = base.add(page);
+
+        libc::mprotect(base as *mut libc::c_void, page, libc::PROT_NONE);
+        libc::mprotect(data.add(capacity) as *mut libc::c_void, page, libc::PROT_NONE);
+
+        #[cfg(target_os = "linux")]
+        libc::madvise(data as *mut libc::c_void, capacity, libc::MADV_DONTDUMP);
+
+        // mlock is limited by RLIMIT_MEMLOCK; if it fails we still keep the
+        // guard pages and zeroization, so warn once instead of failing
+        let locked = libc::mlock(data as *const libc::c_void, capacity) == 0;
+        if !locked {
+          MLOCK_WARNING.call_once(|| {
+            warn!("Failed to lock secret memory: {}", std::io::Error::last_os_error());
+          });
+        }
+
+        Self { base, mapped_len, data, capacity, locked }
+      }
+    }
+
+    pub fn capacity(&self) -> usize {
+      self.capacity
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+      unsafe { std::slice::from_raw_parts(self.data, self.capacity) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+      unsafe { std::slice::from_raw_parts_mut(self.data, self.capacity) }
+    }
+  }
+
+  impl Drop for Region {
+    fn drop(&mut self) {
+      self.as_mut_slice().zeroize();
+      unsafe {
+        if self.locked {
+          libc::munlock(self.data as *const libc::c_void, self.capacity);
+        }
+        libc::munmap(self.base as *mut libc::c_void, self.mapped_len);
+      }
+    }
+  }
+}
+
+#[cfg(not(unix))]
+mod fallback {
+  use zeroize::Zeroize;
+
+  /// Heap storage for platforms without mlock; never reallocated, zeroized on drop.
+  pub struct Region {
+    data: Box<[u8]>,
+  }
+
+  impl Region {
+    pub fn new(min_capacity: usize) -> Self {
+      Self { data: vec![0u8; min_capacity.max(1)].into_boxed_slice() }
+    }
+
+    pub fn capacity(&self) -> usize {
+      self.data.len()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+      &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+      &mut self.data
+    }
+  }
+
+  impl Drop for Region {
+    fn drop(&mut self) {
+      self.data.zeroize();
+    }
+  }
+}
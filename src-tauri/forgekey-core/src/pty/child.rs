@@ -0,0 +1,116 @@
+use portable_pty::{Child, ExitStatus};
+use std::io::Read;
+use std::time::Duration;
+use log::{debug, error};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio_util::sync::CancellationToken;
+
+use crate::models::SecretBuffer;
+use super::PtyError;
+
+/// Owns a child spawned in a PTY and guarantees it is killed and reaped when
+/// dropped, whether the run finished, timed out, was cancelled or panicked.
+pub struct ChildGuard {
+    /// Only taken by `drop`, to reap the child on a blocking thread
+    child: Option<Box<dyn Child + Send + Sync>>,
+    exited: bool,
+}
+
+impl ChildGuard {
+    pub fn new(child: Box<dyn Child + Send + Sync>) -> Self {
+        Self { child: Some(child), exited: false }
+    }
+
+    /// Poll for the child's exit without blocking the async runtime, giving up
+    /// at `deadline` or when `cancel` fires. The child is killed on drop either way.
+    pub async fn wait(
+        &mut self,
+        deadline: tokio::time::Instant,
+        cancel: &CancellationToken,
+    ) -> Result<ExitStatus, PtyError> {
+        let Some(child) = self.child.as_mut() else {
+            return Err(PtyError::CommandFailed("Child was already reaped".to_string()));
+        };
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    self.exited = true;
+                    return Ok(status);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let msg = format!("{}", e);
+                    error!("Failed to wait for child: {}", msg);
+                    return Err(PtyError::CommandFailed(msg));
+                }
+            }
+
+            tokio::select! {
+                _ = cancel.cancelled() => return Err(PtyError::Cancelled),
+                _ = tokio::time::sleep_until(deadline) => {
+                    error!("PTY operation timed out waiting for the command to exit");
+                    return Err(PtyError::Timeout);
+                }
+                _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+            }
+        }
+    }
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        if self.exited {
+            return;
+        }
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        // portable-pty sends SIGHUP first and escalates to SIGKILL after a short
+        // grace period, so the wait cannot hang, but both block. Keep them off
+        // the async worker when there is one.
+        let mut reap = move || {
+            if let Err(e) = child.kill() {
+                debug!("Failed to kill PTY child: {}", e);
+            }
+            let _ = child.wait();
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(reap)),
+            Err(_) => reap(),
+        }
+    }
+}
+
+/// A chunk of PTY output, or the read error that ended the stream
+pub type ReadEvent = Result<SecretBuffer, String>;
+
+/// Read the PTY on a dedicated thread so a silent child can never block the
+/// caller. The channel closes on EOF; the thread exits once the child is gone
+/// or the receiver is dropped.
+pub fn spawn_reader(mut reader: Box<dyn Read + Send>) -> UnboundedReceiver<ReadEvent> {
+    let (tx, rx) = unbounded_channel();
+
+    std::thread::spawn(move || {
+        let mut buf = SecretBuffer::zeroed(1024);
+        loop {
+            match reader.read(buf.as_bytes_mut()) {
+                Ok(0) => break, // EOF
+                Ok(n) => {
+                    if tx.send(Ok(SecretBuffer::from_bytes(&buf.as_bytes()[..n]))).is_err() {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(format!("{}", e)));
+                    break;
+                }
+            }
+        }
+    });
+
+    rx
+}
@@ -0,0 +1,220 @@
+use regex::bytes::Regex;
+
+/// Prompt pattern matching any line that ends with a colon, e.g.
+/// "Enter password: " or "Enter password to encrypt keystore:".
+/// This is the heuristic `run_with_password` has always used.
+pub const ANY_PROMPT: &str = r":\s*$";
+
+/// Output signatures of cast rejecting a keystore password, checked on every
+/// run. "Mac Mismatch" is what eth-keystore reports when decryption fails.
+pub const WRONG_PASSWORD: &str = r"(?i)mac mismatch|invalid password|wrong password|incorrect password|decryption failed";
+
+/// A single expect/send step of an `ExpectScript`
+pub struct ExpectStep<'a> {
+    /// Short name used in logs and errors, e.g. "password" or "private key"
+    pub name: &'static str,
+    /// Pattern that identifies the prompt in the output since the previous step
+    pub prompt: Regex,
+    /// Secret sent (followed by a newline) when the prompt is seen
+    pub response: &'a str,
+    /// Optional steps are skipped if a later step's prompt shows up first
+    pub optional: bool,
+}
+
+/// An ordered expect/send script for driving an interactive command in a PTY.
+///
+/// Each step waits for its prompt pattern and answers with its own secret.
+/// Failure patterns (e.g. "Error:") abort the run as soon as they appear
+/// anywhere in the output. Wrong-password signatures are always checked, see
+/// `WRONG_PASSWORD`.
+///
+/// ```ignore
+/// let script = ExpectScript::new()
+///     .expect("private key", r"(?i)private key:\s*$", &private_key)
+///     .expect("password", r"(?i)password.*:\s*$", &password)
+///     .expect_optional("password confirmation", r"(?i)password.*:\s*$", &password)
+///     .fail_on(r"(?m)^Error:");
+/// ```
+///
+/// Patterns are matched against raw bytes with ANSI escape codes left in, so
+/// anchor on the prompt text rather than the start of the line. Prompts are
+/// only looked for at the end of the output, anchor them there (`\s*$`).
+pub struct ExpectScript<'a> {
+    steps: Vec<ExpectStep<'a>>,
+    failure_patterns: Vec<Regex>,
+    wrong_password: Regex,
+}
+
+impl Default for ExpectScript<'_> {
+    fn default() -> Self {
+        Self {
+            steps: Vec::new(),
+            failure_patterns: Vec::new(),
+            wrong_password: compile(WRONG_PASSWORD),
+        }
+    }
+}
+
+impl<'a> ExpectScript<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a required step.
+    ///
+    /// # Panics
+    /// If `prompt` is not a valid regex. Patterns are written in code, so this
+    /// is a programming error rather than a runtime condition.
+    pub fn expect(self, name: &'static str, prompt: &str, response: &'a str) -> Self {
+        self.step(name, prompt, response, false)
+    }
+
+    /// Add a step that may not be prompted for, e.g. a confirmation prompt
+    /// that only some versions of cast show.
+    ///
+    /// # Panics
+    /// If `prompt` is not a valid regex.
+    pub fn expect_optional(self, name: &'static str, prompt: &str, response: &'a str) -> Self {
+        self.step(name, prompt, response, true)
+    }
+
+    /// Abort the run with `PtyError::FailurePattern` if `pattern` appears in the output.
+    ///
+    /// # Panics
+    /// If `pattern` is not a valid regex.
+    pub fn fail_on(mut self, pattern: &str) -> Self {
+        self.failure_patterns.push(compile(pattern));
+        self
+    }
+
+    fn step(mut self, name: &'static str, prompt: &str, response: &'a str, optional: bool) -> Self {
+        self.steps.push(ExpectStep {
+            name,
+            prompt: compile(prompt),
+            response,
+            optional,
+        });
+        self
+    }
+
+    pub fn steps(&self) -> &[ExpectStep<'a>] {
+        &self.steps
+    }
+
+    /// Find the step whose prompt matches `output`, starting at `next`.
+    /// Optional steps in between are skipped if a later step matches, but
+    /// never a required one.
+    pub(crate) fn match_prompt(&self, next: usize, output: &[u8]) -> Option<usize> {
+        for (index, step) in self.steps.iter().enumerate().skip(next) {
+            if step.prompt.is_match(output) {
+                return Some(index);
+            }
+            if !step.optional {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Find an already answered step whose prompt matches `output` again,
+    /// i.e. cast rejected the answer and is asking for it a second time.
+    pub(crate) fn match_reprompt(&self, next: usize, output: &[u8]) -> Option<usize> {
+        (0..next.min(self.steps.len())).rev().find(|&index| self.steps[index].prompt.is_match(output))
+    }
+
+    /// The first required step at or after `next` that hasn't been answered yet.
+    pub(crate) fn pending_required(&self, next: usize) -> Option<&ExpectStep<'a>> {
+        self.steps.iter().skip(next).find(|step| !step.optional)
+    }
+
+    pub(crate) fn match_wrong_password(&self, output: &[u8]) -> bool {
+        self.wrong_password.is_match(output)
+    }
+
+    /// Return the line of `output` containing the first failure pattern match, if any.
+    pub(crate) fn match_failure(&self, output: &[u8]) -> Option<String> {
+        self.failure_patterns.iter().find_map(|pattern| {
+            let found = pattern.find(output)?;
+            let start = output[..found.start()].iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+            let end = output[found.end()..].iter().position(|b| *b == b'\n').map_or(output.len(), |i| found.end() + i);
+            Some(String::from_utf8_lossy(&output[start..end]).trim().to_string())
+        })
+    }
+}
+
+fn compile(pattern: &str) -> Regex {
+    Regex::new(pattern).unwrap_or_else(|e| panic!("Invalid expect pattern {:?}: {}", pattern, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import_script() -> ExpectScript<'static> {
+        ExpectScript::new()
+            .expect("private key", r"(?i)private key:\s*$", "key")
+            .expect_optional("keystore name", r"(?i)name:\s*$", "name")
+            .expect("password", r"(?i)password:\s*$", "pass")
+            .expect_optional("password confirmation", r"(?i)password:\s*$", "pass")
+            .fail_on(r"(?m)^Error:")
+    }
+
+    #[test]
+    fn skips_optional_steps_but_not_required_ones() {
+        let script = import_script();
+
+        // The name prompt never shows up, the password answers it
+        assert_eq!(script.match_prompt(1, b"Enter password: "), Some(2));
+        assert_eq!(script.match_prompt(1, b"Keystore name: "), Some(1));
+        // The private key is required, a password prompt can't skip it
+        assert_eq!(script.match_prompt(0, b"Enter password: "), None);
+        // Only the confirmation is left and it's optional
+        assert!(script.pending_required(3).is_none());
+        assert_eq!(script.pending_required(1).map(|step| step.name), Some("password"));
+        assert!(script.pending_required(4).is_none());
+    }
+
+    #[test]
+    fn spots_steps_asked_out_of_order() {
+        let script = import_script();
+
+        // Asking for the private key again after it was answered
+        assert_eq!(script.match_prompt(2, b"Enter private key: "), None);
+        assert_eq!(script.match_reprompt(2, b"Enter private key: "), Some(0));
+        // The latest answered step wins when several match
+        assert_eq!(script.match_reprompt(4, b"Enter password: "), Some(3));
+        // Nothing answered yet, nothing to ask again
+        assert_eq!(script.match_reprompt(0, b"Enter private key: "), None);
+        assert_eq!(script.match_reprompt(9, b"Something else"), None);
+    }
+
+    #[test]
+    fn extracts_the_failing_line() {
+        let script = import_script();
+
+        let output = b"starting\nError: keystore exists\r\nmore output\n";
+        assert_eq!(script.match_failure(output).as_deref(), Some("Error: keystore exists"));
+        assert_eq!(script.match_failure(b"Error: at the end").as_deref(), Some("Error: at the end"));
+        // Only at the start of a line
+        assert_eq!(script.match_failure(b"no Error: here\n"), None);
+    }
+
+    #[test]
+    fn matches_prompts_split_across_chunks() {
+        let script = import_script();
+
+        let mut output = b"Enter priv".to_vec();
+        assert_eq!(script.match_prompt(0, &output), None);
+        output.extend_from_slice(b"ate key: ");
+        assert_eq!(script.match_prompt(0, &output), Some(0));
+    }
+
+    #[test]
+    fn recognizes_wrong_passwords() {
+        let script = ExpectScript::new();
+
+        assert!(script.match_wrong_password(b"Error: Mac Mismatch\n"));
+        assert!(script.match_wrong_password(b"Incorrect password"));
+        assert!(!script.match_wrong_password(b"Enter password: "));
+    }
+}
@@ -0,0 +1,553 @@
+use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use std::borrow::Cow;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use log::{debug, error};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio_util::sync::CancellationToken;
+use zeroize::Zeroize;
+
+use crate::models::{SecretBuffer, SecretString};
+use crate::utils::redact_secrets;
+
+mod child;
+mod expect;
+pub mod operations;
+
+use child::{spawn_reader, ChildGuard};
+pub(crate) use child::ReadEvent;
+pub use expect::{ExpectScript, ExpectStep, ANY_PROMPT, WRONG_PASSWORD};
+
+/// How long the command has to stay silent after showing an already answered
+/// prompt before we decide it is really asking again.
+const REPROMPT_QUIET_PERIOD: Duration = Duration::from_millis(300);
+
+/// Prompts end the output while the command waits for an answer, so only this
+/// much of its end is searched for them. Keeps long output, e.g. a forge
+/// script's, from being scanned again on every chunk.
+const PROMPT_TAIL: usize = 1024;
+
+/// Wrong-password and failure signatures are searched from the start of the
+/// line a chunk continues, but at most this far back
+const SCAN_OVERLAP: usize = 1024;
+
+/// Receives the output of a command line by line while it runs, see `PtyConfig::on_output`
+pub type OnOutput = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Configuration for PTY-based command execution
+pub struct PtyConfig {
+    /// Timeout for the entire operation
+    pub timeout: Duration,
+    /// Number of password prompts to expect (1 for decrypt/address, 2 for import with confirmation)
+    pub password_prompt_count: u8,
+    /// Cancels the operation and kills the command when triggered
+    pub cancel: CancellationToken,
+    /// Directory to run the command in, the app's own when unset
+    pub cwd: Option<PathBuf>,
+    /// Called with every line of output as it arrives, with ANSI escape codes
+    /// stripped and keys redacted. Lines end at `\n` or `\r`, so progress
+    /// bars come through one update at a time.
+    pub on_output: Option<OnOutput>,
+}
+
+impl Default for PtyConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            password_prompt_count: 1,
+            cancel: CancellationToken::new(),
+            cwd: None,
+            on_output: None,
+        }
+    }
+}
+
+/// Result from a successful PTY command execution
+///
+/// The output can contain private keys (e.g. `decrypt-keystore`), so it is kept
+/// in locked memory and zeroized when the result is dropped.
+pub struct PtyResult {
+    /// Combined stdout/stderr output from the command
+    pub output: SecretString,
+    /// Exit code from the command (0 = success)
+    pub exit_code: i32,
+}
+
+impl PtyResult {
+    /// Check if the command succeeded (exit code 0)
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// Errors that can occur during PTY operations
+#[derive(Debug)]
+pub enum PtyError {
+    /// Failed to create PTY pair
+    PtyCreation(String),
+    /// Failed to spawn the command
+    SpawnFailed(String),
+    /// Operation timed out
+    Timeout,
+    /// Operation was cancelled through its cancellation token
+    Cancelled,
+    /// I/O error during read/write
+    IoError(String),
+    /// Command execution failed
+    CommandFailed(String),
+    /// Output matched one of the script's failure patterns (the matching line)
+    FailurePattern(String),
+    /// cast reported that the keystore password is wrong
+    WrongPassword,
+    /// cast asked for an already answered prompt again (the step name), which
+    /// means it rejected the answer
+    Reprompted(&'static str),
+    /// The command exited before showing a required prompt
+    UnexpectedEof {
+        /// Name of the step that was still waiting for its prompt
+        expected: &'static str,
+        /// What the command printed instead, with keys redacted
+        output: String,
+    },
+}
+
+impl PtyError {
+    /// Whether the error means the password was rejected, as opposed to cast
+    /// or the PTY failing, so the UI can show a plain "wrong password".
+    pub fn is_wrong_password(&self) -> bool {
+        match self {
+            PtyError::WrongPassword => true,
+            PtyError::Reprompted(step) => step.contains("password"),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for PtyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PtyError::PtyCreation(msg) => write!(f, "Failed to create PTY: {}", msg),
+            PtyError::SpawnFailed(msg) => write!(f, "Failed to spawn command: {}", msg),
+            PtyError::Timeout => write!(f, "Operation timed out"),
+            PtyError::Cancelled => write!(f, "Operation was cancelled"),
+            PtyError::IoError(msg) => write!(f, "I/O error: {}", msg),
+            PtyError::CommandFailed(msg) => write!(f, "Command failed: {}", msg),
+            PtyError::FailurePattern(line) => write!(f, "{}", line),
+            PtyError::WrongPassword => write!(f, "Incorrect password"),
+            PtyError::Reprompted(step) => write!(f, "Command asked for the {} again", step),
+            PtyError::UnexpectedEof { expected, output } if output.is_empty() => {
+                write!(f, "Command exited before asking for the {}", expected)
+            }
+            PtyError::UnexpectedEof { output, .. } => write!(f, "{}", output),
+        }
+    }
+}
+
+impl std::error::Error for PtyError {}
+
+/// Run a command (cast or forge) driven by an expect/send script via PTY stdin
+///
+/// Each step of `script` waits for its prompt and answers with its own
+/// secret, so flows with several different prompts (private key, old and new
+/// password, mnemonic) can be driven without passing any secret as a command
+/// line argument. `config.password_prompt_count` is ignored, the prompts are
+/// defined by the script.
+///
+/// Output is read on a separate thread, so `config.timeout` is a hard
+/// deadline and `config.cancel` takes effect immediately even if the child
+/// never writes anything. The child is killed and reaped whenever the run
+/// ends early, including when the returned future is dropped.
+pub async fn run_script(
+    program: &Path,
+    args: &[&str],
+    script: &ExpectScript<'_>,
+    config: &PtyConfig,
+) -> Result<PtyResult, PtyError> {
+    let deadline = tokio::time::Instant::now() + config.timeout;
+    let pty_system = native_pty_system();
+
+    // Create a new PTY pair
+    let pair = pty_system
+        .openpty(PtySize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| {
+            let msg = format!("{}", e);
+            error!("Failed to create PTY: {}", msg);
+            PtyError::PtyCreation(msg)
+        })?;
+
+    // Build the command
+    let mut cmd = CommandBuilder::new(program);
+    for arg in args {
+        cmd.arg(*arg);
+    }
+    if let Some(cwd) = &config.cwd {
+        cmd.cwd(cwd);
+    }
+
+    // Spawn the command in the PTY
+    let mut child = ChildGuard::new(pair.slave.spawn_command(cmd).map_err(|e| {
+        let msg = format!("{}", e);
+        error!("Failed to spawn command: {}", msg);
+        PtyError::SpawnFailed(msg)
+    })?);
+
+    // Close our copy of the slave side, otherwise reads never see EOF after
+    // the child exits
+    drop(pair.slave);
+
+    // Get the master for reading/writing
+    let mut master = pair.master.take_writer().map_err(|e| {
+        let msg = format!("{}", e);
+        error!("Failed to get PTY writer: {}", msg);
+        PtyError::IoError(msg)
+    })?;
+
+    let reader = pair.master.try_clone_reader().map_err(|e| {
+        let msg = format!("{}", e);
+        error!("Failed to get PTY reader: {}", msg);
+        PtyError::IoError(msg)
+    })?;
+    let mut chunks = spawn_reader(reader);
+
+    let mut transcript = Transcript::default();
+    answer_prompts(&mut chunks, &mut master, script, config, deadline, &mut transcript).await?;
+
+    // Wait for the child to exit and get the exit code
+    let exit_status = child.wait(deadline, &config.cancel).await?;
+    Ok(transcript.into_result(exit_status.exit_code() as i32))
+}
+
+/// What a command printed after the last prompt it showed, and the steps of
+/// the script that were answered
+pub(crate) struct Transcript {
+    /// Output since the last answered prompt, kept in locked memory since it
+    /// can contain private keys
+    pub output: SecretBuffer,
+    /// Names of the answered steps, in order
+    pub answered: Vec<&'static str>,
+}
+
+impl Default for Transcript {
+    fn default() -> Self {
+        Self {
+            output: SecretBuffer::with_capacity(4096),
+            answered: Vec::new(),
+        }
+    }
+}
+
+impl Transcript {
+    pub fn into_result(self, exit_code: i32) -> PtyResult {
+        // Strip ANSI escape codes from output for cleaner parsing
+        let mut text = String::from_utf8_lossy(self.output.as_bytes());
+        let clean_output = strip_ansi_codes(&text);
+        // Invalid UTF-8 forces a copy outside of locked memory, wipe it
+        if let Cow::Owned(ref mut copy) = text {
+            copy.zeroize();
+        }
+
+        PtyResult {
+            output: clean_output,
+            exit_code,
+        }
+    }
+}
+
+/// Answer the prompts of `script` as the command's output arrives on
+/// `chunks`, writing the answers to `input`, until the output ends.
+///
+/// This is the part of `run_script` that talks to the command, separate from
+/// spawning it so `FakeCast` can drive the same loop without a real process.
+/// `transcript` is filled in as the run goes, so callers can tell which steps
+/// were answered even when it fails.
+pub(crate) async fn answer_prompts(
+    chunks: &mut UnboundedReceiver<ReadEvent>,
+    input: &mut (dyn Write + Send),
+    script: &ExpectScript<'_>,
+    config: &PtyConfig,
+    deadline: tokio::time::Instant,
+    transcript: &mut Transcript,
+) -> Result<(), PtyError> {
+    let output = &mut transcript.output;
+    let mut next_step = 0usize;
+    // Set when an answered prompt shows up again, see REPROMPT_QUIET_PERIOD
+    let mut reprompt: Option<(usize, tokio::time::Instant)> = None;
+    // Incomplete last line of output, held back until it is finished
+    let mut pending_line = SecretBuffer::with_capacity(256);
+
+    // Read until EOF, answering the script's prompts along the way
+    loop {
+        let event = tokio::select! {
+            _ = config.cancel.cancelled() => {
+                debug!("PTY operation cancelled");
+                return Err(PtyError::Cancelled);
+            }
+            _ = tokio::time::sleep_until(deadline) => {
+                error!("PTY operation timed out");
+                return Err(PtyError::Timeout);
+            }
+            _ = sleep_until_some(reprompt.map(|(_, at)| at)) => {
+                let step = script.steps()[reprompt.map_or(0, |(index, _)| index)].name;
+                error!("Command asked for the {} again", step);
+                return Err(PtyError::Reprompted(step));
+            }
+            event = chunks.recv() => event,
+        };
+        reprompt = None;
+
+        let chunk = match event {
+            Some(Ok(chunk)) => chunk,
+            // EOF - process has closed its output
+            None => break,
+            // Reading a PTY whose child has exited fails with EIO on Linux, so
+            // treat read errors as the end of output and let the caller's wait
+            // decide whether the command actually finished
+            Some(Err(msg)) => {
+                debug!("PTY read ended: {}", msg);
+                break;
+            }
+        };
+        let new_from = output.len();
+        output.extend_from_slice(chunk.as_bytes());
+        if let Some(on_output) = &config.on_output {
+            stream_lines(&mut pending_line, chunk.as_bytes(), script, on_output);
+        }
+
+        let unscanned = &output.as_bytes()[scan_start(output.as_bytes(), new_from)..];
+        if script.match_wrong_password(unscanned) {
+            error!("Command rejected the password");
+            return Err(PtyError::WrongPassword);
+        }
+
+        if let Some(failure) = script.match_failure(unscanned) {
+            return Err(failure_error(failure));
+        }
+
+        let tail = &output.as_bytes()[output.len().saturating_sub(PROMPT_TAIL)..];
+        let Some(index) = script.match_prompt(next_step, tail) else {
+            // Only treat it as a re-prompt once the command goes quiet, so
+            // output that merely pauses after a colon isn't mistaken for one
+            if let Some(index) = script.match_reprompt(next_step, tail) {
+                reprompt = Some((index, tokio::time::Instant::now() + REPROMPT_QUIET_PERIOD));
+            }
+            continue;
+        };
+        let step = &script.steps()[index];
+        debug!("Answering {} prompt", step.name);
+
+        // Send the secret for this step followed by newline
+        input.write_all(step.response.as_bytes()).map_err(|e| {
+            let msg = format!("{}", e);
+            error!("Failed to write {}: {}", step.name, msg);
+            PtyError::IoError(msg)
+        })?;
+        input.write_all(b"\n").map_err(|e| {
+            let msg = format!("{}", e);
+            error!("Failed to write newline: {}", msg);
+            PtyError::IoError(msg)
+        })?;
+        input.flush().map_err(|e| {
+            let msg = format!("{}", e);
+            error!("Failed to flush: {}", msg);
+            PtyError::IoError(msg)
+        })?;
+
+        next_step = index + 1;
+        transcript.answered.push(step.name);
+
+        // Clear the output buffer after handling a prompt to avoid re-detecting it
+        output.clear();
+    }
+
+    if let Some(on_output) = &config.on_output {
+        emit_line(&pending_line, script, on_output);
+    }
+
+    if let Some(step) = script.pending_required(next_step) {
+        let output = redact_secrets(&strip_ansi_codes(&String::from_utf8_lossy(output.as_bytes())));
+        let output = output.trim().to_string();
+        error!("Command exited before asking for the {}: {}", step.name, output);
+        return Err(PtyError::UnexpectedEof { expected: step.name, output });
+    }
+
+    Ok(())
+}
+
+/// Where to search `output` for wrong-password and failure signatures after
+/// new output was appended at `new_from`: the start of the line it continues,
+/// at most `SCAN_OVERLAP` bytes back
+fn scan_start(output: &[u8], new_from: usize) -> usize {
+    let floor = new_from.saturating_sub(SCAN_OVERLAP);
+    output[floor..new_from].iter().rposition(|b| *b == b'\n').map_or(floor, |index| floor + index + 1)
+}
+
+/// Sleep until `at`, or forever if there is nothing to wait for.
+async fn sleep_until_some(at: Option<tokio::time::Instant>) {
+    match at {
+        Some(at) => tokio::time::sleep_until(at).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Pass the lines completed by `chunk` to `on_output`, keeping the unfinished
+/// rest in `pending`
+fn stream_lines(pending: &mut SecretBuffer, chunk: &[u8], script: &ExpectScript<'_>, on_output: &OnOutput) {
+    for (index, part) in chunk.split(|b| *b == b'\n' || *b == b'\r').enumerate() {
+        if index > 0 {
+            emit_line(pending, script, on_output);
+            pending.clear();
+        }
+        pending.extend_from_slice(part);
+    }
+}
+
+fn emit_line(line: &SecretBuffer, script: &ExpectScript<'_>, on_output: &OnOutput) {
+    let line = strip_ansi_codes(&String::from_utf8_lossy(line.as_bytes()));
+    if line.trim().is_empty() {
+        return;
+    }
+
+    let mut text = redact_secrets(line.trim_end());
+    // Commands that leave echo on print our answers back right after the prompt
+    for step in script.steps().iter().filter(|step| !step.response.is_empty()) {
+        if text.contains(step.response) {
+            let mut echoed = std::mem::take(&mut text);
+            text = echoed.replace(step.response, "[REDACTED]");
+            echoed.zeroize();
+        }
+    }
+    on_output(&text);
+    text.zeroize();
+}
+
+fn failure_error(line: String) -> PtyError {
+    // The failing line comes straight from cast, make sure it can't leak a key
+    let line = redact_secrets(&strip_ansi_codes(&line));
+    error!("Command failed: {}", line);
+    PtyError::FailurePattern(line)
+}
+
+/// Strip ANSI escape codes from a string
+fn strip_ansi_codes(s: &str) -> SecretString {
+    let mut result = SecretString::with_capacity(s.len());
+    let mut utf8 = [0u8; 4];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Start of escape sequence
+            if chars.peek() == Some(&'[') {
+                chars.next(); // consume '['
+                // Skip until we hit the end of the escape sequence
+                while let Some(&next) = chars.peek() {
+                    chars.next();
+                    // Escape sequences end with a letter
+                    if next.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+        } else {
+            result.push_str(c.encode_utf8(&mut utf8));
+        }
+    }
+
+    result
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Run `sh -c <shell>` with a short timeout, expecting a private key prompt
+    /// the shell never shows. The shell gets a file to write its pid to as `$0`.
+    async fn run_shell(shell: &str, pid_file: &Path, config: &PtyConfig) -> Result<PtyResult, PtyError> {
+        let script = ExpectScript::new().expect("private key", r"(?i)private key:\s*$", "key");
+        let pid_file = pid_file.to_str().unwrap();
+        run_script(Path::new("sh"), &["-c", shell, pid_file], &script, config).await
+    }
+
+    fn short_config() -> PtyConfig {
+        PtyConfig {
+            timeout: Duration::from_millis(500),
+            ..Default::default()
+        }
+    }
+
+    /// Wait for the shell that wrote `pid_file` to be killed and reaped, i.e.
+    /// not even a zombie is left
+    async fn assert_reaped(pid_file: &Path) {
+        let pid: libc::pid_t = std::fs::read_to_string(pid_file).unwrap().trim().parse().unwrap();
+        let _ = std::fs::remove_file(pid_file);
+        let started = Instant::now();
+        while unsafe { libc::kill(pid, 0) } == 0 {
+            assert!(started.elapsed() < Duration::from_secs(5), "child {} is still around", pid);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[test]
+    fn rescans_only_the_continued_line() {
+        let output = b"Compiling...\nError: Mac Mis";
+        assert_eq!(scan_start(output, output.len()), 13);
+        assert_eq!(scan_start(b"Error: Mac Mis", 14), 0);
+        assert_eq!(scan_start(b"done\n", 5), 5);
+
+        let long_line = vec![b'.'; SCAN_OVERLAP * 3];
+        assert_eq!(scan_start(&long_line, long_line.len()), SCAN_OVERLAP * 2);
+    }
+
+    fn pid_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("forgekey-pty-{}-{}", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn times_out_on_a_silent_child() {
+        let pid_file = pid_file("silent");
+        let started = Instant::now();
+        let result = run_shell(r#"echo $$ > "$0"; exec sleep 30"#, &pid_file, &short_config()).await;
+
+        assert!(matches!(result, Err(PtyError::Timeout)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_reaped(&pid_file).await;
+    }
+
+    #[tokio::test]
+    async fn cancelling_kills_the_child() {
+        let pid_file = pid_file("cancelled");
+        let config = PtyConfig {
+            timeout: Duration::from_secs(30),
+            ..Default::default()
+        };
+        let cancel = config.cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            cancel.cancel();
+        });
+        let result = run_shell(r#"echo $$ > "$0"; printf "Password: "; exec sleep 30"#, &pid_file, &config).await;
+
+        assert!(matches!(result, Err(PtyError::Cancelled)));
+        assert_reaped(&pid_file).await;
+    }
+
+    #[tokio::test]
+    async fn dropping_the_run_kills_the_child() {
+        let pid_file = pid_file("dropped");
+        let config = PtyConfig {
+            timeout: Duration::from_secs(30),
+            ..Default::default()
+        };
+        let run = run_shell(r#"echo $$ > "$0"; printf "Password: "; exec sleep 30"#, &pid_file, &config);
+
+        assert!(tokio::time::timeout(Duration::from_millis(300), run).await.is_err());
+        assert_reaped(&pid_file).await;
+    }
+}
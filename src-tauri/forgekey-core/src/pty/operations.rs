@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use log::{debug, error};
+use tokio_util::sync::CancellationToken;
+
+/// Cancellation tokens of the PTY operations currently running, keyed by the
+/// operation id the frontend passed in, so it can cancel them by id. Each
+/// entry also carries the serial number of the `Operation` that owns it.
+static OPERATIONS: LazyLock<Mutex<HashMap<String, (u64, CancellationToken)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Serial number of the next registered operation
+static NEXT_SERIAL: AtomicU64 = AtomicU64::new(0);
+
+/// A registered cancellable operation. Unregisters itself when dropped.
+pub struct Operation {
+    id: Option<String>,
+    serial: u64,
+    token: CancellationToken,
+}
+
+impl Operation {
+    /// Register a new operation. Without an id it can't be cancelled from the
+    /// frontend, but still gets a token so callers don't need to special-case it.
+    /// Fails if an operation with the same id is still running.
+    pub fn register(id: Option<String>) -> Result<Self, String> {
+        let token = CancellationToken::new();
+        let serial = NEXT_SERIAL.fetch_add(1, Ordering::Relaxed);
+        if let Some(id) = &id {
+            let mut operations = OPERATIONS.lock().unwrap();
+            if operations.contains_key(id) {
+                let err_msg = format!("Operation {} is already running", id);
+                error!("{}", err_msg);
+                return Err(err_msg);
+            }
+            operations.insert(id.clone(), (serial, token.clone()));
+        }
+        Ok(Self { id, serial, token })
+    }
+
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        let Some(id) = &self.id else {
+            return;
+        };
+        let mut operations = OPERATIONS.lock().unwrap();
+        // Only remove our own entry, never one registered under the same id since
+        if operations.get(id).is_some_and(|(serial, _)| *serial == self.serial) {
+            operations.remove(id);
+        }
+    }
+}
+
+/// Cancel the running operation with `id`. Returns whether one was found.
+pub fn cancel(id: &str) -> bool {
+    match OPERATIONS.lock().unwrap().get(id) {
+        Some((_, token)) => {
+            debug!("Cancelling operation {}", id);
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_stay_with_the_running_operation() {
+        let first = Operation::register(Some("duplicate".to_string())).unwrap();
+        assert!(Operation::register(Some("duplicate".to_string())).is_err());
+
+        // The rejected registration must not have unregistered the first one
+        assert!(cancel("duplicate"));
+        assert!(first.token().is_cancelled());
+
+        drop(first);
+        assert!(!cancel("duplicate"));
+        let second = Operation::register(Some("duplicate".to_string())).unwrap();
+        assert!(!second.token().is_cancelled());
+        assert!(Operation::register(None).is_ok() && Operation::register(None).is_ok());
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use log::{debug, info};
+
+use crate::models::{Password, SecretString};
+
+/// How long a keystore stays unlocked when the user has not configured a timeout.
+pub const DEFAULT_AUTO_LOCK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How often the auto-lock thread checks for expired sessions.
+const AUTO_LOCK_TICK: Duration = Duration::from_secs(1);
+
+/// If wall-clock time advances this much more than monotonic time between two
+/// ticks, the machine was asleep (monotonic clocks stop during suspend).
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(5);
+
+/// Decrypted material for a keystore the user has unlocked.
+/// Both secrets live in locked memory and are zeroized when the session is dropped.
+/// They are only readable inside the crate: outside it, handing out a key
+/// takes the password again (see `commands::decrypt_keystore`).
+pub struct UnlockedKeystore {
+  pub address: String,
+  pub(crate) password: Password,
+  pub(crate) private_key: SecretString,
+  unlocked_at: Instant,
+}
+
+static SESSIONS: LazyLock<Mutex<HashMap<String, UnlockedKeystore>>> =
+  LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static AUTO_LOCK_TIMEOUT: Mutex<Duration> = Mutex::new(DEFAULT_AUTO_LOCK_TIMEOUT);
+
+/// Store the decrypted material for `keystore_name`, replacing any previous session.
+pub fn unlock(keystore_name: &str, address: String, password: Password, private_key: SecretString) {
+  let session = UnlockedKeystore {
+    address,
+    password,
+    private_key,
+    unlocked_at: Instant::now(),
+  };
+  SESSIONS.lock().unwrap().insert(keystore_name.to_string(), session);
+  debug!("Unlocked keystore {}", keystore_name);
+}
+
+/// Run `f` against the session for `keystore_name` if it is unlocked and not expired.
+pub fn with_unlocked<T>(keystore_name: &str, f: impl FnOnce(&UnlockedKeystore) -> T) -> Option<T> {
+  let timeout = auto_lock_timeout();
+  let sessions = SESSIONS.lock().unwrap();
+  sessions
+    .get(keystore_name)
+    .filter(|session| session.unlocked_at.elapsed() < timeout)
+    .map(f)
+}
+
+/// Private key of the unlocked `keystore_name`, for commands that re-encrypt
+/// it rather than hand it out
+pub(crate) fn private_key(keystore_name: &str) -> Option<SecretString> {
+  with_unlocked(keystore_name, |session| SecretString::new(session.private_key.as_str()))
+}
+
+/// Lock a single keystore. Returns whether it was unlocked.
+pub fn lock(keystore_name: &str) -> bool {
+  let removed = SESSIONS.lock().unwrap().remove(keystore_name).is_some();
+  if removed {
+    debug!("Locked keystore {}", keystore_name);
+  }
+  removed
+}
+
+/// Lock every keystore and return the names that were unlocked.
+pub fn lock_all() -> Vec<String> {
+  let names: Vec<String> = SESSIONS.lock().unwrap().drain().map(|(name, _)| name).collect();
+  if !names.is_empty() {
+    debug!("Locked {} keystore(s)", names.len());
+  }
+  names
+}
+
+/// Names of the keystores that are currently unlocked.
+pub fn unlocked_keystores() -> Vec<String> {
+  let timeout = auto_lock_timeout();
+  SESSIONS
+    .lock()
+    .unwrap()
+    .iter()
+    .filter(|(_, session)| session.unlocked_at.elapsed() < timeout)
+    .map(|(name, _)| name.clone())
+    .collect()
+}
+
+/// Name of the unlocked keystore holding `address`, compared ignoring case
+pub fn unlocked_keystore_for(address: &str) -> Option<String> {
+  let timeout = auto_lock_timeout();
+  SESSIONS
+    .lock()
+    .unwrap()
+    .iter()
+    .find(|(_, session)| session.unlocked_at.elapsed() < timeout && session.address.eq_ignore_ascii_case(address))
+    .map(|(name, _)| name.clone())
+}
+
+/// Addresses of the keystores that are currently unlocked.
+pub fn unlocked_addresses() -> Vec<String> {
+  let timeout = auto_lock_timeout();
+  SESSIONS
+    .lock()
+    .unwrap()
+    .values()
+    .filter(|session| session.unlocked_at.elapsed() < timeout)
+    .map(|session| session.address.clone())
+    .collect()
+}
+
+pub fn auto_lock_timeout() -> Duration {
+  *AUTO_LOCK_TIMEOUT.lock().unwrap()
+}
+
+/// Change the auto-lock timeout. Applies to sessions that are already unlocked too.
+pub fn set_auto_lock_timeout(timeout: Duration) {
+  *AUTO_LOCK_TIMEOUT.lock().unwrap() = timeout;
+}
+
+/// Drop every session older than the auto-lock timeout and return their names.
+fn lock_expired() -> Vec<String> {
+  let timeout = auto_lock_timeout();
+  let mut sessions = SESSIONS.lock().unwrap();
+  let expired: Vec<String> = sessions
+    .iter()
+    .filter(|(_, session)| session.unlocked_at.elapsed() >= timeout)
+    .map(|(name, _)| name.clone())
+    .collect();
+  for name in &expired {
+    sessions.remove(name);
+  }
+  expired
+}
+
+/// Spawn the background thread that locks keystores when their session expires
+/// or when the machine wakes up from sleep. `on_locked` is called with the name
+/// of every keystore that gets locked so the UI can be told about it.
+pub fn start_auto_lock<F>(on_locked: F)
+where
+  F: Fn(&str) + Send + 'static,
+{
+  std::thread::spawn(move || {
+    let mut last_tick = (Instant::now(), SystemTime::now());
+
+    loop {
+      std::thread::sleep(AUTO_LOCK_TICK);
+
+      let now = (Instant::now(), SystemTime::now());
+      let monotonic_elapsed = now.0.duration_since(last_tick.0);
+      let wall_elapsed = now.1.duration_since(last_tick.1).unwrap_or_default();
+      last_tick = now;
+
+      let locked = if wall_elapsed > monotonic_elapsed + SUSPEND_THRESHOLD {
+        info!("System resumed from sleep, locking all keystores");
+        lock_all()
+      } else {
+        lock_expired()
+      };
+
+      for name in &locked {
+        on_locked(name);
+      }
+    }
+  });
+}
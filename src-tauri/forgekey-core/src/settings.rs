@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use dirs::config_dir;
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+
+/// Same directory Tauri's `app_config_dir` resolves to for our identifier,
+/// without needing an `AppHandle`
+pub const APP_IDENTIFIER: &str = "com.ForgeKey.dev";
+
+/// User settings persisted across restarts in `settings.json`.
+/// Missing fields fall back to their defaults, so older files keep loading.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+  /// cast binary chosen by the user, overriding the automatic lookup
+  pub cast_path: Option<PathBuf>,
+  /// Foundry version installed from a release archive, e.g. "1.2.3"
+  pub pinned_foundry_version: Option<String>,
+  /// Whether the local JSON-RPC signer starts with the app
+  pub signer_enabled: bool,
+  /// Port for the signer instead of `signer::DEFAULT_PORT`
+  pub signer_port: Option<u16>,
+  /// `Origin`s of the web pages the user connected to the signer
+  pub signer_origins: Vec<String>,
+}
+
+static SETTINGS: LazyLock<Mutex<Settings>> = LazyLock::new(|| Mutex::new(load()));
+
+fn settings_path() -> Option<PathBuf> {
+  config_dir().map(|dir| dir.join(APP_IDENTIFIER).join("settings.json"))
+}
+
+fn load() -> Settings {
+  let Some(path) = settings_path().filter(|path| path.exists()) else {
+    return Settings::default();
+  };
+
+  match fs::read_to_string(&path).map(|json| serde_json::from_str(&json)) {
+    Ok(Ok(settings)) => settings,
+    Ok(Err(e)) => {
+      warn!("Ignoring invalid settings file {}: {}", path.display(), e);
+      Settings::default()
+    }
+    Err(e) => {
+      warn!("Failed to read settings file {}: {}", path.display(), e);
+      Settings::default()
+    }
+  }
+}
+
+/// Current settings
+pub fn get() -> Settings {
+  SETTINGS.lock().unwrap().clone()
+}
+
+/// Change the settings with `f` and write them to disk.
+/// Nothing changes if they can't be saved.
+pub fn update(f: impl FnOnce(&mut Settings)) -> Result<(), String> {
+  let mut settings = SETTINGS.lock().unwrap();
+  let mut updated = settings.clone();
+  f(&mut updated);
+
+  let path = settings_path().ok_or_else(|| {
+    let err_msg = "Could not find config directory".to_string();
+    error!("{}", err_msg);
+    err_msg
+  })?;
+  let json = serde_json::to_string_pretty(&updated).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+  // Write to a temporary file first so a crash can't leave a truncated file
+  let tmp_path = path.with_extension("json.tmp");
+  path.parent()
+    .map_or(Ok(()), fs::create_dir_all)
+    .and_then(|_| fs::write(&tmp_path, json))
+    .and_then(|_| fs::rename(&tmp_path, &path))
+    .map_err(|e| {
+      let err_msg = format!("Failed to save settings: {}", e);
+      error!("{}", err_msg);
+      err_msg
+    })?;
+
+  *settings = updated;
+  Ok(())
+}
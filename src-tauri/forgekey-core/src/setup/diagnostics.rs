@@ -0,0 +1,170 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use dirs::home_dir;
+use serde_json::Value;
+use crate::cast::locate::locate;
+use crate::utils::{get_keystore_dir, redact_secrets};
+use super::versions::foundry_versions;
+
+/// What only the running app knows about itself
+pub struct AppInfo {
+	pub version: String,
+	pub log_dir: Option<PathBuf>,
+	/// Whether the tray icon (menu bar extra on macOS) was created
+	pub tray_available: bool,
+}
+
+/// Describe the environment ForgeKey runs in as Markdown for a bug report.
+/// Keystore file names, the home directory and anything shaped like a private
+/// key are left out, so the report can be shared as is.
+pub fn report(app: AppInfo) -> String {
+	let mut report = String::new();
+
+	let _ = writeln!(report, "### ForgeKey {}", app.version);
+	let _ = writeln!(report, "- OS: {} ({})", os_name(), env::consts::ARCH);
+	if cfg!(target_os = "linux") {
+		let _ = writeln!(report, "- Desktop: {}", env_or_unknown("XDG_CURRENT_DESKTOP"));
+		let _ = writeln!(report, "- Session: {}", env_or_unknown("XDG_SESSION_TYPE"));
+	}
+	let _ = writeln!(report, "- Tray: {}", if app.tray_available { "available" } else { "unavailable" });
+	let _ = writeln!(report, "- Logs: {}", app.log_dir.as_deref().map_or("unknown".to_string(), |dir| dir.display().to_string()));
+
+	write_foundry(&mut report);
+	write_keystores(&mut report);
+
+	redact(&report)
+}
+
+fn write_foundry(report: &mut String) {
+	let _ = writeln!(report, "\n### Foundry");
+	let versions = foundry_versions();
+	match locate() {
+		Ok(location) => {
+			let _ = writeln!(report, "- cast: {} ({:?})", location.path.display(), location.source);
+		}
+		Err(e) => {
+			let _ = writeln!(report, "- cast: {}", e);
+		}
+	}
+	let _ = writeln!(report, "- cast version: {}", versions.cast.as_deref().unwrap_or("unknown"));
+	let _ = writeln!(report, "- forge version: {}", versions.forge.as_deref().unwrap_or("unknown"));
+	if let Some(pinned) = &versions.pinned {
+		let _ = writeln!(report, "- Pinned: {}", pinned);
+	}
+	if let Some(warning) = &versions.warning {
+		let _ = writeln!(report, "- Warning: {}", warning);
+	}
+}
+
+fn write_keystores(report: &mut String) {
+	let _ = writeln!(report, "\n### Keystores");
+	let dir = match get_keystore_dir() {
+		Ok(dir) => dir,
+		Err(e) => {
+			let _ = writeln!(report, "- Directory: {}", e);
+			return;
+		}
+	};
+
+	let _ = writeln!(report, "- Directory: {}", dir.display());
+	let entries = match fs::read_dir(&dir) {
+		Ok(entries) => entries,
+		Err(e) => {
+			let _ = writeln!(report, "- Not readable: {}", e);
+			return;
+		}
+	};
+	if let Some(mode) = mode(&dir) {
+		let _ = writeln!(report, "- Directory mode: {:o}", mode);
+	}
+
+	let mut count = 0;
+	let mut too_open = 0;
+	let mut problems = Vec::new();
+	let files = entries
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+		// Same files the keystore watcher skips
+		.filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'));
+	for entry in files {
+		count += 1;
+		let path = entry.path();
+		// Keystores should only be readable by their owner
+		if mode(&path).is_some_and(|mode| mode & 0o077 != 0) {
+			too_open += 1;
+		}
+		if let Err(e) = check_keystore(&path) {
+			// Numbered rather than named, the names often identify the user
+			problems.push(format!("keystore #{}: {}", count, e));
+		}
+	}
+
+	let _ = writeln!(report, "- Files: {}", count);
+	if too_open > 0 {
+		let _ = writeln!(report, "- Readable by other users: {}", too_open);
+	}
+	for problem in problems {
+		let _ = writeln!(report, "- Invalid {}", problem);
+	}
+}
+
+/// Check that `path` reads as a version 3 Web3 Secret Storage file
+fn check_keystore(path: &Path) -> Result<(), String> {
+	let contents = fs::read(path).map_err(|e| format!("unreadable ({})", e))?;
+	let json: Value = serde_json::from_slice(&contents).map_err(|e| format!("not JSON ({})", e))?;
+
+	// Geth writes "crypto", some older tools "Crypto"
+	if !json.get("crypto").or_else(|| json.get("Crypto")).is_some_and(Value::is_object) {
+		return Err("no crypto section".to_string());
+	}
+	match json.get("version").and_then(Value::as_u64) {
+		Some(3) => Ok(()),
+		Some(version) => Err(format!("unsupported version {}", version)),
+		None => Err("no version".to_string()),
+	}
+}
+
+#[cfg(unix)]
+fn mode(path: &Path) -> Option<u32> {
+	use std::os::unix::fs::PermissionsExt;
+	fs::metadata(path).ok().map(|metadata| metadata.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn mode(_path: &Path) -> Option<u32> {
+	None
+}
+
+fn os_name() -> String {
+	#[cfg(target_os = "linux")]
+	if let Some(name) = fs::read_to_string("/etc/os-release").ok().and_then(|release| {
+		release
+			.lines()
+			.find_map(|line| line.strip_prefix("PRETTY_NAME="))
+			.map(|name| name.trim_matches('"').to_string())
+	}) {
+		return name;
+	}
+
+	#[cfg(target_os = "macos")]
+	if let Ok(output) = std::process::Command::new("sw_vers").arg("-productVersion").output() {
+		return format!("macOS {}", String::from_utf8_lossy(&output.stdout).trim());
+	}
+
+	env::consts::OS.to_string()
+}
+
+fn env_or_unknown(name: &str) -> String {
+	env::var(name).ok().filter(|value| !value.is_empty()).unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Hide the user name behind `~` along with anything that looks like a key
+fn redact(report: &str) -> String {
+	let report = match home_dir() {
+		Some(home) if home.parent().is_some() => report.replace(&*home.to_string_lossy(), "~"),
+		_ => report.to_string(),
+	};
+	redact_secrets(&report)
+}
@@ -0,0 +1,412 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use dirs::{data_dir, home_dir};
+use flate2::read::GzDecoder;
+use log::{error, info};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use crate::cast::locate::{forget, locate, verify, CastLocation, CAST_EXECUTABLE};
+use crate::settings::{self, APP_IDENTIFIER};
+
+/// Where a Foundry check or installation is at, sent to the UI as it progresses
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InstallStage {
+	Checking,
+	/// cast wasn't found, installing needs the user's go-ahead
+	Missing,
+	/// Fetching and running the foundryup installer script
+	Downloading,
+	/// Running foundryup to install the binaries
+	Installing,
+	Done,
+	Failed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct InstallStatus {
+	pub stage: InstallStage,
+	/// Output line for progress, the cast path when done, stderr on failure
+	pub message: Option<String>,
+}
+
+impl InstallStatus {
+	pub(super) fn new(stage: InstallStage, message: Option<String>) -> Self {
+		Self { stage, message }
+	}
+}
+
+/// Set while an installation is running, so the UI can't start a second one
+static INSTALLING: AtomicBool = AtomicBool::new(false);
+
+/// Look for cast and report `Done` with its path, or `Missing`.
+/// Never installs anything, see `start_install`.
+pub fn check_foundry(on_status: impl Fn(InstallStatus)) -> bool {
+	on_status(InstallStatus::new(InstallStage::Checking, None));
+
+	match locate() {
+		Ok(location) => {
+			info!("Cast is installed at {}", location.path.display());
+			on_status(InstallStatus::new(InstallStage::Done, Some(location.path.display().to_string())));
+			true
+		}
+		Err(_) => {
+			info!("Cast not found, waiting for the user to install Foundry");
+			on_status(InstallStatus::new(InstallStage::Missing, None));
+			false
+		}
+	}
+}
+
+/// Install Foundry with foundryup on a background thread, reporting progress
+/// through `on_status`. Only call this after the user agreed to it. Can be
+/// called again to retry after a failure.
+pub fn start_install(on_status: impl Fn(InstallStatus) + Send + 'static) -> Result<(), String> {
+	run_exclusive(on_status, |on_status| {
+		install_foundryup(on_status)?;
+		// After installing foundryup, run it to install Foundry
+		run_foundryup(on_status, &[])
+	})
+}
+
+/// Run `job` on a background thread unless another installation is running,
+/// then pick up the binary it installed and report `Done` or `Failed`.
+pub(super) fn run_exclusive(
+	on_status: impl Fn(InstallStatus) + Send + 'static,
+	job: impl FnOnce(&dyn Fn(InstallStatus)) -> Result<(), String> + Send + 'static,
+) -> Result<(), String> {
+	if INSTALLING.swap(true, Ordering::SeqCst) {
+		let err_msg = "Foundry installation is already running".to_string();
+		error!("{}", err_msg);
+		return Err(err_msg);
+	}
+
+	thread::spawn(move || {
+		let result = job(&on_status).and_then(|_| {
+			// Pick up the freshly installed binary
+			forget();
+			locate()
+		});
+
+		match result {
+			Ok(location) => {
+				info!("Foundry installed successfully");
+				on_status(InstallStatus::new(InstallStage::Done, Some(location.path.display().to_string())));
+			}
+			Err(e) => {
+				error!("Failed to install Foundry: {}", e);
+				on_status(InstallStatus::new(InstallStage::Failed, Some(e)));
+			}
+		}
+		INSTALLING.store(false, Ordering::SeqCst);
+	});
+
+	Ok(())
+}
+
+fn install_foundryup(on_status: &dyn Fn(InstallStatus)) -> Result<(), String> {
+	on_status(InstallStatus::new(InstallStage::Downloading, None));
+
+	// pipefail so a failed download isn't hidden by bash happily running nothing
+	let mut command = Command::new("bash");
+	command.arg("-c").arg("set -o pipefail; curl -fsSL https://foundry.paradigm.xyz | bash");
+	run_streaming(command, InstallStage::Downloading, on_status)
+		.map_err(|e| format!("Failed to install Foundryup: {}", e))
+}
+
+/// Run foundryup with `args`, e.g. `["--install", "nightly"]`
+pub(super) fn run_foundryup(on_status: &dyn Fn(InstallStatus), args: &[&str]) -> Result<(), String> {
+	on_status(InstallStatus::new(InstallStage::Installing, None));
+
+	let foundryup_path = foundry_dir()?.join("bin").join("foundryup");
+	let mut command = Command::new(foundryup_path);
+	command.args(args);
+	run_streaming(command, InstallStage::Installing, on_status)
+		.map_err(|e| format!("Failed to install Foundry: {}", e))
+}
+
+/// `$FOUNDRY_DIR`, or `~/.foundry` where foundryup installs by default
+pub(crate) fn foundry_dir() -> Result<PathBuf, String> {
+	if let Some(dir) = std::env::var_os("FOUNDRY_DIR").filter(|dir| !dir.is_empty()) {
+		return Ok(PathBuf::from(dir));
+	}
+	let home = home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
+	Ok(home.join(".foundry"))
+}
+
+/// Run `command`, reporting each line it prints as progress for `stage`.
+/// Fails with its stderr if it exits unsuccessfully.
+fn run_streaming(mut command: Command, stage: InstallStage, on_status: &dyn Fn(InstallStatus)) -> Result<(), String> {
+	let mut child = command
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.map_err(|e| format!("Failed to execute command: {}", e))?;
+
+	// Collect stderr on its own thread so a chatty command can't fill the pipe
+	let mut stderr = child.stderr.take().expect("stderr is piped");
+	let stderr_reader = thread::spawn(move || {
+		let mut output = String::new();
+		let _ = stderr.read_to_string(&mut output);
+		output
+	});
+
+	if let Some(stdout) = child.stdout.take() {
+		for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+			let line = line.trim().to_string();
+			if !line.is_empty() {
+				on_status(InstallStatus::new(stage, Some(line)));
+			}
+		}
+	}
+
+	let status = child.wait().map_err(|e| format!("Failed to wait for command: {}", e))?;
+	let stderr = stderr_reader.join().unwrap_or_default();
+
+	if status.success() {
+		Ok(())
+	} else if stderr.trim().is_empty() {
+		Err(format!("exited with {}", status))
+	} else {
+		Err(stderr.trim().to_string())
+	}
+}
+
+/// Install cast from a Foundry release archive on disk (e.g.
+/// `foundry_v1.2.3_darwin_arm64.tar.gz`), for machines that can't or shouldn't
+/// run the online installer. The archive must match `expected_sha256`, the
+/// checksum published next to the release. Only `cast` is extracted, into
+/// ForgeKey's own bin directory, and pinned in the settings so it is used
+/// over any other installation.
+pub fn install_from_archive(archive: &Path, expected_sha256: &str) -> Result<CastLocation, String> {
+	let bin_dir = managed_bin_dir()?;
+	let cast_path = extract_verified(archive, expected_sha256, &bin_dir)?;
+
+	let version = verify(&cast_path).map_err(|e| {
+		let err_msg = format!("Extracted cast does not work: {}", e);
+		error!("{}", err_msg);
+		err_msg
+	})?;
+
+	settings::update(|settings| {
+		settings.cast_path = Some(cast_path.clone());
+		settings.pinned_foundry_version = Some(version.to_string());
+	})?;
+	forget();
+
+	info!("Installed cast {} from {}", version, archive.display());
+	locate()
+}
+
+/// Check `archive` against `expected_sha256` and extract its cast into `bin_dir`
+fn extract_verified(archive: &Path, expected_sha256: &str, bin_dir: &Path) -> Result<PathBuf, String> {
+	let expected = parse_sha256(expected_sha256)?;
+	fs::create_dir_all(bin_dir).map_err(|e| format!("Failed to create {}: {}", bin_dir.display(), e))?;
+
+	// Hash a private copy and extract from that, so the archive can't be
+	// swapped between checking and extracting it
+	let copy_path = bin_dir.join("foundry.tar.gz.part");
+	let result = copy_and_hash(archive, &copy_path).and_then(|actual| {
+		if actual != expected {
+			let err_msg = format!("Checksum mismatch for {}: expected {}, got {}", archive.display(), expected, actual);
+			error!("{}", err_msg);
+			return Err(err_msg);
+		}
+		extract_cast(&copy_path, bin_dir)
+	});
+	let _ = fs::remove_file(&copy_path);
+	result
+}
+
+/// Where `install_from_archive` puts cast, managed by ForgeKey alone
+/// (`versions` keeps its rollback copies next to it)
+pub(super) fn managed_bin_dir() -> Result<PathBuf, String> {
+	data_dir()
+		.map(|dir| dir.join(APP_IDENTIFIER).join("foundry").join("bin"))
+		.ok_or_else(|| "Could not find data directory".to_string())
+}
+
+/// Accept the checksum as printed by `sha256sum`/`shasum` ("<hex>  <file>"),
+/// with an optional "sha256:" prefix
+fn parse_sha256(checksum: &str) -> Result<String, String> {
+	let hex = checksum.split_whitespace().next().unwrap_or("");
+	let hex = hex.strip_prefix("sha256:").unwrap_or(hex).to_ascii_lowercase();
+	if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+		let err_msg = "Expected a SHA-256 checksum of 64 hex characters".to_string();
+		error!("{}", err_msg);
+		return Err(err_msg);
+	}
+	Ok(hex)
+}
+
+fn copy_and_hash(source: &Path, destination: &Path) -> Result<String, String> {
+	let mut reader = File::open(source).map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+	let mut writer = File::create(destination).map_err(|e| format!("Failed to copy archive: {}", e))?;
+	let mut hasher = Sha256::new();
+	let mut buf = [0u8; 64 * 1024];
+
+	loop {
+		let n = match reader.read(&mut buf) {
+			Ok(0) => break,
+			Ok(n) => n,
+			Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(e) => return Err(format!("Failed to read {}: {}", source.display(), e)),
+		};
+		hasher.update(&buf[..n]);
+		writer.write_all(&buf[..n]).map_err(|e| format!("Failed to copy archive: {}", e))?;
+	}
+
+	Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Extract the `cast` entry of the archive into `bin_dir`. The entry's own
+/// path is never used as a destination, so a malicious archive can't write
+/// anywhere else.
+fn extract_cast(archive: &Path, bin_dir: &Path) -> Result<PathBuf, String> {
+	let file = File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
+	let mut tar = tar::Archive::new(GzDecoder::new(file));
+	let entries = tar.entries().map_err(|e| format!("Failed to read archive: {}", e))?;
+
+	for entry in entries {
+		let mut entry = entry.map_err(|e| format!("Failed to read archive: {}", e))?;
+		let is_cast = entry.header().entry_type().is_file()
+			&& entry.path().ok().and_then(|path| path.file_name().map(|name| name == CAST_EXECUTABLE)).unwrap_or(false);
+		if !is_cast {
+			continue;
+		}
+
+		// Write next to the final path and move it into place, so a running
+		// cast is never overwritten half way
+		let tmp_path = bin_dir.join(format!("{}.tmp", CAST_EXECUTABLE));
+		let cast_path = bin_dir.join(CAST_EXECUTABLE);
+		let mut out = File::create(&tmp_path).map_err(|e| format!("Failed to extract cast: {}", e))?;
+		io::copy(&mut entry, &mut out).map_err(|e| format!("Failed to extract cast: {}", e))?;
+		drop(out);
+
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::PermissionsExt;
+			fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o755))
+				.map_err(|e| format!("Failed to make cast executable: {}", e))?;
+		}
+		fs::rename(&tmp_path, &cast_path).map_err(|e| format!("Failed to extract cast: {}", e))?;
+		return Ok(cast_path);
+	}
+
+	let err_msg = "The archive does not contain cast".to_string();
+	error!("{}", err_msg);
+	Err(err_msg)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use flate2::write::GzEncoder;
+	use flate2::Compression;
+
+	const CAST: &[u8] = b"#!/bin/sh\necho cast\n";
+
+	/// An empty directory for `test`, removed first if a previous run left it
+	fn temp_dir(test: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("forgekey-{}-{}", test, std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	/// Write a tar.gz of `entries` (path, type, contents or link target) to
+	/// `path` and return its SHA-256. Paths are written as is, `tar` would
+	/// refuse to build the malicious ones.
+	fn write_archive(path: &Path, entries: &[(&str, tar::EntryType, &[u8])]) -> String {
+		let mut builder = tar::Builder::new(GzEncoder::new(File::create(path).unwrap(), Compression::default()));
+		for (name, entry_type, data) in entries {
+			let mut header = tar::Header::new_old();
+			header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+			header.set_entry_type(*entry_type);
+			header.set_mode(0o755);
+			if entry_type.is_symlink() {
+				header.as_old_mut().linkname[..data.len()].copy_from_slice(data);
+				header.set_size(0);
+				header.set_cksum();
+				builder.append(&header, io::empty()).unwrap();
+			} else {
+				header.set_size(data.len() as u64);
+				header.set_cksum();
+				builder.append(&header, *data).unwrap();
+			}
+		}
+		builder.into_inner().unwrap().finish().unwrap();
+		copy_and_hash(path, &path.with_extension("copy")).unwrap()
+	}
+
+	#[test]
+	fn accepts_checksums_as_published() {
+		let hex = "9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08";
+		let expected = hex.to_ascii_lowercase();
+		assert_eq!(parse_sha256(hex).unwrap(), expected);
+		assert_eq!(parse_sha256(&format!("sha256:{}", expected)).unwrap(), expected);
+		assert_eq!(parse_sha256(&format!("{}  foundry_v1.2.3_darwin_arm64.tar.gz\n", expected)).unwrap(), expected);
+		assert!(parse_sha256(&expected[1..]).is_err());
+		assert!(parse_sha256(&format!("md5:{}", expected)).is_err());
+		assert!(parse_sha256("").is_err());
+	}
+
+	#[test]
+	fn rejects_a_checksum_mismatch() {
+		let dir = temp_dir("archive-mismatch");
+		let archive = dir.join("foundry.tar.gz");
+		let actual = write_archive(&archive, &[(CAST_EXECUTABLE, tar::EntryType::Regular, CAST)]);
+		let bin_dir = dir.join("bin");
+
+		let err = extract_verified(&archive, &"0".repeat(64), &bin_dir).unwrap_err();
+		assert!(err.starts_with("Checksum mismatch"), "{}", err);
+		assert!(!bin_dir.join(CAST_EXECUTABLE).exists());
+		assert!(!bin_dir.join("foundry.tar.gz.part").exists());
+
+		let cast_path = extract_verified(&archive, &format!("sha256:{}", actual), &bin_dir).unwrap();
+		assert_eq!(cast_path, bin_dir.join(CAST_EXECUTABLE));
+		assert_eq!(fs::read(&cast_path).unwrap(), CAST);
+		let _ = fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn only_writes_cast_into_the_bin_dir() {
+		let dir = temp_dir("archive-escape");
+		let archive = dir.join("foundry.tar.gz");
+		let bin_dir = dir.join("a").join("b").join("bin");
+		let checksum = write_archive(&archive, &[
+			(CAST_EXECUTABLE, tar::EntryType::Symlink, b"/etc/passwd"),
+			(&format!("../../{}", CAST_EXECUTABLE), tar::EntryType::Regular, CAST),
+		]);
+
+		let cast_path = extract_verified(&archive, &checksum, &bin_dir).unwrap();
+		assert_eq!(cast_path, bin_dir.join(CAST_EXECUTABLE));
+		assert!(!fs::symlink_metadata(&cast_path).unwrap().file_type().is_symlink());
+		assert_eq!(fs::read(&cast_path).unwrap(), CAST);
+		assert!(!dir.join("a").join(CAST_EXECUTABLE).exists());
+		assert!(!dir.join(CAST_EXECUTABLE).exists());
+		let _ = fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn fails_without_cast() {
+		let dir = temp_dir("archive-no-cast");
+		let archive = dir.join("foundry.tar.gz");
+		let bin_dir = dir.join("bin");
+		let checksum = write_archive(&archive, &[
+			("forge", tar::EntryType::Regular, CAST),
+			(CAST_EXECUTABLE, tar::EntryType::Symlink, b"forge"),
+			(CAST_EXECUTABLE, tar::EntryType::Directory, b""),
+		]);
+
+		let err = extract_verified(&archive, &checksum, &bin_dir).unwrap_err();
+		assert_eq!(err, "The archive does not contain cast");
+		assert!(!bin_dir.join(CAST_EXECUTABLE).exists());
+		let _ = fs::remove_dir_all(&dir);
+	}
+}
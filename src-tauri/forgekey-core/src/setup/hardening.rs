@@ -0,0 +1,23 @@
+/// Stop the process from writing core dumps, which would put passwords and
+/// decrypted keys held in memory on disk if the app crashed. Runs before
+/// logging is set up, so failures are returned for the caller to log later.
+pub fn disable_core_dumps() -> Result<(), String> {
+	#[cfg(unix)]
+	unsafe {
+		let limit = libc::rlimit {
+			rlim_cur: 0,
+			rlim_max: 0,
+		};
+		if libc::setrlimit(libc::RLIMIT_CORE, &limit) != 0 {
+			return Err(format!("Failed to disable core dumps: {}", std::io::Error::last_os_error()));
+		}
+
+		// Also blocks ptrace attach from non-root processes of the same user
+		#[cfg(target_os = "linux")]
+		if libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0) != 0 {
+			return Err(format!("Failed to mark process as non-dumpable: {}", std::io::Error::last_os_error()));
+		}
+	}
+
+	Ok(())
+}
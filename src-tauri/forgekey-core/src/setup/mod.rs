@@ -0,0 +1,4 @@
+pub mod diagnostics;
+pub mod foundry;
+pub mod hardening;
+pub mod versions;
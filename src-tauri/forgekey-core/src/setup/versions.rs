@@ -0,0 +1,249 @@
+use std::env::consts::EXE_SUFFIX;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use log::{info, warn};
+use serde::Serialize;
+use crate::cast::locate::{locate, verify, CAST_EXECUTABLE, FORGE_EXECUTABLE};
+use crate::cast::version::{range_warning, CastVersion};
+use crate::settings;
+use super::foundry::{foundry_dir, managed_bin_dir, run_exclusive, run_foundryup, InstallStatus};
+
+/// Binaries foundryup installs, backed up together for rollback
+const FOUNDRY_TOOLS: [&str; 4] = ["forge", "cast", "anvil", "chisel"];
+
+/// File next to the backed up binaries recording their cast version
+const VERSION_FILE: &str = "VERSION";
+
+/// Installed Foundry versions, as shown in the UI
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FoundryVersions {
+	pub cast: Option<String>,
+	pub forge: Option<String>,
+	pub cast_path: Option<PathBuf>,
+	/// Version ForgeKey was told to stay on, if any
+	pub pinned: Option<String>,
+	/// Version `start_rollback` would go back to, if any
+	pub previous: Option<String>,
+	/// Set if cast is outside the supported range or differs from the pin
+	pub warning: Option<String>,
+}
+
+/// Report the cast in use and the forge next to it
+pub fn foundry_versions() -> FoundryVersions {
+	let settings = settings::get();
+	let mut versions = FoundryVersions {
+		pinned: settings.pinned_foundry_version,
+		previous: previous_version(),
+		..Default::default()
+	};
+
+	let location = match locate() {
+		Ok(location) => location,
+		Err(e) => {
+			versions.warning = Some(e);
+			return versions;
+		}
+	};
+
+	let cast = verify(&location.path).ok();
+	versions.forge = forge_version(&location.path.with_file_name(FORGE_EXECUTABLE)).map(|v| v.to_string());
+	versions.cast = cast.map(|v| v.to_string());
+	versions.cast_path = Some(location.path);
+
+	versions.warning = cast.and_then(|cast| range_warning(&cast)).or_else(|| {
+		let (cast, pinned) = (versions.cast.as_ref()?, versions.pinned.as_ref()?);
+		(cast != pinned).then(|| format!("cast {} is in use, but Foundry is pinned to {}", cast, pinned))
+	});
+	if let Some(warning) = &versions.warning {
+		warn!("{}", warning);
+	}
+
+	versions
+}
+
+/// Install a Foundry release ("1.2.3") or channel ("stable", "nightly") with
+/// foundryup on a background thread, reporting progress through `on_status`.
+/// The binaries it replaces are kept for `start_rollback`. A release is
+/// pinned in the settings, a channel clears the pin.
+pub fn start_update(target: &str, on_status: impl Fn(InstallStatus) + Send + 'static) -> Result<(), String> {
+	let release = parse_release(target);
+	if release.is_none() && target != "stable" && target != "nightly" {
+		return Err(format!("Expected a Foundry version like 1.2.3, \"stable\" or \"nightly\", got {:?}", target));
+	}
+	let target = target.to_string();
+
+	run_exclusive(on_status, move |on_status| {
+		let staged = stage_backup()?;
+		if let Err(e) = run_foundryup(on_status, &["--install", &target]) {
+			// The version before the last update is still what a rollback needs
+			if let Ok(staging_dir) = staging_dir() {
+				let _ = fs::remove_dir_all(staging_dir);
+			}
+			return Err(e);
+		}
+		if staged {
+			replace_backup()?;
+		}
+		pin(release.map(|version| version.to_string()))
+	})
+}
+
+/// Put back the Foundry binaries `start_update` replaced last, on a
+/// background thread, reporting progress through `on_status`.
+pub fn start_rollback(on_status: impl Fn(InstallStatus) + Send + 'static) -> Result<(), String> {
+	let Some(previous) = previous_version() else {
+		return Err("No previous Foundry version to roll back to".to_string());
+	};
+
+	run_exclusive(on_status, move |_| {
+		let backup_dir = backup_dir()?;
+		let bin_dir = foundry_dir()?.join("bin");
+		copy_tools(&backup_dir, &bin_dir)?;
+		let _ = fs::remove_dir_all(&backup_dir);
+
+		info!("Rolled Foundry back to {}", previous);
+		let pinned = settings::get().pinned_foundry_version.map(|_| previous);
+		pin(pinned)
+	})
+}
+
+/// "1.2.3" or "v1.2.3" as a version, anything else isn't a release
+fn parse_release(target: &str) -> Option<CastVersion> {
+	let version = target.strip_prefix('v').unwrap_or(target);
+	let mut parts = version.split('.').map(|part| part.parse().ok());
+	match (parts.next()?, parts.next()?, parts.next()?, parts.next()) {
+		(Some(major), Some(minor), Some(patch), None) => Some(CastVersion::new(major, minor, patch)),
+		_ => None,
+	}
+}
+
+/// Make ForgeKey use the cast foundryup manages, pinned to `version` if given
+fn pin(version: Option<String>) -> Result<(), String> {
+	let managed = managed_bin_dir()?;
+	settings::update(|settings| {
+		// A cast installed from an archive would otherwise keep taking precedence
+		if settings.cast_path.as_ref().is_some_and(|path| path.starts_with(&managed)) {
+			settings.cast_path = None;
+		}
+		settings.pinned_foundry_version = version;
+	})
+}
+
+/// Rollback copies live next to ForgeKey's managed bin directory
+fn backup_dir() -> Result<PathBuf, String> {
+	let managed = managed_bin_dir()?;
+	Ok(managed.parent().unwrap_or(&managed).join("previous"))
+}
+
+fn previous_version() -> Option<String> {
+	let version = fs::read_to_string(backup_dir().ok()?.join(VERSION_FILE)).ok()?;
+	Some(version.trim().to_string())
+}
+
+/// Where `stage_backup` copies the binaries until the update succeeds
+fn staging_dir() -> Result<PathBuf, String> {
+	Ok(backup_dir()?.with_file_name("previous.staged"))
+}
+
+/// Copy the binaries in foundryup's bin directory aside, to become the
+/// rollback copy once the update succeeds (see `replace_backup`). Returns
+/// whether there was anything to copy.
+fn stage_backup() -> Result<bool, String> {
+	let bin_dir = foundry_dir()?.join("bin");
+	let Ok(version) = verify(&bin_dir.join(CAST_EXECUTABLE)) else {
+		// Nothing installed by foundryup yet, so nothing to roll back to
+		return Ok(false);
+	};
+
+	let staging_dir = staging_dir()?;
+	let _ = fs::remove_dir_all(&staging_dir);
+	fs::create_dir_all(&staging_dir).map_err(|e| format!("Failed to create {}: {}", staging_dir.display(), e))?;
+	copy_tools(&bin_dir, &staging_dir)?;
+	fs::write(staging_dir.join(VERSION_FILE), version.to_string())
+		.map_err(|e| format!("Failed to back up Foundry: {}", e))?;
+
+	info!("Staged Foundry {} for rollback", version);
+	Ok(true)
+}
+
+/// Swap the staged binaries in for the previous rollback copy
+fn replace_backup() -> Result<(), String> {
+	let (staging_dir, backup_dir) = (staging_dir()?, backup_dir()?);
+	let _ = fs::remove_dir_all(&backup_dir);
+	fs::rename(&staging_dir, &backup_dir).map_err(|e| format!("Failed to back up Foundry: {}", e))?;
+	if let Some(version) = previous_version() {
+		info!("Backed up Foundry {} for rollback", version);
+	}
+	Ok(())
+}
+
+/// Copy the Foundry binaries present in `from` into `to`. Each one is written
+/// next to its destination and moved into place, so a running binary is
+/// never overwritten half way.
+fn copy_tools(from: &Path, to: &Path) -> Result<(), String> {
+	for tool in FOUNDRY_TOOLS {
+		let name = format!("{}{}", tool, EXE_SUFFIX);
+		let source = from.join(&name);
+		if !source.is_file() {
+			continue;
+		}
+		let tmp_path = to.join(format!("{}.tmp", name));
+		fs::copy(&source, &tmp_path)
+			.and_then(|_| fs::rename(&tmp_path, to.join(&name)))
+			.map_err(|e| format!("Failed to copy {}: {}", name, e))?;
+	}
+	Ok(())
+}
+
+fn forge_version(path: &Path) -> Option<CastVersion> {
+	let output = Command::new(path)
+		.arg("--version")
+		.stdin(Stdio::null())
+		.output()
+		.ok()?;
+	CastVersion::parse_forge(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_releases_but_not_channels() {
+		assert_eq!(parse_release("1.2.3"), Some(CastVersion::new(1, 2, 3)));
+		assert_eq!(parse_release("v0.2.0"), Some(CastVersion::new(0, 2, 0)));
+		for target in ["stable", "nightly", "1.2", "1.2.3.4", "v1.2.x", "1.2.3-rc1", "", "vv1.2.3"] {
+			assert_eq!(parse_release(target), None, "{}", target);
+		}
+	}
+
+	#[test]
+	fn copies_tools_back_and_forth() {
+		let dir = std::env::temp_dir().join(format!("forgekey-copy-tools-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		let (bin_dir, backup_dir) = (dir.join("bin"), dir.join("previous"));
+		fs::create_dir_all(&bin_dir).unwrap();
+		fs::create_dir_all(&backup_dir).unwrap();
+		let name = |tool: &str| format!("{}{}", tool, EXE_SUFFIX);
+		fs::write(bin_dir.join(name("cast")), "cast 1.0.0").unwrap();
+		fs::write(bin_dir.join(name("forge")), "forge 1.0.0").unwrap();
+		fs::write(bin_dir.join("foundryup"), "installer").unwrap();
+
+		copy_tools(&bin_dir, &backup_dir).unwrap();
+		fs::write(bin_dir.join(name("cast")), "cast 1.1.0").unwrap();
+		fs::write(bin_dir.join(name("anvil")), "anvil 1.1.0").unwrap();
+		copy_tools(&backup_dir, &bin_dir).unwrap();
+
+		assert_eq!(fs::read_to_string(bin_dir.join(name("cast"))).unwrap(), "cast 1.0.0");
+		assert_eq!(fs::read_to_string(bin_dir.join(name("forge"))).unwrap(), "forge 1.0.0");
+		// Only the tools the backup has are put back
+		assert_eq!(fs::read_to_string(bin_dir.join(name("anvil"))).unwrap(), "anvil 1.1.0");
+		let mut backed_up: Vec<_> = fs::read_dir(&backup_dir).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+		backed_up.sort();
+		assert_eq!(backed_up, [name("cast"), name("forge")]);
+		assert!(!bin_dir.join(format!("{}.tmp", name("cast"))).exists());
+		let _ = fs::remove_dir_all(&dir);
+	}
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+use dirs::{data_dir, home_dir, runtime_dir};
+use log::error;
+
+use crate::settings::APP_IDENTIFIER;
+
+pub fn get_keystore_dir() -> Result<PathBuf, String> {
+  let home = home_dir().ok_or_else(|| {
+    let err_msg = "Could not find home directory".to_string();
+    error!("{}", err_msg);
+    err_msg
+  })?;
+
+  Ok(home.join(".foundry").join("keystores"))
+}
+
+/// Per-user directory for the app's Unix sockets
+pub fn socket_dir() -> Option<PathBuf> {
+  runtime_dir().or_else(data_dir).map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+/// Control socket the app serves `forgekey-cli` on
+pub fn control_socket_path() -> Option<PathBuf> {
+  socket_dir().map(|dir| dir.join("forgekey.sock"))
+}
+
+/// Replace anything shaped like a private key (64 hex characters, with or
+/// without a `0x` prefix) with `[REDACTED]`. Longer hex runs such as
+/// signatures and shorter ones such as addresses are left untouched, and so
+/// are values labelled as hashes (`transactionHash`, `blockHash`, `Hash:`),
+/// which users need to see in cast and forge output.
+pub fn redact_secrets(text: &str) -> String {
+  const KEY_HEX_LEN: usize = 64;
+
+  let bytes = text.as_bytes();
+  let mut result = String::with_capacity(text.len());
+  let mut last = 0;
+  let mut i = 0;
+
+  while i < bytes.len() {
+    if !bytes[i].is_ascii_hexdigit() || (i > 0 && bytes[i - 1].is_ascii_alphanumeric()) {
+      i += 1;
+      continue;
+    }
+
+    // Include a "0x" prefix in the redacted span, but not the "0" on its own
+    let hex_start = if bytes[i] == b'0' && matches!(bytes.get(i + 1), Some(b'x' | b'X')) { i + 2 } else { i };
+    let hex_end = hex_start + bytes[hex_start..].iter().take_while(|b| b.is_ascii_hexdigit()).count();
+    let followed_by_word = bytes.get(hex_end).is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_');
+
+    if hex_end - hex_start == KEY_HEX_LEN && !followed_by_word && !labelled_as_hash(&text[..i]) {
+      result.push_str(&text[last..i]);
+      result.push_str("[REDACTED]");
+      last = hex_end;
+    }
+    i = hex_end.max(i + 1);
+  }
+
+  result.push_str(&text[last..]);
+  result
+}
+
+/// Whether the word right before the end of `prefix`, on the same line and
+/// past separators like `: = "`, ends in "hash"
+fn labelled_as_hash(prefix: &str) -> bool {
+  let line = prefix.rsplit('\n').next().unwrap_or_default();
+  let label = line.trim_end_matches(|c: char| c.is_whitespace() || matches!(c, ':' | '=' | '"' | '\'' | ','));
+  let word_start = label.rfind(|c: char| !c.is_ascii_alphanumeric() && c != '_').map_or(0, |index| index + 1);
+  label[word_start..].to_ascii_lowercase().ends_with("hash")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+  #[test]
+  fn redacts_keys() {
+    assert_eq!(redact_secrets(&format!("PRIVATE_KEY={}", KEY)), "PRIVATE_KEY=[REDACTED]");
+    assert_eq!(redact_secrets(&format!("Private key: {}", &KEY[2..])), "Private key: [REDACTED]");
+    assert_eq!(redact_secrets(&format!("{} and hash {}", KEY, KEY)), format!("[REDACTED] and hash {}", KEY));
+  }
+
+  #[test]
+  fn keeps_hashes_addresses_and_signatures() {
+    for line in [
+      format!("transactionHash      {}", KEY),
+      format!("blockHash {}", KEY),
+      format!("✅  Hash: {}", KEY),
+      format!(r#"{{"transactionHash":"{}"}}"#, KEY),
+      "address 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string(),
+      format!("signature {}{}", KEY, &KEY[2..]),
+    ] {
+      assert_eq!(redact_secrets(&line), line);
+    }
+  }
+
+  #[test]
+  fn hash_label_only_applies_to_its_own_line() {
+    assert_eq!(redact_secrets(&format!("blockHash\n{}", KEY)), "blockHash\n[REDACTED]");
+  }
+}
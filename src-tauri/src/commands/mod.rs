@@ -1,15 +0,0 @@
-mod import;
-mod new;
-mod vanity;
-mod list;
-mod decrypt;
-mod remove;
-
-pub use import::import_wallet;
-pub use new::create_new_wallet; 
-pub use vanity::create_vanity_wallet;
-pub use vanity::cancel_vanity_wallet;
-pub use list::list_wallets;
-pub use list::get_wallet_address;
-pub use decrypt::decrypt_keystore;
-pub use remove::remove_keystore;
\ No newline at end of file
@@ -0,0 +1,272 @@
+use std::io::{self, Write};
+use tokio_util::sync::CancellationToken;
+
+use forgekey_core::cast::{self, CastExecutor};
+use forgekey_core::commands;
+use forgekey_core::models::{Password, SecretString};
+
+// Headless mode, for CI and SSH sessions without a display. `ForgeKey <command>`
+// runs one operation from `commands` and exits before any window is created.
+// Secrets are read from the terminal with echo off or from a file descriptor
+// (`--password-fd 3`), never from the command line where `ps` would show them.
+
+const USAGE: &str = "\
+Usage: ForgeKey <command> [options]
+
+Commands:
+  list                          List keystores
+  create <label>                Create a keystore with a new random key
+  import <label>                Import a private key into a new keystore
+  vanity <label>                Create a keystore with a vanity address
+      --starts-with <hex>         Address prefix
+      --ends-with <hex>           Address suffix
+  remove <label> [--yes]        Delete a keystore
+  export <label>                Print the private key of a keystore
+
+Secrets are asked for on the terminal, or read up to the first newline from
+a file descriptor:
+  --password-fd <fd>            Keystore password
+  --private-key-fd <fd>         Private key to import
+
+Without a command, the app starts as usual.";
+
+const COMMANDS: [&str; 8] = ["list", "create", "import", "vanity", "remove", "export", "help", "--help"];
+
+/// Whether `args` (without the program name) start with a headless command
+pub fn is_command(args: &[String]) -> bool {
+  args.first().is_some_and(|command| COMMANDS.contains(&command.as_str()))
+}
+
+/// Run the command in `args` (without the program name) if there is one and
+/// return the exit code. Returns None when the app should start normally.
+pub fn run(args: &[String]) -> Option<i32> {
+  let command = args.first().filter(|_| is_command(args))?;
+  if command == "help" || command == "--help" {
+    println!("{}", USAGE);
+    return Some(0);
+  }
+
+  let result = Options::parse(&args[1..]).and_then(|options| {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+      .enable_all()
+      .build()
+      .map_err(|e| format!("Failed to start runtime: {}", e))?;
+    runtime.block_on(execute(&*cast::executor(), command, options))
+  });
+
+  Some(match result {
+    Ok(()) => 0,
+    Err(e) if e == USAGE => {
+      eprintln!("{}", USAGE);
+      2
+    }
+    Err(e) => {
+      eprintln!("Error: {}", e);
+      1
+    }
+  })
+}
+
+#[derive(Default)]
+struct Options {
+  label: Option<String>,
+  starts_with: Option<String>,
+  ends_with: Option<String>,
+  password_fd: Option<i32>,
+  private_key_fd: Option<i32>,
+  yes: bool,
+}
+
+impl Options {
+  fn parse(args: &[String]) -> Result<Self, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+      let mut value = || args.next().cloned().ok_or_else(|| format!("{} needs a value", arg));
+      match arg.as_str() {
+        "--starts-with" => options.starts_with = Some(value()?),
+        "--ends-with" => options.ends_with = Some(value()?),
+        "--password-fd" => options.password_fd = Some(parse_fd(&value()?)?),
+        "--private-key-fd" => options.private_key_fd = Some(parse_fd(&value()?)?),
+        "--yes" | "-y" => options.yes = true,
+        "--password" | "--private-key" => {
+          return Err(format!("{} is not accepted on the command line, use {}-fd or the terminal prompt", arg, arg));
+        }
+        flag if flag.starts_with('-') => return Err(format!("Unknown option {}", flag)),
+        label if options.label.is_none() => options.label = Some(label.to_string()),
+        extra => return Err(format!("Unexpected argument {}", extra)),
+      }
+    }
+
+    Ok(options)
+  }
+
+  fn label(&self) -> Result<String, String> {
+    self.label.clone().ok_or_else(|| USAGE.to_string())
+  }
+}
+
+async fn execute(cast: &dyn CastExecutor, command: &str, options: Options) -> Result<(), String> {
+  match command {
+    "list" => {
+      for name in commands::list_wallets(cast).await? {
+        println!("{}", name);
+      }
+    }
+    "create" => {
+      let label = options.label()?;
+      let password = new_password(options.password_fd)?;
+      let address = commands::create_new_wallet(cast, label, password, CancellationToken::new()).await?;
+      println!("{}", address);
+    }
+    "import" => {
+      let label = options.label()?;
+      let private_key = SecretString::from_string(read_secret("Private key", options.private_key_fd)?);
+      let password = new_password(options.password_fd)?;
+      let address = commands::import_wallet(cast, private_key, label, password, CancellationToken::new()).await?;
+      println!("{}", address);
+    }
+    "vanity" => {
+      let label = options.label()?;
+      if options.starts_with.is_none() && options.ends_with.is_none() {
+        return Err("vanity needs --starts-with and/or --ends-with".to_string());
+      }
+      let password = new_password(options.password_fd)?;
+      eprintln!("Searching for a matching address, this can take a while...");
+      let address = commands::create_vanity_wallet(cast, options.starts_with, options.ends_with, label, password, CancellationToken::new()).await?;
+      println!("{}", address);
+    }
+    "remove" => {
+      let label = options.label()?;
+      if !options.yes && !confirm(&format!("Delete keystore '{}'? This can't be undone", label))? {
+        return Err("Cancelled".to_string());
+      }
+      commands::remove_keystore(label)?;
+    }
+    "export" => {
+      let label = options.label()?;
+      let password = Password::from_string(read_secret("Keystore password", options.password_fd)?);
+      let private_key = SecretString::from_string(
+        commands::decrypt_keystore(cast, label, password, CancellationToken::new()).await?,
+      );
+      println!("{}", private_key.as_str());
+    }
+    _ => return Err(USAGE.to_string()),
+  }
+  Ok(())
+}
+
+/// Password for a new keystore. On the terminal it is asked for twice to
+/// catch typos, since a mistyped one would lock the key away for good.
+fn new_password(fd: Option<i32>) -> Result<Password, String> {
+  let password = Password::from_string(read_secret("New keystore password", fd)?);
+  if password.is_empty() {
+    return Err("The password can't be empty".to_string());
+  }
+  if fd.is_none() && *Password::from_string(read_secret("Repeat password", None)?) != *password {
+    return Err("The passwords don't match".to_string());
+  }
+  Ok(password)
+}
+
+/// Read a secret from `fd` if given, otherwise from the terminal without echo
+fn read_secret(prompt: &str, fd: Option<i32>) -> Result<String, String> {
+  match fd {
+    Some(fd) => secret_input::read_fd(fd).map_err(|e| format!("Failed to read {} from fd {}: {}", prompt.to_lowercase(), fd, e)),
+    None => secret_input::read_tty(prompt).map_err(|e| format!("Failed to read {} from the terminal: {}", prompt.to_lowercase(), e)),
+  }
+}
+
+fn confirm(question: &str) -> Result<bool, String> {
+  eprint!("{} [y/N] ", question);
+  io::stderr().flush().map_err(|e| e.to_string())?;
+  let mut answer = String::new();
+  io::stdin().read_line(&mut answer).map_err(|e| e.to_string())?;
+  Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn parse_fd(value: &str) -> Result<i32, String> {
+  value.parse().map_err(|_| format!("Invalid file descriptor {:?}", value))
+}
+
+#[cfg(unix)]
+mod secret_input {
+  use std::fs::{File, OpenOptions};
+  use std::io::{self, Read, Write};
+  use std::mem::ManuallyDrop;
+  use std::os::unix::io::{AsRawFd, FromRawFd};
+  use zeroize::Zeroize;
+
+  /// Read up to the first newline of `fd`, one byte at a time so nothing after
+  /// it is consumed and the same descriptor can carry several secrets
+  pub fn read_fd(fd: i32) -> io::Result<String> {
+    // Borrowed, the descriptor belongs to whoever passed it to us
+    let mut file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+    read_line(&mut *file)
+  }
+
+  /// Prompt on the controlling terminal with echo turned off, so it works
+  /// with stdin and stdout redirected too
+  pub fn read_tty(prompt: &str) -> io::Result<String> {
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    write!(tty, "{}: ", prompt)?;
+    tty.flush()?;
+
+    let fd = tty.as_raw_fd();
+    let mut original = unsafe { std::mem::zeroed::<libc::termios>() };
+    if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    let mut silent = original;
+    silent.c_lflag &= !libc::ECHO;
+    silent.c_lflag |= libc::ECHONL;
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &silent) } != 0 {
+      return Err(io::Error::last_os_error());
+    }
+
+    let result = read_line(&mut tty);
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &original) };
+    result
+  }
+
+  fn read_line(reader: &mut impl Read) -> io::Result<String> {
+    // Sized up front so growing it doesn't leave copies of the secret behind
+    let mut bytes = Vec::with_capacity(1024);
+    let mut byte = [0u8; 1];
+    loop {
+      match reader.read(&mut byte) {
+        Ok(0) => break,
+        Ok(_) if byte[0] == b'\n' => break,
+        Ok(_) => bytes.push(byte[0]),
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+        Err(e) => {
+          bytes.zeroize();
+          return Err(e);
+        }
+      }
+    }
+    if bytes.last() == Some(&b'\r') {
+      bytes.pop();
+    }
+
+    String::from_utf8(bytes).map_err(|e| {
+      let mut bytes = e.into_bytes();
+      bytes.zeroize();
+      io::Error::new(io::ErrorKind::InvalidData, "not valid UTF-8")
+    })
+  }
+}
+
+#[cfg(not(unix))]
+mod secret_input {
+  use std::io;
+
+  pub fn read_fd(_fd: i32) -> io::Result<String> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "file descriptors are only supported on macOS and Linux"))
+  }
+
+  pub fn read_tty(_prompt: &str) -> io::Result<String> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "headless mode is only supported on macOS and Linux"))
+  }
+}
@@ -1,33 +1,49 @@
-use log::{error, LevelFilter};
+use std::path::Path;
+use std::sync::Arc;
+use log::{debug, error, warn, LevelFilter};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_log::TimezoneStrategy;
+use time::macros::format_description;
 #[cfg(target_os = "macos")]
 use tauri::ActivationPolicy;
 
-use crate::models::Password;
+use crate::signer::{SignRequest, SignerStatus};
+use forgekey_core::cast::locate::CastLocation;
+use forgekey_core::commands::{AccountFormat, ExportedKeystore, ImportCandidate, LeakFix, LeakedKey, ProjectAccount};
+use forgekey_core::models::{Password, SecretString};
+use forgekey_core::pty::operations::Operation;
+use forgekey_core::setup::foundry::InstallStatus;
+use forgekey_core::setup::versions::FoundryVersions;
+use forgekey_core::{cast, commands, pty, session, settings, setup, utils};
 
-mod commands;
-mod models;
-mod pty;
-mod setup;
+mod headless;
+mod screen_lock;
+mod signer;
 #[cfg(target_os = "macos")]
 mod tray;
 #[cfg(target_os = "linux")]
 mod tray_linux;
-mod utils;
+mod watcher;
 
 #[tauri::command(rename_all = "snake_case")]
-fn create_new_wallet(address_label: String, password: String) -> Result<String, String> {
-  commands::create_new_wallet(address_label, password)
+async fn create_new_wallet(address_label: String, password: String, operation_id: Option<String>) -> Result<String, String> {
+  let password = Password::from_string(password);
+  let operation = Operation::register(operation_id)?;
+  commands::create_new_wallet(&*cast::executor(), address_label, password, operation.token()).await
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn import_private_key(private_key: String, address_label: String, password: String) -> Result<String, String> {
+async fn import_private_key(private_key: String, address_label: String, password: String, operation_id: Option<String>) -> Result<String, String> {
   let password = Password::from_string(password);
-  commands::import_wallet(private_key, address_label, password)
+  let operation = Operation::register(operation_id)?;
+  commands::import_wallet(&*cast::executor(), SecretString::from_string(private_key), address_label, password, operation.token()).await
 }
 
 #[tauri::command(rename_all = "snake_case")]
-async fn create_vanity_wallet(starts_with: Option<String>, ends_with: Option<String>, address_label: String, password: String) -> Result<String, String> {
-  commands::create_vanity_wallet(starts_with, ends_with, address_label, password).await
+async fn create_vanity_wallet(starts_with: Option<String>, ends_with: Option<String>, address_label: String, password: String, operation_id: Option<String>) -> Result<String, String> {
+  let password = Password::from_string(password);
+  let operation = Operation::register(operation_id)?;
+  commands::create_vanity_wallet(&*cast::executor(), starts_with, ends_with, address_label, password, operation.token()).await
 }
 
 #[tauri::command(rename_all = "snake_case")]
@@ -36,18 +52,306 @@ fn cancel_vanity_wallet() -> Result<(), String> {
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn list_wallets() -> Result<Vec<String>, String> {
-  commands::list_wallets()
+async fn list_wallets() -> Result<Vec<String>, String> {
+  commands::list_wallets(&*cast::executor()).await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn get_wallet_address(keystore_name: String, password: Option<String>, operation_id: Option<String>) -> Result<String, String> {
+  let password = password.map(Password::from_string);
+  let operation = Operation::register(operation_id)?;
+  commands::get_wallet_address(&*cast::executor(), &keystore_name, password.as_deref(), operation.token()).await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn decrypt_keystore(keystore_name: String, password: String, operation_id: Option<String>) -> Result<String, String> {
+  let operation = Operation::register(operation_id)?;
+  commands::decrypt_keystore(&*cast::executor(), keystore_name, Password::from_string(password), operation.token()).await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn unlock_keystore(keystore_name: String, password: String, operation_id: Option<String>) -> Result<String, String> {
+  let operation = Operation::register(operation_id)?;
+  commands::unlock_keystore(&*cast::executor(), keystore_name, password, operation.token()).await
+}
+
+/// Run `forge script <script> --broadcast` in `project_dir`, signing with an
+/// unlocked keystore. Output is streamed as `command-output` events carrying
+/// `operation_id`. Returns the hashes of the transactions sent.
+#[tauri::command(rename_all = "snake_case")]
+async fn run_forge_script(app: AppHandle, project_dir: String, keystore_name: String, script: String, args: Vec<String>, operation_id: Option<String>) -> Result<Vec<String>, String> {
+  let on_output = emit_command_output(app, operation_id.clone());
+  let operation = Operation::register(operation_id)?;
+  commands::run_forge_script(&*cast::executor(), Path::new(&project_dir), &keystore_name, &script, &args, on_output, operation.token()).await
+}
+
+/// Run `cast send` in `project_dir`, like `run_forge_script`
+#[tauri::command(rename_all = "snake_case")]
+async fn cast_send(app: AppHandle, project_dir: String, keystore_name: String, args: Vec<String>, operation_id: Option<String>) -> Result<Vec<String>, String> {
+  let on_output = emit_command_output(app, operation_id.clone());
+  let operation = Operation::register(operation_id)?;
+  commands::cast_send(&*cast::executor(), Path::new(&project_dir), &keystore_name, &args, on_output, operation.token()).await
+}
+
+/// Accounts a Foundry project refers to (foundry.toml, .env, Makefile and
+/// justfile), matched with the keystores.
+#[tauri::command(rename_all = "snake_case")]
+async fn detect_project_accounts(project_dir: String) -> Result<Vec<ProjectAccount>, String> {
+  commands::detect_project_accounts(&*cast::executor(), Path::new(&project_dir)).await
+}
+
+/// Private keys and mnemonics left in plain text under `dir`. Findings carry
+/// the derived address, never the secret.
+#[tauri::command(rename_all = "snake_case")]
+async fn scan_for_plaintext_keys(dir: String, operation_id: Option<String>) -> Result<Vec<LeakedKey>, String> {
+  let operation = Operation::register(operation_id)?;
+  commands::scan_for_plaintext_keys(&*cast::executor(), Path::new(&dir), operation.token()).await
+}
+
+/// Import a key or mnemonic found by `scan_for_plaintext_keys` and replace it in the file
+/// with a reference to the keystore.
+#[tauri::command(rename_all = "snake_case")]
+async fn import_leaked_key(dir: String, path: String, line: usize, position: usize, keystore_name: String, password: String, operation_id: Option<String>) -> Result<LeakFix, String> {
+  let password = Password::from_string(password);
+  let operation = Operation::register(operation_id)?;
+  commands::import_leaked_key(&*cast::executor(), Path::new(&dir), &path, line, position, keystore_name, password, operation.token()).await
+}
+
+/// Accounts of geth, ape, brownie, Hardhat or .env files that can be
+/// imported. `path` is optional for the tools with a default directory.
+#[tauri::command(rename_all = "snake_case")]
+async fn preview_account_import(format: AccountFormat, path: Option<String>, operation_id: Option<String>) -> Result<Vec<ImportCandidate>, String> {
+  let operation = Operation::register(operation_id)?;
+  commands::preview_account_import(&*cast::executor(), format, path.as_deref().map(Path::new), operation.token()).await
+}
+
+/// Import one of the candidates from `preview_account_import`. The password
+/// is only needed for plain private keys, encrypted keystores keep theirs.
+#[tauri::command(rename_all = "snake_case")]
+async fn import_account(path: String, line: Option<usize>, position: usize, keystore_name: String, password: Option<String>, operation_id: Option<String>) -> Result<String, String> {
+  let password = password.map(Password::from_string);
+  let operation = Operation::register(operation_id)?;
+  commands::import_account(&*cast::executor(), Path::new(&path), line, position, keystore_name, password, operation.token()).await
+}
+
+/// Write keystores to `target_dir` as geth `UTC--<timestamp>--<address>`
+/// files. They are only decrypted when `new_password` re-encrypts them.
+#[tauri::command(rename_all = "snake_case")]
+async fn export_keystores(keystore_names: Vec<String>, target_dir: String, ethers: bool, new_password: Option<String>, operation_id: Option<String>) -> Result<Vec<ExportedKeystore>, String> {
+  let new_password = new_password.map(Password::from_string);
+  let operation = Operation::register(operation_id)?;
+  commands::export_keystores(&*cast::executor(), &keystore_names, Path::new(&target_dir), ethers, new_password, operation.token()).await
+}
+
+/// Path of a one-shot pipe handing out the password of an unlocked keystore,
+/// for `--password-file` in commands the user runs.
+#[tauri::command(rename_all = "snake_case")]
+fn create_password_pipe(keystore_name: String) -> Result<String, String> {
+  commands::create_password_pipe(&keystore_name)
+}
+
+/// Cancel a running PTY operation started with the same `operation_id`.
+#[tauri::command(rename_all = "snake_case")]
+fn cancel_operation(operation_id: String) -> Result<(), String> {
+  pty::operations::cancel(&operation_id);
+  Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn lock_keystore(app: AppHandle, keystore_name: String) -> Result<(), String> {
+  if commands::lock_keystore(&keystore_name)? {
+    emit_keystore_locked(app)(&keystore_name);
+  }
+  Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn lock_all(app: AppHandle) -> Result<(), String> {
+  let on_locked = emit_keystore_locked(app);
+  for keystore_name in commands::lock_all()? {
+    on_locked(&keystore_name);
+  }
+  Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn list_unlocked_keystores() -> Result<Vec<String>, String> {
+  commands::list_unlocked_keystores()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn set_auto_lock_timeout(seconds: u64) -> Result<(), String> {
+  commands::set_auto_lock_timeout(seconds)
+}
+
+/// Version of the cast binary in use, e.g. "1.2.3". Fails if it is unsupported.
+#[tauri::command(rename_all = "snake_case")]
+async fn get_cast_version() -> Result<String, String> {
+  cast::version::supported(&*cast::executor()).await.map(|version| version.to_string())
+}
+
+/// Which cast binary is in use and how it was found.
+#[tauri::command(rename_all = "snake_case")]
+fn get_cast_location() -> Result<CastLocation, String> {
+  cast::locate::locate()
 }
 
+/// Use the cast binary at `path`, or look it up automatically again with `null`.
 #[tauri::command(rename_all = "snake_case")]
-fn get_wallet_address(keystore_name: String, password: String) -> Result<String, String> {
-  commands::get_wallet_address(&keystore_name, &password)
+fn set_cast_path(path: Option<String>) -> Result<CastLocation, String> {
+  cast::locate::set_cast_path(path.map(Into::into))
 }
 
+/// Look for Foundry again, reporting the result as `foundry-install-status` events.
 #[tauri::command(rename_all = "snake_case")]
-fn decrypt_keystore(keystore_name: String, password: String) -> Result<String, String> {
-  commands::decrypt_keystore(keystore_name, password)
+fn check_foundry(app: AppHandle) -> Result<(), String> {
+  std::thread::spawn(move || setup::foundry::check_foundry(emit_install_status(app)));
+  Ok(())
+}
+
+/// Install Foundry in the background once the user agreed to it, reporting
+/// progress as `foundry-install-status` events. Also used to retry.
+#[tauri::command(rename_all = "snake_case")]
+fn install_foundry(app: AppHandle) -> Result<(), String> {
+  setup::foundry::start_install(emit_install_status(app))
+}
+
+/// Install cast from a Foundry release archive on disk, checked against its
+/// published SHA-256, without network access.
+#[tauri::command(rename_all = "snake_case")]
+async fn install_foundry_from_archive(archive_path: String, sha256: String) -> Result<CastLocation, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    setup::foundry::install_from_archive(archive_path.as_ref(), &sha256)
+  })
+  .await
+  .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Versions of cast and forge in use, the pinned and the previous version.
+#[tauri::command(rename_all = "snake_case")]
+async fn get_foundry_versions() -> Result<FoundryVersions, String> {
+  tauri::async_runtime::spawn_blocking(setup::versions::foundry_versions)
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Install a Foundry release or channel with foundryup, keeping the current
+/// binaries for `rollback_foundry`. Progress arrives as `foundry-install-status` events.
+#[tauri::command(rename_all = "snake_case")]
+fn update_foundry(app: AppHandle, target: String) -> Result<(), String> {
+  setup::versions::start_update(&target, emit_install_status(app))
+}
+
+/// Go back to the Foundry binaries replaced by the last `update_foundry`.
+#[tauri::command(rename_all = "snake_case")]
+fn rollback_foundry(app: AppHandle) -> Result<(), String> {
+  setup::versions::start_rollback(emit_install_status(app))
+}
+
+/// Describe the environment for a bug report, with personal details redacted.
+#[tauri::command(rename_all = "snake_case")]
+async fn diagnostics(app: AppHandle) -> Result<String, String> {
+  let tray_id = if cfg!(target_os = "macos") { "menu_extra" } else { "system_tray" };
+  let info = setup::diagnostics::AppInfo {
+    version: app.package_info().version.to_string(),
+    log_dir: app.path().app_log_dir().ok(),
+    tray_available: app.tray_by_id(tray_id).is_some(),
+  };
+
+  tauri::async_runtime::spawn_blocking(move || setup::diagnostics::report(info))
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Start the local JSON-RPC signer and start it with the app from now on.
+#[tauri::command(rename_all = "snake_case")]
+async fn start_signer(app: AppHandle, port: Option<u16>) -> Result<SignerStatus, String> {
+  let port = port.or(settings::get().signer_port).unwrap_or(signer::DEFAULT_PORT);
+  let status = signer::start(port, on_sign_request(app)).await?;
+  settings::update(|settings| {
+    settings.signer_enabled = true;
+    settings.signer_port = Some(port);
+  })?;
+  Ok(status)
+}
+
+/// Stop the signer and keep it off on the next launch.
+#[tauri::command(rename_all = "snake_case")]
+fn stop_signer() -> Result<(), String> {
+  signer::stop();
+  settings::update(|settings| settings.signer_enabled = false)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_signer_status() -> SignerStatus {
+  signer::status()
+}
+
+/// Approve or reject a request received as a `signer-request` event.
+#[tauri::command(rename_all = "snake_case")]
+fn respond_signer_request(id: u64, approved: bool) -> Result<(), String> {
+  signer::respond(id, approved)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_signer_origins() -> Vec<String> {
+  signer::connected_origins()
+}
+
+/// Disconnect a web page from the signer, it has to ask again to use it.
+#[tauri::command(rename_all = "snake_case")]
+fn disconnect_signer_origin(origin: String) -> Result<(), String> {
+  signer::disconnect(&origin)
+}
+
+/// Show every signature request in the tray window for approval
+fn on_sign_request(app: AppHandle) -> impl Fn(SignRequest) + Send + Sync + 'static {
+  move |request| {
+    if let Err(e) = app.emit("signer-request", request) {
+      error!("Failed to emit signer-request event: {}", e);
+    }
+    show_main_window(&app);
+  }
+}
+
+/// Tell every window about locked keystores, whether locked from the UI or by
+/// the backend (timeout, sleep, screen lock, forgekey-cli)
+fn emit_keystore_locked(app: AppHandle) -> impl Fn(&str) + Send + Sync + 'static {
+  move |keystore_name| {
+    if let Err(e) = app.emit("keystore-locked", keystore_name) {
+      error!("Failed to emit keystore-locked event: {}", e);
+    }
+  }
+}
+
+/// Bring up the tray window without the user clicking the icon
+fn show_main_window(app: &AppHandle) {
+  #[cfg(target_os = "macos")]
+  tray::show_panel(app);
+
+  #[cfg(not(target_os = "macos"))]
+  if let Some(window) = app.get_webview_window("main") {
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+}
+
+/// Stream the output of `forge script` and `cast send` to the UI
+fn emit_command_output(app: AppHandle, operation_id: Option<String>) -> pty::OnOutput {
+  Arc::new(move |line| {
+    let payload = serde_json::json!({ "operationId": operation_id, "line": line });
+    if let Err(e) = app.emit("command-output", payload) {
+      error!("Failed to emit command-output event: {}", e);
+    }
+  })
+}
+
+fn emit_install_status(app: AppHandle) -> impl Fn(InstallStatus) + Send + 'static {
+  move |status| {
+    if let Err(e) = app.emit("foundry-install-status", status) {
+      error!("Failed to emit foundry-install-status event: {}", e);
+    }
+  }
 }
 
 #[tauri::command(rename_all = "snake_case")]
@@ -57,6 +361,21 @@ fn remove_keystore(keystore_name: String) -> Result<(), String> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+  // Must happen before any password or key is loaded into memory, the
+  // outcome is logged once logging is set up
+  let core_dumps = setup::hardening::disable_core_dumps();
+
+  // Headless commands run and exit before Tauri creates any window
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  if let Err(e) = &core_dumps {
+    if headless::is_command(&args) {
+      eprintln!("Warning: {}", e);
+    }
+  }
+  if let Some(code) = headless::run(&args) {
+    std::process::exit(code);
+  }
+
   tauri::Builder::default()
     .invoke_handler(tauri::generate_handler![
       create_new_wallet,
@@ -66,12 +385,43 @@ pub fn run() {
       list_wallets,
       get_wallet_address,
       decrypt_keystore,
-      remove_keystore
+      remove_keystore,
+      unlock_keystore,
+      run_forge_script,
+      cast_send,
+      create_password_pipe,
+      detect_project_accounts,
+      scan_for_plaintext_keys,
+      import_leaked_key,
+      preview_account_import,
+      import_account,
+      export_keystores,
+      lock_keystore,
+      lock_all,
+      list_unlocked_keystores,
+      set_auto_lock_timeout,
+      cancel_operation,
+      get_cast_version,
+      get_cast_location,
+      set_cast_path,
+      check_foundry,
+      install_foundry,
+      install_foundry_from_archive,
+      get_foundry_versions,
+      update_foundry,
+      rollback_foundry,
+      diagnostics,
+      start_signer,
+      stop_signer,
+      get_signer_status,
+      respond_signer_request,
+      get_signer_origins,
+      disconnect_signer_origin
     ])
     .plugin(tauri_plugin_positioner::init())
     .plugin(tauri_plugin_updater::Builder::new().build())
     .plugin(tauri_plugin_process::init())
-    .setup(|app| {
+    .setup(move |app| {
       // Initialize NSPanel plugin on macOS
       #[cfg(target_os = "macos")]
       app.handle().plugin(tauri_nspanel::init())?;
@@ -83,15 +433,68 @@ pub fn run() {
         LevelFilter::Info   // Less verbose in release builds
       };
 
-      // Enable logging with level filter
+      // Enable logging with level filter, redacting anything that looks like a
+      // private key before it reaches stdout or the log file
+      let time_format = format_description!("[[[year]-[month]-[day]][[[hour]:[minute]:[second]]");
       app.handle().plugin(
         tauri_plugin_log::Builder::default()
           .level(log_level)
+          .format(move |out, message, record| {
+            out.finish(format_args!(
+              "{}[{}][{}] {}",
+              TimezoneStrategy::UseUtc.get_now().format(&time_format).unwrap_or_default(),
+              record.target(),
+              record.level(),
+              utils::redact_secrets(&message.to_string())
+            ))
+          })
           .build(),
       )?;
 
-      if let Err(e) = setup::foundry::check_and_install_foundry() {
-        error!("Failed to check/install Foundry: {}", e);
+      match core_dumps {
+        Ok(()) => debug!("Core dumps disabled"),
+        Err(e) => warn!("{}", e),
+      }
+
+      // Look for Foundry without blocking startup. Installing it is up to the
+      // user, the UI offers it when this reports it missing
+      let handle = app.handle().clone();
+      std::thread::spawn(move || setup::foundry::check_foundry(emit_install_status(handle)));
+
+      // Detect the cast version up front, so an unsupported one shows up in
+      // the logs at startup rather than on the first operation
+      tauri::async_runtime::spawn(async {
+        let _ = cast::version::supported(&*cast::executor()).await;
+      });
+
+      // Keep the UI in sync with keystores created or removed from the CLI
+      if let Err(e) = watcher::start_keystore_watcher(app.handle()) {
+        error!("Failed to start keystore watcher: {}", e);
+      }
+
+      // Bring the signer back if the user left it on
+      let settings = settings::get();
+      if settings.signer_enabled {
+        let port = settings.signer_port.unwrap_or(signer::DEFAULT_PORT);
+        let on_request = on_sign_request(app.handle().clone());
+        tauri::async_runtime::spawn(async move {
+          let _ = signer::start(port, on_request).await;
+        });
+      }
+
+      // Lock unlocked keystores on timeout, after sleep and when the screen
+      // locks, and tell the UI
+      session::start_auto_lock(emit_keystore_locked(app.handle().clone()));
+      screen_lock::start(emit_keystore_locked(app.handle().clone()));
+
+      // Serve forgekey-cli, which shares the unlocked sessions and approval UI
+      #[cfg(unix)]
+      {
+        let on_request = on_sign_request(app.handle().clone());
+        let on_locked = emit_keystore_locked(app.handle().clone());
+        tauri::async_runtime::spawn(async move {
+          let _ = signer::start_control(on_request, on_locked).await;
+        });
       }
 
       #[cfg(target_os = "macos")]
@@ -0,0 +1,117 @@
+use forgekey_core::session;
+use log::info;
+
+// Lock every keystore when the screen locks, so an unlocked keystore isn't
+// left usable at an unattended machine. macOS posts a distributed
+// notification, on Linux logind and the desktop's screensaver signal it on
+// D-Bus. Suspend is detected by `session::start_auto_lock` itself.
+
+/// Start watching for screen locks. `on_locked` is called with the name of
+/// every keystore that gets locked.
+pub fn start<F>(on_locked: F)
+where
+  F: Fn(&str) + Send + Sync + 'static,
+{
+  platform::start(on_locked);
+}
+
+fn lock_all(on_locked: &impl Fn(&str)) {
+  let locked = session::lock_all();
+  if !locked.is_empty() {
+    info!("Screen locked, locking all keystores");
+  }
+  for name in &locked {
+    on_locked(name);
+  }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+  use std::ptr::NonNull;
+  use block2::RcBlock;
+  use objc2_foundation::{NSDistributedNotificationCenter, NSNotification, NSString};
+
+  /// Observe `com.apple.screenIsLocked`. Must be called on the main thread,
+  /// whose run loop delivers the notification.
+  pub fn start<F>(on_locked: F)
+  where
+    F: Fn(&str) + Send + Sync + 'static,
+  {
+    let name = NSString::from_str("com.apple.screenIsLocked");
+    let block = RcBlock::new(move |_notification: NonNull<NSNotification>| super::lock_all(&on_locked));
+
+    let observer = unsafe {
+      NSDistributedNotificationCenter::defaultCenter().addObserverForName_object_queue_usingBlock(Some(&name), None, None, &block)
+    };
+    // Observed for as long as the app runs
+    std::mem::forget(observer);
+  }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+  use std::io::{BufRead, BufReader};
+  use std::process::{Command, Stdio};
+  use std::sync::Arc;
+  use log::{debug, warn};
+
+  /// Bus, sender and the part of a `gdbus monitor` line that means the
+  /// screen locked: logind's `Lock` signal on the session, which
+  /// `loginctl lock-session` and most lock screens send, and the screensaver
+  /// turning on for desktops that only signal that.
+  const SIGNALS: [(&str, &str, &str); 2] = [
+    ("--system", "org.freedesktop.login1", "org.freedesktop.login1.Session.Lock ("),
+    ("--session", "org.freedesktop.ScreenSaver", "org.freedesktop.ScreenSaver.ActiveChanged (true"),
+  ];
+
+  /// Follow the signals with `gdbus monitor`, which comes with GLib and so
+  /// with every desktop the app runs on
+  pub fn start<F>(on_locked: F)
+  where
+    F: Fn(&str) + Send + Sync + 'static,
+  {
+    let on_locked = Arc::new(on_locked);
+
+    for (bus, destination, signal) in SIGNALS {
+      let on_locked = on_locked.clone();
+      std::thread::spawn(move || {
+        let child = Command::new("gdbus")
+          .args(["monitor", bus, "--dest", destination])
+          .stdin(Stdio::null())
+          .stdout(Stdio::piped())
+          .stderr(Stdio::null())
+          .spawn();
+        let mut child = match child {
+          Ok(child) => child,
+          Err(e) => {
+            warn!("Can't watch {} for screen locks, gdbus is unavailable: {}", destination, e);
+            return;
+          }
+        };
+
+        let Some(stdout) = child.stdout.take() else {
+          return;
+        };
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+          if line.contains(signal) {
+            super::lock_all(&*on_locked);
+          }
+        }
+        let _ = child.wait();
+        debug!("Stopped watching {} for screen locks", destination);
+      });
+    }
+  }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+mod platform {
+  use log::debug;
+
+  pub fn start<F>(_on_locked: F)
+  where
+    F: Fn(&str) + Send + Sync + 'static,
+  {
+    debug!("Screen lock detection is not available on this platform");
+  }
+}
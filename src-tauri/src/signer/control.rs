@@ -0,0 +1,82 @@
+use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
+
+use forgekey_core::{cast, commands, session};
+use super::rpc::{invalid_params, rejected, string_param, RpcError, INTERNAL_ERROR, UNAUTHORIZED};
+use super::{approve, preview, OnLocked, OnRequest, Preview};
+
+// `forgekey_*` methods behind the control socket, for the `forgekey-cli`
+// binary. They work on keystore names rather than addresses and share the
+// app's unlocked sessions. Passwords never go over the socket, keystores are
+// unlocked in the app.
+
+/// result: [{ name, address, unlocked }], address only for unlocked keystores
+pub(super) async fn list() -> Result<Value, RpcError> {
+  let names = commands::list_wallets(&*cast::executor())
+    .await
+    .map_err(|e| RpcError::new(INTERNAL_ERROR, e))?;
+
+  Ok(names
+    .into_iter()
+    .map(|name| {
+      let address = session::with_unlocked(&name, |session| session.address.clone());
+      json!({ "name": name, "unlocked": address.is_some(), "address": address })
+    })
+    .collect())
+}
+
+/// params: [name]
+pub(super) fn address(params: &Value) -> Result<Value, RpcError> {
+  let name = string_param(params, 0)?;
+  unlocked_address(name).map(Value::String)
+}
+
+/// params: [name, message], signed like `personal_sign` after the user approved it
+pub(super) async fn sign(params: &Value, on_request: &OnRequest, cancel: &CancellationToken) -> Result<Value, RpcError> {
+  let (name, message) = (string_param(params, 0)?, string_param(params, 1)?);
+  let address = unlocked_address(name)?;
+
+  if !approve(on_request, "personal_sign", None, Some(name.to_string()), Some(address), preview::message(message)).await {
+    return Err(rejected());
+  }
+
+  commands::sign_message(&*cast::executor(), name, message, cancel.child_token())
+    .await
+    .map(Value::String)
+    .map_err(|e| RpcError::new(INTERNAL_ERROR, e))
+}
+
+/// params: [name]. result: path of a one-shot pipe to read the keystore
+/// password from, see `commands::create_password_pipe`, once the user approved it
+pub(super) async fn password_pipe(params: &Value, on_request: &OnRequest) -> Result<Value, RpcError> {
+  let name = string_param(params, 0)?;
+  let address = unlocked_address(name)?;
+
+  if !approve(on_request, "forgekey_passwordPipe", None, Some(name.to_string()), Some(address), Preview::PasswordPipe).await {
+    return Err(rejected());
+  }
+
+  commands::create_password_pipe(name)
+    .map(Value::String)
+    .map_err(|e| RpcError::new(INTERNAL_ERROR, e))
+}
+
+/// params: [name] to lock one keystore, [] to lock all.
+/// result: the names that were unlocked
+pub(super) fn lock(params: &Value, on_locked: &OnLocked) -> Result<Value, RpcError> {
+  let locked = match params.get(0) {
+    None | Some(Value::Null) => session::lock_all(),
+    Some(Value::String(name)) => session::lock(name).then(|| name.clone()).into_iter().collect(),
+    Some(_) => return Err(invalid_params("expected [name] or []")),
+  };
+
+  for name in &locked {
+    on_locked(name);
+  }
+  Ok(json!(locked))
+}
+
+fn unlocked_address(name: &str) -> Result<String, RpcError> {
+  session::with_unlocked(name, |session| session.address.clone())
+    .ok_or_else(|| RpcError::new(UNAUTHORIZED, format!("Keystore '{}' is locked, unlock it in ForgeKey first", name)))
+}
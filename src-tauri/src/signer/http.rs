@@ -0,0 +1,179 @@
+use std::sync::Arc;
+use std::time::Duration;
+use log::warn;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+use super::{is_connected, rpc, OnRequest};
+
+// Just enough HTTP/1.1 for JSON-RPC clients: one POST per connection,
+// answered with `Connection: close`, plus CORS preflight for web pages.
+
+/// Headers beyond this are rejected rather than buffered
+const MAX_HEAD: usize = 16 * 1024;
+/// Large enough for contract deployments
+const MAX_BODY: usize = 4 * 1024 * 1024;
+/// Clients send the whole request at once, connections that don't within
+/// this are closed instead of being held open
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Connections served at once, further ones wait to be accepted. Each can be
+/// waiting for the user to approve a signature.
+const MAX_CONNECTIONS: usize = 32;
+
+struct HttpRequest {
+  method: String,
+  host: Option<String>,
+  origin: Option<String>,
+  body: Vec<u8>,
+}
+
+pub(super) async fn serve(listener: TcpListener, on_request: OnRequest, cancel: CancellationToken) {
+  let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+  loop {
+    let permit = tokio::select! {
+      _ = cancel.cancelled() => return,
+      permit = connections.clone().acquire_owned() => match permit {
+        Ok(permit) => permit,
+        Err(_) => return,
+      },
+    };
+    let stream = tokio::select! {
+      _ = cancel.cancelled() => return,
+      accepted = listener.accept() => match accepted {
+        Ok((stream, _)) => stream,
+        Err(e) => {
+          warn!("Signer accept failed: {}", e);
+          continue;
+        }
+      },
+    };
+
+    let (on_request, cancel) = (on_request.clone(), cancel.clone());
+    tokio::spawn(async move {
+      tokio::select! {
+        _ = cancel.cancelled() => {}
+        _ = serve_connection(stream, &on_request, &cancel) => {}
+      }
+      drop(permit);
+    });
+  }
+}
+
+async fn serve_connection(mut stream: TcpStream, on_request: &OnRequest, cancel: &CancellationToken) {
+  let read = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await;
+  let request = match read.unwrap_or(Err("408 Request Timeout")) {
+    Ok(request) => request,
+    Err(status) => {
+      let _ = respond(&mut stream, status, None, "").await;
+      return;
+    }
+  };
+
+  // A page on another domain resolving to 127.0.0.1 (DNS rebinding) still
+  // sends its own Host, so only accept requests addressed to localhost
+  if !request.host.as_deref().is_some_and(is_local_host) {
+    let _ = respond(&mut stream, "403 Forbidden", None, "").await;
+    return;
+  }
+
+  let origin = request.origin.as_deref();
+  match request.method.as_str() {
+    // Preflight carries no data and lets a page send `eth_requestAccounts`
+    // to connect, its answer is only readable once connected
+    "OPTIONS" => {
+      let _ = respond(&mut stream, "204 No Content", origin, "").await;
+    }
+    "POST" => {
+      let response = rpc::handle(&rpc::Api::Signer, &request.body, origin, on_request, cancel).await;
+      let _ = respond(&mut stream, "200 OK", connected(origin), &response.to_string()).await;
+    }
+    _ => {
+      let _ = respond(&mut stream, "405 Method Not Allowed", None, "").await;
+    }
+  }
+}
+
+/// Read the request line, the headers we need and the body
+async fn read_request(stream: &mut TcpStream) -> Result<HttpRequest, &'static str> {
+  let mut buffer = Vec::new();
+  let head_end = loop {
+    let mut chunk = [0u8; 4096];
+    let read = stream.read(&mut chunk).await.map_err(|_| "400 Bad Request")?;
+    if read == 0 {
+      return Err("400 Bad Request");
+    }
+    buffer.extend_from_slice(&chunk[..read]);
+    if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+      break end;
+    }
+    if buffer.len() > MAX_HEAD {
+      return Err("431 Request Header Fields Too Large");
+    }
+  };
+
+  let head = std::str::from_utf8(&buffer[..head_end]).map_err(|_| "400 Bad Request")?;
+  let mut lines = head.split("\r\n");
+  let method = lines.next().and_then(|line| line.split(' ').next()).ok_or("400 Bad Request")?.to_string();
+
+  let (mut host, mut origin, mut content_length) = (None, None, 0);
+  for line in lines {
+    let Some((name, value)) = line.split_once(':') else {
+      continue;
+    };
+    let value = value.trim();
+    match name.trim().to_ascii_lowercase().as_str() {
+      "host" => host = Some(value.to_string()),
+      "origin" => origin = Some(value.to_string()),
+      "content-length" => content_length = value.parse().map_err(|_| "400 Bad Request")?,
+      _ => {}
+    }
+  }
+  if content_length > MAX_BODY {
+    return Err("413 Payload Too Large");
+  }
+
+  let mut body = buffer.split_off(head_end + 4);
+  if body.len() < content_length {
+    let mut rest = vec![0u8; content_length - body.len()];
+    stream.read_exact(&mut rest).await.map_err(|_| "400 Bad Request")?;
+    body.extend_from_slice(&rest);
+  }
+  body.truncate(content_length);
+
+  Ok(HttpRequest { method, host, origin, body })
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, origin: Option<&str>, body: &str) -> std::io::Result<()> {
+  let mut response = format!(
+    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+    status,
+    body.len(),
+  );
+  if let Some(origin) = origin {
+    response.push_str(&format!(
+      "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Methods: POST, OPTIONS\r\nAccess-Control-Allow-Headers: content-type\r\nVary: Origin\r\n",
+      origin,
+    ));
+  }
+  response.push_str("\r\n");
+  response.push_str(body);
+
+  stream.write_all(response.as_bytes()).await?;
+  stream.shutdown().await
+}
+
+/// `origin` if the user connected it, the only pages allowed to read answers
+fn connected(origin: Option<&str>) -> Option<&str> {
+  origin.filter(|origin| is_connected(Some(origin)))
+}
+
+/// `localhost`, `127.0.0.1` or `[::1]`, with or without a port
+fn is_local_host(host: &str) -> bool {
+  let name = match host.strip_prefix('[') {
+    Some(rest) => rest.split(']').next().unwrap_or(""),
+    None => host.split(':').next().unwrap_or(""),
+  };
+  matches!(name, "localhost" | "127.0.0.1" | "::1")
+}
@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use log::{error, info, warn};
+use serde::Serialize;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+use forgekey_core::settings;
+use forgekey_core::utils::{control_socket_path, socket_dir};
+
+mod control;
+mod http;
+mod preview;
+mod rpc;
+
+pub use preview::Preview;
+
+// Local JSON-RPC signer. Serves `eth_accounts`, `personal_sign`,
+// `eth_signTypedData_v4` and `eth_signTransaction` for the unlocked
+// keystores over HTTP on localhost and a Unix socket, and asks the user to
+// approve every signature. Off unless the user turns it on.
+//
+// Web pages must be connected first: `eth_requestAccounts` asks the user, and
+// until then a page sees no accounts and can't ask for signatures. Local
+// programs send no `Origin` and need no connection.
+//
+// Separately, the control socket serves the `forgekey-cli` companion binary
+// whenever the app runs, see `start_control`.
+
+/// Port clef listens on, so tools already set up for it work unchanged
+pub const DEFAULT_PORT: u16 = 8550;
+
+/// A request that never gets an answer is rejected after this long
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A signature waiting for the user's approval
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignRequest {
+  pub id: u64,
+  pub method: String,
+  /// `Origin` of the web page asking, None for other local programs
+  pub origin: Option<String>,
+  /// None when connecting a web page
+  pub keystore_name: Option<String>,
+  pub address: Option<String>,
+  pub preview: Preview,
+}
+
+/// Where the signer listens, if it is running
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignerStatus {
+  pub running: bool,
+  /// e.g. "http://127.0.0.1:8550"
+  pub url: Option<String>,
+  pub socket_path: Option<PathBuf>,
+}
+
+/// Told about every request to approve, answered with `respond`
+pub type OnRequest = Arc<dyn Fn(SignRequest) + Send + Sync>;
+
+/// Told about every keystore locked through the control socket
+pub type OnLocked = Arc<dyn Fn(&str) + Send + Sync>;
+
+struct Server {
+  cancel: CancellationToken,
+  status: SignerStatus,
+}
+
+static SERVER: Mutex<Option<Server>> = Mutex::new(None);
+
+static PENDING: LazyLock<Mutex<HashMap<u64, oneshot::Sender<bool>>>> =
+  LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Set while a web page waits to be connected, pages are asked one at a time
+static CONNECTING: AtomicBool = AtomicBool::new(false);
+
+/// Web pages the user didn't connect, not asked again until the app restarts
+static REFUSED: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Start serving on `127.0.0.1:port` and, on Unix, `socket_path()`.
+/// Restarts the signer if it is already running.
+pub async fn start(port: u16, on_request: impl Fn(SignRequest) + Send + Sync + 'static) -> Result<SignerStatus, String> {
+  stop();
+
+  let cancel = CancellationToken::new();
+  let on_request: OnRequest = Arc::new(on_request);
+
+  let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await.map_err(|e| {
+    let err_msg = format!("Failed to listen on port {}: {}", port, e);
+    error!("{}", err_msg);
+    err_msg
+  })?;
+  let url = format!("http://{}", listener.local_addr().map_err(|e| e.to_string())?);
+  tokio::spawn(http::serve(listener, on_request.clone(), cancel.clone()));
+
+  #[cfg(unix)]
+  let socket = socket_path().ok_or_else(no_socket_dir).and_then(|path| Ok((bind_socket(&path)?, path)));
+  #[cfg(unix)]
+  let socket_path = match socket {
+    Ok((listener, path)) => {
+      tokio::spawn(rpc::serve_socket(listener, rpc::Api::Signer, on_request, cancel.clone()));
+      Some(path)
+    }
+    Err(e) => {
+      // HTTP alone is still useful
+      warn!("Signer socket unavailable: {}", e);
+      None
+    }
+  };
+  #[cfg(not(unix))]
+  let socket_path = None;
+
+  let status = SignerStatus { running: true, url: Some(url), socket_path };
+  info!("Signer listening on {:?}", status);
+  *SERVER.lock().unwrap() = Some(Server { cancel, status: status.clone() });
+  Ok(status)
+}
+
+/// Stop serving and reject every request still waiting for approval
+pub fn stop() {
+  let Some(server) = SERVER.lock().unwrap().take() else {
+    return;
+  };
+  server.cancel.cancel();
+  if let Some(path) = &server.status.socket_path {
+    let _ = fs::remove_file(path);
+  }
+  PENDING.lock().unwrap().clear();
+  info!("Signer stopped");
+}
+
+pub fn status() -> SignerStatus {
+  SERVER.lock().unwrap().as_ref().map(|server| server.status.clone()).unwrap_or_default()
+}
+
+/// Approve or reject the request with `id`
+pub fn respond(id: u64, approved: bool) -> Result<(), String> {
+  let sender = PENDING.lock().unwrap().remove(&id).ok_or_else(|| {
+    let err_msg = format!("Signing request {} is no longer pending", id);
+    error!("{}", err_msg);
+    err_msg
+  })?;
+  let _ = sender.send(approved);
+  Ok(())
+}
+
+/// Serve the `forgekey_*` methods on `control_socket_path()` until the app
+/// exits, for the `forgekey-cli` binary. Signing asks for approval through
+/// `on_request` like the signer does.
+#[cfg(unix)]
+pub async fn start_control(
+  on_request: impl Fn(SignRequest) + Send + Sync + 'static,
+  on_locked: impl Fn(&str) + Send + Sync + 'static,
+) -> Result<(), String> {
+  let path = control_socket_path().ok_or_else(no_socket_dir)?;
+  let listener = bind_socket(&path).map_err(|e| {
+    let err_msg = format!("Control socket unavailable: {}", e);
+    error!("{}", err_msg);
+    err_msg
+  })?;
+
+  info!("Control socket listening on {}", path.display());
+  let api = rpc::Api::Control { on_locked: Arc::new(on_locked) };
+  tokio::spawn(rpc::serve_socket(listener, api, Arc::new(on_request), CancellationToken::new()));
+  Ok(())
+}
+
+/// Unix socket next to the other per-user runtime files
+pub fn socket_path() -> Option<PathBuf> {
+  socket_dir().map(|dir| dir.join("signer.sock"))
+}
+
+fn no_socket_dir() -> String {
+  "Could not find a directory for the socket".to_string()
+}
+
+/// Whether the web page at `origin` may use the signer. Local programs,
+/// without an origin, always may.
+fn is_connected(origin: Option<&str>) -> bool {
+  origin.is_none_or(|origin| settings::get().signer_origins.iter().any(|connected| connected == origin))
+}
+
+/// Ask the user to connect the web page at `origin` and remember it if they
+/// do. A page that was turned down, or that asks while another one waits,
+/// is refused without asking.
+async fn connect(on_request: &OnRequest, origin: &str) -> Result<(), rpc::RpcError> {
+  if REFUSED.lock().unwrap().contains(origin) {
+    return Err(rpc::rejected());
+  }
+  if CONNECTING.swap(true, Ordering::SeqCst) {
+    return Err(rpc::RpcError::new(rpc::RESOURCE_UNAVAILABLE, "Another page is waiting to connect"));
+  }
+
+  let approved = approve(on_request, "eth_requestAccounts", Some(origin), None, None, Preview::Connect).await;
+  CONNECTING.store(false, Ordering::SeqCst);
+  if !approved {
+    REFUSED.lock().unwrap().insert(origin.to_string());
+    return Err(rpc::rejected());
+  }
+
+  info!("Connected {} to the signer", origin);
+  settings::update(|settings| settings.signer_origins.push(origin.to_string()))
+    .map_err(|e| rpc::RpcError::new(rpc::INTERNAL_ERROR, e))
+}
+
+/// Web pages connected to the signer
+pub fn connected_origins() -> Vec<String> {
+  settings::get().signer_origins
+}
+
+/// Forget the web page at `origin`, it has to connect again to use the signer
+pub fn disconnect(origin: &str) -> Result<(), String> {
+  settings::update(|settings| settings.signer_origins.retain(|connected| connected != origin))?;
+  REFUSED.lock().unwrap().remove(origin);
+  info!("Disconnected {} from the signer", origin);
+  Ok(())
+}
+
+/// Ask the user through `on_request` and wait for their answer. No answer in
+/// time, or the signer stopping, counts as a rejection.
+async fn approve(
+  on_request: &OnRequest,
+  method: &str,
+  origin: Option<&str>,
+  keystore_name: Option<String>,
+  address: Option<String>,
+  preview: Preview,
+) -> bool {
+  let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+  let (sender, receiver) = oneshot::channel();
+  PENDING.lock().unwrap().insert(id, sender);
+
+  on_request(SignRequest {
+    id,
+    method: method.to_string(),
+    origin: origin.map(str::to_string),
+    keystore_name,
+    address,
+    preview,
+  });
+
+  let approved = matches!(tokio::time::timeout(APPROVAL_TIMEOUT, receiver).await, Ok(Ok(true)));
+  PENDING.lock().unwrap().remove(&id);
+  approved
+}
+
+/// Listen on `path`, only accessible to the user
+#[cfg(unix)]
+fn bind_socket(path: &std::path::Path) -> Result<tokio::net::UnixListener, String> {
+  use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+  use std::os::unix::net::UnixStream;
+
+  if let Some(dir) = path.parent() {
+    fs::DirBuilder::new()
+      .recursive(true)
+      .mode(0o700)
+      .create(dir)
+      .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+  }
+
+  // A socket nobody answers on was left behind by an app that didn't shut
+  // down cleanly, one that answers belongs to another running instance
+  if UnixStream::connect(path).is_ok() {
+    return Err(format!("{} is in use by another ForgeKey", path.display()));
+  }
+  let _ = fs::remove_file(path);
+
+  let listener = tokio::net::UnixListener::bind(path).map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?;
+  fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+    .map_err(|e| format!("Failed to restrict {}: {}", path.display(), e))?;
+
+  Ok(listener)
+}
@@ -0,0 +1,116 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use forgekey_core::models::TransactionRequest;
+
+/// What a request would sign, decoded for the approval window
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Preview {
+  #[serde(rename_all = "camelCase")]
+  Message {
+    /// The message as text, if it is printable UTF-8
+    text: Option<String>,
+    hex: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  TypedData {
+    /// Which app and chain the signature is valid for
+    domain: Value,
+    primary_type: String,
+    message: Value,
+  },
+  #[serde(rename_all = "camelCase")]
+  Transaction {
+    /// None for a contract deployment
+    to: Option<String>,
+    /// Value in ether, e.g. "1.5"
+    value: String,
+    /// First four bytes of the calldata, naming the function called
+    selector: Option<String>,
+    data_len: usize,
+    chain_id: Option<String>,
+    nonce: Option<String>,
+    gas: Option<String>,
+    /// `maxFeePerGas` or `gasPrice`, in gwei
+    max_fee: Option<String>,
+  },
+  /// The keystore password itself, handed out once through a pipe for
+  /// `--password-file`
+  PasswordPipe,
+  /// A web page asking to see the unlocked accounts and request signatures
+  Connect,
+}
+
+/// Preview `personal_sign` data, hex encoded or plain text
+pub fn message(data: &str) -> Preview {
+  let bytes = data.strip_prefix("0x").and_then(decode_hex).unwrap_or_else(|| data.as_bytes().to_vec());
+  let text = String::from_utf8(bytes.clone())
+    .ok()
+    .filter(|text| text.chars().all(|c| !c.is_control() || c.is_whitespace()));
+
+  Preview::Message {
+    text,
+    hex: format!("0x{}", bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+  }
+}
+
+/// Preview EIP-712 typed data
+pub fn typed_data(typed_data: &Value) -> Result<Preview, String> {
+  let primary_type = typed_data
+    .get("primaryType")
+    .and_then(Value::as_str)
+    .ok_or_else(|| "Typed data has no primaryType".to_string())?;
+
+  Ok(Preview::TypedData {
+    domain: typed_data.get("domain").cloned().unwrap_or(Value::Null),
+    primary_type: primary_type.to_string(),
+    message: typed_data.get("message").cloned().unwrap_or(Value::Null),
+  })
+}
+
+/// Preview a transaction to sign
+pub fn transaction(tx: &TransactionRequest) -> Preview {
+  let data = tx.data.as_deref().and_then(|data| data.strip_prefix("0x")).and_then(decode_hex).unwrap_or_default();
+  let quantity = |value: &Option<String>| value.as_deref().and_then(parse_quantity).map(|value| value.to_string());
+
+  Preview::Transaction {
+    to: tx.to.clone(),
+    value: format_units(tx.value.as_deref().and_then(parse_quantity).unwrap_or(0), 18),
+    selector: (tx.to.is_some() && data.len() >= 4).then(|| format!("0x{}", data[..4].iter().map(|b| format!("{:02x}", b)).collect::<String>())),
+    data_len: data.len(),
+    chain_id: quantity(&tx.chain_id),
+    nonce: quantity(&tx.nonce),
+    gas: quantity(&tx.gas),
+    max_fee: tx.max_fee_per_gas.as_deref().or(tx.gas_price.as_deref()).and_then(parse_quantity).map(|fee| format_units(fee, 9)),
+  }
+}
+
+fn parse_quantity(value: &str) -> Option<u128> {
+  match value.strip_prefix("0x") {
+    Some(hex) => u128::from_str_radix(hex, 16).ok(),
+    None => value.parse().ok(),
+  }
+}
+
+/// `amount` divided by 10^`decimals`, without trailing zeros
+fn format_units(amount: u128, decimals: u32) -> String {
+  let unit = 10u128.pow(decimals);
+  let fraction = format!("{:0width$}", amount % unit, width = decimals as usize);
+  let fraction = fraction.trim_end_matches('0');
+  if fraction.is_empty() {
+    (amount / unit).to_string()
+  } else {
+    format!("{}.{}", amount / unit, fraction)
+  }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+  if !hex.len().is_multiple_of(2) {
+    return None;
+  }
+  (0..hex.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+    .collect()
+}
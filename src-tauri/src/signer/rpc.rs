@@ -0,0 +1,245 @@
+use log::{debug, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
+
+use forgekey_core::models::TransactionRequest;
+use forgekey_core::{cast, commands, session};
+use super::{approve, connect, control, is_connected, preview, OnLocked, OnRequest};
+
+// JSON-RPC 2.0 over whatever transport `http` and `serve_socket` provide.
+// Error codes follow EIP-1193 where one fits.
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+pub(super) const INTERNAL_ERROR: i64 = -32603;
+const USER_REJECTED: i64 = 4001;
+pub(super) const UNAUTHORIZED: i64 = 4100;
+/// A connection request is already waiting, as MetaMask answers
+pub(super) const RESOURCE_UNAVAILABLE: i64 = -32002;
+
+#[derive(Deserialize)]
+struct Request {
+  #[serde(default)]
+  id: Value,
+  method: String,
+  #[serde(default)]
+  params: Value,
+}
+
+/// Which methods a transport serves
+pub(super) enum Api {
+  /// The standard signing methods, see the module docs of `signer`
+  Signer,
+  /// `forgekey_*` methods for the companion CLI, on the control socket only
+  Control { on_locked: OnLocked },
+}
+
+pub(super) struct RpcError {
+  code: i64,
+  message: String,
+}
+
+impl RpcError {
+  pub(super) fn new(code: i64, message: impl Into<String>) -> Self {
+    Self { code, message: message.into() }
+  }
+}
+
+/// Answer a request body, a single call or a batch. `origin` is shown to
+/// the user when asking for approval.
+pub(super) async fn handle(api: &Api, body: &[u8], origin: Option<&str>, on_request: &OnRequest, cancel: &CancellationToken) -> Value {
+  let Ok(body) = serde_json::from_slice::<Value>(body) else {
+    return error_response(Value::Null, RpcError::new(PARSE_ERROR, "Parse error"));
+  };
+
+  match body {
+    Value::Array(calls) if !calls.is_empty() => {
+      let mut responses = Vec::with_capacity(calls.len());
+      for call in calls {
+        responses.push(handle_call(api, call, origin, on_request, cancel).await);
+      }
+      Value::Array(responses)
+    }
+    call => handle_call(api, call, origin, on_request, cancel).await,
+  }
+}
+
+async fn handle_call(api: &Api, call: Value, origin: Option<&str>, on_request: &OnRequest, cancel: &CancellationToken) -> Value {
+  let Ok(request) = serde_json::from_value::<Request>(call) else {
+    return error_response(Value::Null, RpcError::new(INVALID_REQUEST, "Invalid request"));
+  };
+  debug!("Signer call {} from {}", request.method, origin.unwrap_or("a local program"));
+
+  let params = &request.params;
+  let result = match (api, request.method.as_str()) {
+    (Api::Signer, "eth_accounts") => Ok(accounts(origin)),
+    (Api::Signer, "eth_requestAccounts") => request_accounts(origin, on_request).await,
+    (Api::Signer, _) if !is_connected(origin) => Err(RpcError::new(UNAUTHORIZED, "Call eth_requestAccounts to connect this page first")),
+    (Api::Signer, "personal_sign") => personal_sign(params, origin, on_request, cancel).await,
+    (Api::Signer, "eth_signTypedData_v4") => sign_typed_data(params, origin, on_request, cancel).await,
+    (Api::Signer, "eth_signTransaction") => sign_transaction(params, origin, on_request, cancel).await,
+    (Api::Control { .. }, "forgekey_list") => control::list().await,
+    (Api::Control { .. }, "forgekey_address") => control::address(params),
+    (Api::Control { .. }, "forgekey_sign") => control::sign(params, on_request, cancel).await,
+    (Api::Control { on_locked }, "forgekey_lock") => control::lock(params, on_locked),
+    (Api::Control { .. }, "forgekey_passwordPipe") => control::password_pipe(params, on_request).await,
+    (_, method) => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method {} is not supported", method))),
+  };
+
+  match result {
+    Ok(result) => json!({ "jsonrpc": "2.0", "id": request.id, "result": result }),
+    Err(e) => error_response(request.id, e),
+  }
+}
+
+/// The unlocked addresses, none for a web page that isn't connected
+fn accounts(origin: Option<&str>) -> Value {
+  if is_connected(origin) {
+    json!(session::unlocked_addresses())
+  } else {
+    json!([])
+  }
+}
+
+/// Connect the web page asking, once the user approved it, then answer like
+/// `eth_accounts`
+async fn request_accounts(origin: Option<&str>, on_request: &OnRequest) -> Result<Value, RpcError> {
+  if let Some(origin) = origin.filter(|origin| !is_connected(Some(origin))) {
+    connect(on_request, origin).await?;
+  }
+  Ok(accounts(origin))
+}
+
+/// params: [data, address]
+async fn personal_sign(params: &Value, origin: Option<&str>, on_request: &OnRequest, cancel: &CancellationToken) -> Result<Value, RpcError> {
+  let (data, address) = (string_param(params, 0)?, string_param(params, 1)?);
+  let keystore_name = keystore_for(address)?;
+
+  let preview = preview::message(data);
+  if !approve(on_request, "personal_sign", origin, Some(keystore_name.clone()), Some(address.to_string()), preview).await {
+    return Err(rejected());
+  }
+
+  commands::sign_message(&*cast::executor(), &keystore_name, data, cancel.child_token())
+    .await
+    .map(Value::String)
+    .map_err(|e| RpcError::new(INTERNAL_ERROR, e))
+}
+
+/// params: [address, typedData], the typed data as a JSON string or object
+async fn sign_typed_data(params: &Value, origin: Option<&str>, on_request: &OnRequest, cancel: &CancellationToken) -> Result<Value, RpcError> {
+  let address = string_param(params, 0)?;
+  let typed_data = match params.get(1) {
+    Some(Value::String(json)) => serde_json::from_str(json).map_err(|_| invalid_params("typed data is not valid JSON"))?,
+    Some(object @ Value::Object(_)) => object.clone(),
+    _ => return Err(invalid_params("expected [address, typedData]")),
+  };
+  let keystore_name = keystore_for(address)?;
+
+  let preview = preview::typed_data(&typed_data).map_err(|e| RpcError::new(INVALID_PARAMS, e))?;
+  if !approve(on_request, "eth_signTypedData_v4", origin, Some(keystore_name.clone()), Some(address.to_string()), preview).await {
+    return Err(rejected());
+  }
+
+  commands::sign_typed_data(&*cast::executor(), &keystore_name, &typed_data.to_string(), cancel.child_token())
+    .await
+    .map(Value::String)
+    .map_err(|e| RpcError::new(INTERNAL_ERROR, e))
+}
+
+/// params: [transaction], signed by its `from`
+async fn sign_transaction(params: &Value, origin: Option<&str>, on_request: &OnRequest, cancel: &CancellationToken) -> Result<Value, RpcError> {
+  let tx: TransactionRequest = params
+    .get(0)
+    .cloned()
+    .and_then(|tx| serde_json::from_value(tx).ok())
+    .ok_or_else(|| invalid_params("expected [transaction]"))?;
+  let address = tx.from.clone().ok_or_else(|| invalid_params("transaction has no from"))?;
+  let keystore_name = keystore_for(&address)?;
+
+  let preview = preview::transaction(&tx);
+  if !approve(on_request, "eth_signTransaction", origin, Some(keystore_name.clone()), Some(address), preview).await {
+    return Err(rejected());
+  }
+
+  commands::sign_transaction(&*cast::executor(), &keystore_name, &tx, cancel.child_token())
+    .await
+    .map(Value::String)
+    .map_err(|e| RpcError::new(INTERNAL_ERROR, e))
+}
+
+/// Only unlocked keystores can sign, the signer never asks for a password
+fn keystore_for(address: &str) -> Result<String, RpcError> {
+  session::unlocked_keystore_for(address)
+    .ok_or_else(|| RpcError::new(UNAUTHORIZED, format!("Account {} is not unlocked in ForgeKey", address)))
+}
+
+pub(super) fn string_param(params: &Value, index: usize) -> Result<&str, RpcError> {
+  params
+    .get(index)
+    .and_then(Value::as_str)
+    .ok_or_else(|| invalid_params(&format!("expected a string as parameter {}", index)))
+}
+
+pub(super) fn invalid_params(message: &str) -> RpcError {
+  RpcError::new(INVALID_PARAMS, format!("Invalid params: {}", message))
+}
+
+pub(super) fn rejected() -> RpcError {
+  RpcError::new(USER_REJECTED, "User rejected the request")
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+  if error.code != USER_REJECTED {
+    warn!("Signer error {}: {}", error.code, error.message);
+  }
+  json!({ "jsonrpc": "2.0", "id": id, "error": { "code": error.code, "message": error.message } })
+}
+
+/// Serve newline-delimited JSON-RPC on a Unix socket, the way Foundry and
+/// geth talk to an IPC endpoint. Only the user can open the socket.
+#[cfg(unix)]
+pub(super) async fn serve_socket(listener: tokio::net::UnixListener, api: Api, on_request: OnRequest, cancel: CancellationToken) {
+  use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+  let api = std::sync::Arc::new(api);
+  loop {
+    let stream = tokio::select! {
+      _ = cancel.cancelled() => return,
+      accepted = listener.accept() => match accepted {
+        Ok((stream, _)) => stream,
+        Err(e) => {
+          warn!("Signer socket accept failed: {}", e);
+          continue;
+        }
+      },
+    };
+
+    let (api, on_request, cancel) = (api.clone(), on_request.clone(), cancel.clone());
+    tokio::spawn(async move {
+      let (reader, mut writer) = stream.into_split();
+      let mut lines = BufReader::new(reader).lines();
+      loop {
+        let line = tokio::select! {
+          _ = cancel.cancelled() => return,
+          line = lines.next_line() => match line {
+            Ok(Some(line)) => line,
+            _ => return,
+          },
+        };
+        if line.trim().is_empty() {
+          continue;
+        }
+
+        let mut response = handle(&api, line.as_bytes(), None, &on_request, &cancel).await.to_string();
+        response.push('\n');
+        if writer.write_all(response.as_bytes()).await.is_err() {
+          return;
+        }
+      }
+    });
+  }
+}
@@ -27,6 +27,22 @@ tauri_panel! {
   })
 }
 
+/// Show the panel under the menu bar icon without a click, e.g. for a
+/// signature waiting for approval.
+pub fn show_panel(app: &AppHandle<Wry>) {
+  let Some(window) = app.get_webview_window("main") else {
+    return;
+  };
+  let _ = window.move_window(tauri_plugin_positioner::Position::TrayBottomCenter);
+
+  if let Ok(panel) = app.get_webview_panel("main") {
+    panel.show_and_make_key();
+  } else {
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+}
+
 /// Initialize the panel for fullscreen support.
 /// Must be called after plugin initialization.
 pub fn init_panel(app_handle: &AppHandle<Wry>) {
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use log::{debug, error};
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use tauri::{AppHandle, Emitter, Wry};
+
+use forgekey_core::utils::get_keystore_dir;
+
+/// Emitted with the keystore name when a keystore file appears in the directory.
+pub const KEYSTORE_ADDED_EVENT: &str = "keystore-added";
+/// Emitted with the keystore name when a keystore file is deleted or renamed away.
+pub const KEYSTORE_REMOVED_EVENT: &str = "keystore-removed";
+/// Emitted with the keystore name when an existing keystore file is rewritten.
+pub const KEYSTORE_MODIFIED_EVENT: &str = "keystore-modified";
+
+/// How long the directory has to be quiet before a batch of changes is emitted.
+/// `cast wallet import` writes the file in several steps, so we wait for it to settle.
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Global handle to the running watcher. Dropping it stops the watch thread.
+static KEYSTORE_WATCHER: Mutex<Option<Debouncer<RecommendedWatcher>>> = Mutex::new(None);
+
+type Snapshot = HashMap<String, Option<SystemTime>>;
+
+/// Start watching the Foundry keystore directory and emit an event for every
+/// keystore that is added, removed or modified outside of ForgeKey (e.g. by
+/// `cast wallet import` in a terminal).
+pub fn start_keystore_watcher(app: &AppHandle<Wry>) -> Result<(), String> {
+  let keystore_dir = get_keystore_dir()?;
+
+  // cast creates the directory lazily on first import, so make sure there is
+  // something to watch on a fresh install
+  fs::create_dir_all(&keystore_dir).map_err(|e| {
+    let err_msg = format!("Failed to create keystore directory: {}", e);
+    error!("{}", err_msg);
+    err_msg
+  })?;
+
+  let mut snapshot = snapshot_keystores(&keystore_dir);
+  let handle = app.clone();
+  let dir = keystore_dir.clone();
+
+  let mut debouncer = new_debouncer(DEBOUNCE_TIMEOUT, move |result: DebounceEventResult| {
+    if let Err(e) = result {
+      error!("Keystore watcher error: {}", e);
+      return;
+    }
+
+    // The debounced events only tell us that something changed, so diff the
+    // directory against the last snapshot to know what actually happened
+    let current = snapshot_keystores(&dir);
+    emit_changes(&handle, &snapshot, &current);
+    snapshot = current;
+  })
+  .map_err(|e| {
+    let err_msg = format!("Failed to create keystore watcher: {}", e);
+    error!("{}", err_msg);
+    err_msg
+  })?;
+
+  debouncer
+    .watcher()
+    .watch(&keystore_dir, RecursiveMode::NonRecursive)
+    .map_err(|e| {
+      let err_msg = format!("Failed to watch keystore directory: {}", e);
+      error!("{}", err_msg);
+      err_msg
+    })?;
+
+  let mut guard = KEYSTORE_WATCHER.lock().unwrap();
+  *guard = Some(debouncer);
+
+  debug!("Watching keystore directory {}", keystore_dir.display());
+  Ok(())
+}
+
+/// Read the keystore names in `dir` together with their modification time.
+fn snapshot_keystores(dir: &Path) -> Snapshot {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return Snapshot::new();
+  };
+
+  entries
+    .filter_map(|entry| entry.ok())
+    .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+    .filter_map(|entry| {
+      let name = entry.file_name().into_string().ok()?;
+      // Skip editor swap files, .DS_Store and the like
+      if name.starts_with('.') {
+        return None;
+      }
+      let modified = entry.metadata().and_then(|m| m.modified()).ok();
+      Some((name, modified))
+    })
+    .collect()
+}
+
+fn emit_changes(app: &AppHandle<Wry>, previous: &Snapshot, current: &Snapshot) {
+  for (name, modified) in current {
+    let event = match previous.get(name) {
+      None => KEYSTORE_ADDED_EVENT,
+      Some(previous_modified) if previous_modified != modified => KEYSTORE_MODIFIED_EVENT,
+      Some(_) => continue,
+    };
+    emit(app, event, name);
+  }
+
+  for name in previous.keys().filter(|name| !current.contains_key(*name)) {
+    emit(app, KEYSTORE_REMOVED_EVENT, name);
+  }
+}
+
+fn emit(app: &AppHandle<Wry>, event: &str, keystore_name: &str) {
+  debug!("{}: {}", event, keystore_name);
+  if let Err(e) = app.emit(event, keystore_name) {
+    error!("Failed to emit {} event: {}", event, e);
+  }
+}
@@ -1,7 +1,174 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { VanityOpts } from '@/types/address';
 import { ZeroizedString } from '@/lib/zeroized-string';
 
+export interface CastLocation {
+  path: string;
+  source: 'setting' | 'foundry-dir' | 'path' | 'default';
+}
+
+export type FoundryInstallStage =
+  | 'checking'
+  | 'missing'
+  | 'downloading'
+  | 'installing'
+  | 'done'
+  | 'failed';
+
+export interface FoundryInstallStatus {
+  stage: FoundryInstallStage;
+  /** Output line while installing, cast path when done, error when failed */
+  message: string | null;
+}
+
+export interface FoundryVersions {
+  cast: string | null;
+  forge: string | null;
+  castPath: string | null;
+  pinned: string | null;
+  /** Version rollbackFoundry would restore */
+  previous: string | null;
+  /** Set if cast is outside the supported range or differs from the pin */
+  warning: string | null;
+}
+
+export type SignerPreview =
+  | { kind: 'message'; text: string | null; hex: string }
+  | {
+      kind: 'typed-data';
+      domain: Record<string, unknown> | null;
+      primaryType: string;
+      message: unknown;
+    }
+  | {
+      kind: 'transaction';
+      to: string | null;
+      /** In ether */
+      value: string;
+      selector: string | null;
+      dataLen: number;
+      chainId: string | null;
+      nonce: string | null;
+      gas: string | null;
+      /** In gwei */
+      maxFee: string | null;
+    }
+  | { kind: 'password-pipe' }
+  | { kind: 'connect' };
+
+export interface SignerRequest {
+  id: number;
+  method: string;
+  /** Web page asking, null for other local programs */
+  origin: string | null;
+  /** Null when connecting a web page */
+  keystoreName: string | null;
+  address: string | null;
+  preview: SignerPreview;
+}
+
+export interface SignerStatus {
+  running: boolean;
+  url: string | null;
+  socketPath: string | null;
+}
+
+export interface CommandOutput {
+  /** Operation id passed to runForgeScript or castSend */
+  operationId: string | null;
+  line: string;
+}
+
+export interface ProjectAccount {
+  /** Where it is referenced, e.g. "Makefile:12" or "foundry.toml [profile.ci]" */
+  source: string;
+  /** Keystore named by --account or ETH_KEYSTORE_ACCOUNT */
+  account: string | null;
+  /** Address given by --sender, --from, ETH_FROM or sender */
+  sender: string | null;
+  keystore: string | null;
+  keystoreAddress: string | null;
+  /**
+   * missing: no keystore with that name. mismatch: the sender is not the
+   * keystore's address. unverified: the keystore is locked, the sender
+   * couldn't be checked. unknown: no keystore has the sender's address.
+   */
+  status: 'ok' | 'missing' | 'mismatch' | 'unverified' | 'unknown';
+}
+
+export interface LeakedKey {
+  /** Relative to the scanned directory */
+  path: string;
+  line: number;
+  /** Index of the secret among those on the same line */
+  position: number;
+  /** The line with the secret replaced by [REDACTED] */
+  excerpt: string;
+  variable: string | null;
+  kind: 'private-key' | 'mnemonic';
+  /** Of the key, or of the mnemonic's first account */
+  address: string | null;
+  /** Keystore that already holds the key */
+  keystore: string | null;
+}
+
+export interface LeakFix {
+  keystore: string;
+  address: string;
+  imported: boolean;
+  /** Whether the line now refers to the keystore instead of the key */
+  rewritten: boolean;
+}
+
+export type AccountFormat = 'geth' | 'ape' | 'brownie' | 'hardhat' | 'env';
+
+/**
+ * Whether a keystore already holds a candidate's address. "unknown" means no
+ * keystore with a known address does, but the locked ones still have to be
+ * unlocked to tell: cast doesn't store the address in keystores it imports.
+ */
+export type DuplicateStatus =
+  | { status: 'duplicate'; keystore: string }
+  | { status: 'new' }
+  | { status: 'unknown'; locked: string[] };
+
+export interface ImportCandidate {
+  format: AccountFormat;
+  /** Where it was found, e.g. "deployer.json" or ".env:3" */
+  source: string;
+  path: string;
+  /** Set for plain private keys, null for encrypted keystores */
+  line: number | null;
+  position: number;
+  address: string;
+  /** Encrypted keystores keep their password, plain keys need a new one */
+  encrypted: boolean;
+  suggestedName: string;
+  duplicate: DuplicateStatus;
+  nameTaken: boolean;
+}
+
+export interface ExportedKeystore {
+  keystore: string;
+  address: string;
+  /** The written UTC--<timestamp>--<address> file */
+  path: string;
+}
+
+export interface KeystoreChange {
+  kind: 'added' | 'removed' | 'modified';
+  keystoreName: string;
+}
+
+/**
+ * Message the backend returns when cast rejects a keystore password
+ */
+export const WRONG_PASSWORD_ERROR = 'Incorrect password';
+
+export const isWrongPasswordError = (error: unknown): boolean =>
+  error === WRONG_PASSWORD_ERROR;
+
 /**
  * Wallet API - Centralizes all Tauri invoke calls related to wallet functionality.
  * All methods that accept ZeroizedString will zeroize them in their finally blocks.
@@ -9,13 +176,15 @@ import { ZeroizedString } from '@/lib/zeroized-string';
 export const walletApi = {
   createNewWallet: async (
     addressLabel: string,
-    password: ZeroizedString
+    password: ZeroizedString,
+    operationId?: string
   ): Promise<string> => {
     try {
       return await password.use((securePassword) =>
         invoke<string>('create_new_wallet', {
           address_label: addressLabel,
           password: securePassword,
+          operation_id: operationId,
         })
       );
     } finally {
@@ -25,13 +194,15 @@ export const walletApi = {
 
   createVanityWallet: async (
     vanityOpts: Omit<VanityOpts, 'password'>,
-    password: ZeroizedString
+    password: ZeroizedString,
+    operationId?: string
   ): Promise<string> => {
     try {
       return await password.use((securePassword) =>
         invoke<string>('create_vanity_wallet', {
           ...vanityOpts,
           password: securePassword,
+          operation_id: operationId,
         })
       );
     } finally {
@@ -46,7 +217,8 @@ export const walletApi = {
   importPrivateKey: async (
     privateKey: ZeroizedString,
     addressLabel: string,
-    password: ZeroizedString
+    password: ZeroizedString,
+    operationId?: string
   ): Promise<string> => {
     try {
       return await privateKey.use((securePrivateKey) =>
@@ -55,6 +227,7 @@ export const walletApi = {
             private_key: securePrivateKey,
             address_label: addressLabel,
             password: securePassword,
+            operation_id: operationId,
           })
         )
       );
@@ -66,13 +239,15 @@ export const walletApi = {
 
   getWalletAddress: async (
     keystoreName: string,
-    password: ZeroizedString
+    password: ZeroizedString,
+    operationId?: string
   ): Promise<string> => {
     try {
       return await password.use((securePassword) =>
         invoke<string>('get_wallet_address', {
           keystore_name: keystoreName,
           password: securePassword,
+          operation_id: operationId,
         })
       );
     } finally {
@@ -86,13 +261,15 @@ export const walletApi = {
 
   decryptKeystore: async (
     keystoreName: string,
-    password: ZeroizedString
+    password: ZeroizedString,
+    operationId?: string
   ): Promise<string> => {
     try {
       return await password.use((securePassword) =>
         invoke<string>('decrypt_keystore', {
           keystore_name: keystoreName,
           password: securePassword,
+          operation_id: operationId,
         })
       );
     } finally {
@@ -100,7 +277,395 @@ export const walletApi = {
     }
   },
 
+  /**
+   * Unlocks a keystore for the auto-lock period so it can be used without
+   * re-entering the password. Resolves to the keystore address.
+   */
+  unlockKeystore: async (
+    keystoreName: string,
+    password: ZeroizedString,
+    operationId?: string
+  ): Promise<string> => {
+    try {
+      return await password.use((securePassword) =>
+        invoke<string>('unlock_keystore', {
+          keystore_name: keystoreName,
+          password: securePassword,
+          operation_id: operationId,
+        })
+      );
+    } finally {
+      password.zeroize();
+    }
+  },
+
+  /**
+   * Runs `forge script <script> --broadcast` in a Foundry project, signing with
+   * an unlocked keystore whose password is typed into forge's prompt by the
+   * backend. Output arrives through onCommandOutput. Resolves to the hashes of
+   * the transactions sent.
+   */
+  runForgeScript: async (
+    projectDir: string,
+    keystoreName: string,
+    script: string,
+    args: string[] = [],
+    operationId?: string
+  ): Promise<string[]> => {
+    return await invoke('run_forge_script', {
+      project_dir: projectDir,
+      keystore_name: keystoreName,
+      script,
+      args,
+      operation_id: operationId,
+    });
+  },
+
+  /**
+   * Runs `cast send --account <keystore> <args>` in a project directory, like
+   * runForgeScript. `args` holds the recipient, function and its arguments.
+   */
+  castSend: async (
+    projectDir: string,
+    keystoreName: string,
+    args: string[],
+    operationId?: string
+  ): Promise<string[]> => {
+    return await invoke('cast_send', {
+      project_dir: projectDir,
+      keystore_name: keystoreName,
+      args,
+      operation_id: operationId,
+    });
+  },
+
+  /**
+   * Finds the accounts a Foundry project refers to in foundry.toml, .env
+   * files, Makefiles and justfiles, and matches them with the keystores.
+   */
+  detectProjectAccounts: async (projectDir: string): Promise<ProjectAccount[]> => {
+    return await invoke('detect_project_accounts', { project_dir: projectDir });
+  },
+
+  /**
+   * Looks for private keys and mnemonics left in plain text in a directory,
+   * skipping files git ignores except .env files.
+   */
+  scanForPlaintextKeys: async (
+    dir: string,
+    operationId?: string
+  ): Promise<LeakedKey[]> => {
+    return await invoke('scan_for_plaintext_keys', {
+      dir,
+      operation_id: operationId,
+    });
+  },
+
+  /**
+   * Imports a private key or mnemonic found by scanForPlaintextKeys as a
+   * keystore, or reuses the keystore that has it, and replaces the line with
+   * `ETH_KEYSTORE_ACCOUNT=<keystore>` or `--account <keystore>`.
+   */
+  importLeakedKey: async (
+    dir: string,
+    leak: Pick<LeakedKey, 'path' | 'line' | 'position'>,
+    keystoreName: string,
+    password: ZeroizedString,
+    operationId?: string
+  ): Promise<LeakFix> => {
+    try {
+      return await password.use((securePassword) =>
+        invoke<LeakFix>('import_leaked_key', {
+          dir,
+          path: leak.path,
+          line: leak.line,
+          position: leak.position,
+          keystore_name: keystoreName,
+          password: securePassword,
+          operation_id: operationId,
+        })
+      );
+    } finally {
+      password.zeroize();
+    }
+  },
+
+  /**
+   * Lists the accounts another tool has, to pick the ones to import. geth,
+   * ape and brownie default to their usual directory, Hardhat and .env need
+   * the project directory.
+   */
+  previewAccountImport: async (
+    format: AccountFormat,
+    path?: string,
+    operationId?: string
+  ): Promise<ImportCandidate[]> => {
+    return await invoke('preview_account_import', {
+      format,
+      path,
+      operation_id: operationId,
+    });
+  },
+
+  /**
+   * Imports a candidate from previewAccountImport as a keystore. Encrypted
+   * keystores are copied and keep their password, plain private keys are
+   * encrypted with the given one.
+   */
+  importAccount: async (
+    candidate: Pick<ImportCandidate, 'path' | 'line' | 'position'>,
+    keystoreName: string,
+    password?: ZeroizedString,
+    operationId?: string
+  ): Promise<string> => {
+    const request = (securePassword?: string) =>
+      invoke<string>('import_account', {
+        path: candidate.path,
+        line: candidate.line,
+        position: candidate.position,
+        keystore_name: keystoreName,
+        password: securePassword,
+        operation_id: operationId,
+      });
+
+    if (!password) {
+      return await request();
+    }
+    try {
+      return await password.use((securePassword) => request(securePassword));
+    } finally {
+      password.zeroize();
+    }
+  },
+
+  /**
+   * Writes keystores to a directory as geth UTC--<timestamp>--<address>
+   * files, with ethers' x-ethers metadata if asked. The encrypted keys are
+   * copied as they are unless a new password is given, which re-encrypts
+   * them and needs the keystores to be unlocked.
+   */
+  exportKeystores: async (
+    keystoreNames: string[],
+    targetDir: string,
+    options: { ethers?: boolean; newPassword?: ZeroizedString } = {},
+    operationId?: string
+  ): Promise<ExportedKeystore[]> => {
+    const { ethers = false, newPassword } = options;
+    const request = (securePassword?: string) =>
+      invoke<ExportedKeystore[]>('export_keystores', {
+        keystore_names: keystoreNames,
+        target_dir: targetDir,
+        ethers,
+        new_password: securePassword,
+        operation_id: operationId,
+      });
+
+    if (!newPassword) {
+      return await request();
+    }
+    try {
+      return await newPassword.use((securePassword) => request(securePassword));
+    } finally {
+      newPassword.zeroize();
+    }
+  },
+
+  /**
+   * Creates a named pipe that hands the password of an unlocked keystore to
+   * the first program reading it, then disappears. Resolves to its path, for
+   * `--password-file` in a command the user runs.
+   */
+  createPasswordPipe: async (keystoreName: string): Promise<string> => {
+    return await invoke('create_password_pipe', { keystore_name: keystoreName });
+  },
+
+  /**
+   * Subscribes to the output of runForgeScript and castSend, line by line.
+   */
+  onCommandOutput: async (
+    callback: (output: CommandOutput) => void
+  ): Promise<UnlistenFn> => {
+    return listen<CommandOutput>('command-output', (event) =>
+      callback(event.payload)
+    );
+  },
+
+  /**
+   * Cancels a running operation that was started with the same operation id,
+   * killing the underlying cast process.
+   */
+  cancelOperation: async (operationId: string): Promise<void> => {
+    await invoke('cancel_operation', { operation_id: operationId });
+  },
+
+  getCastVersion: async (): Promise<string> => {
+    return await invoke('get_cast_version');
+  },
+
+  getCastLocation: async (): Promise<CastLocation> => {
+    return await invoke('get_cast_location');
+  },
+
+  /**
+   * Use the cast binary at `path`, or pass null to find it automatically again
+   */
+  setCastPath: async (path: string | null): Promise<CastLocation> => {
+    return await invoke('set_cast_path', { path });
+  },
+
+  /**
+   * Looks for Foundry again. The result arrives through onFoundryInstallStatus.
+   */
+  checkFoundry: async (): Promise<void> => {
+    await invoke('check_foundry');
+  },
+
+  /**
+   * Installs Foundry with foundryup. Only call this after the user agreed to it.
+   * Progress arrives through onFoundryInstallStatus.
+   */
+  installFoundry: async (): Promise<void> => {
+    await invoke('install_foundry');
+  },
+
+  /**
+   * Installs cast from a Foundry release archive on disk, without network access.
+   * The archive must match the SHA-256 checksum published with the release.
+   */
+  installFoundryFromArchive: async (
+    archivePath: string,
+    sha256: string
+  ): Promise<CastLocation> => {
+    return await invoke('install_foundry_from_archive', {
+      archive_path: archivePath,
+      sha256,
+    });
+  },
+
+  /**
+   * Versions of cast and forge in use, plus the pinned and previous versions.
+   */
+  getFoundryVersions: async (): Promise<FoundryVersions> => {
+    return await invoke('get_foundry_versions');
+  },
+
+  /**
+   * Installs a Foundry release ("1.2.3") or channel ("stable", "nightly"),
+   * keeping the current binaries for rollbackFoundry. Progress arrives
+   * through onFoundryInstallStatus.
+   */
+  updateFoundry: async (target: string): Promise<void> => {
+    await invoke('update_foundry', { target });
+  },
+
+  /**
+   * Restores the Foundry binaries replaced by the last updateFoundry.
+   */
+  rollbackFoundry: async (): Promise<void> => {
+    await invoke('rollback_foundry');
+  },
+
+  /**
+   * Describes the environment (OS, cast, keystores, logs) as Markdown for a bug
+   * report. Home directory, keystore names and anything key-shaped are redacted.
+   */
+  getDiagnostics: async (): Promise<string> => {
+    return await invoke('diagnostics');
+  },
+
+  onFoundryInstallStatus: async (
+    callback: (status: FoundryInstallStatus) => void
+  ): Promise<UnlistenFn> => {
+    return listen<FoundryInstallStatus>('foundry-install-status', (event) =>
+      callback(event.payload)
+    );
+  },
+
+  /**
+   * Starts the local JSON-RPC signer for the unlocked keystores, and keeps it
+   * on across restarts. Each signature still needs approval.
+   */
+  startSigner: async (port?: number): Promise<SignerStatus> => {
+    return await invoke('start_signer', { port: port ?? null });
+  },
+
+  stopSigner: async (): Promise<void> => {
+    await invoke('stop_signer');
+  },
+
+  getSignerStatus: async (): Promise<SignerStatus> => {
+    return await invoke('get_signer_status');
+  },
+
+  respondSignerRequest: async (id: number, approved: boolean): Promise<void> => {
+    await invoke('respond_signer_request', { id, approved });
+  },
+
+  /**
+   * Web pages connected to the signer through eth_requestAccounts.
+   */
+  getSignerOrigins: async (): Promise<string[]> => {
+    return await invoke('get_signer_origins');
+  },
+
+  disconnectSignerOrigin: async (origin: string): Promise<void> => {
+    await invoke('disconnect_signer_origin', { origin });
+  },
+
+  /**
+   * Subscribes to signatures waiting for the user's approval.
+   */
+  onSignerRequest: async (
+    callback: (request: SignerRequest) => void
+  ): Promise<UnlistenFn> => {
+    return listen<SignerRequest>('signer-request', (event) =>
+      callback(event.payload)
+    );
+  },
+
+  lockKeystore: async (keystoreName: string): Promise<void> => {
+    await invoke('lock_keystore', { keystore_name: keystoreName });
+  },
+
+  lockAll: async (): Promise<void> => {
+    await invoke('lock_all');
+  },
+
+  listUnlockedKeystores: async (): Promise<string[]> => {
+    return invoke<string[]>('list_unlocked_keystores');
+  },
+
+  setAutoLockTimeout: async (seconds: number): Promise<void> => {
+    await invoke('set_auto_lock_timeout', { seconds });
+  },
+
+  /**
+   * Subscribes to keystores being locked by the backend (timeout or system sleep).
+   */
+  onKeystoreLocked: async (
+    callback: (keystoreName: string) => void
+  ): Promise<UnlistenFn> => {
+    return listen<string>('keystore-locked', (event) => callback(event.payload));
+  },
+
   listWallets: async (): Promise<string[]> => {
     return invoke<string[]>('list_wallets');
   },
+
+  /**
+   * Subscribes to keystore files being added, removed or modified on disk
+   * (e.g. by `cast wallet import` in a terminal). Resolves to an unlisten function.
+   */
+  onKeystoresChanged: async (
+    callback: (change: KeystoreChange) => void
+  ): Promise<UnlistenFn> => {
+    const unlisteners = await Promise.all(
+      (['added', 'removed', 'modified'] as const).map((kind) =>
+        listen<string>(`keystore-${kind}`, (event) =>
+          callback({ kind, keystoreName: event.payload })
+        )
+      )
+    );
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  },
 };
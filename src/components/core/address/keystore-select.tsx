@@ -3,6 +3,7 @@ import { Button } from '@/components/ui/button';
 import { ScrollArea } from '@/components/ui/scroll-area';
 import { AnimatedPage } from '@/components/layout/animated-page';
 import { ChevronRight } from 'lucide-react';
+import { walletApi } from '@/api/wallet-api';
 
 interface KeystoreSelectProps {
   onKeystoreSelect: (keystoreName: string) => void;
@@ -38,6 +39,21 @@ export function KeystoreSelect({
     loadKeystores();
   }, [loadAvailableKeystores]);
 
+  // Refresh the list when keystores are created or deleted outside the app
+  useEffect(() => {
+    const unlisten = walletApi.onKeystoresChanged(async (change) => {
+      if (change.kind === 'modified') return;
+      try {
+        setAvailableKeystores(await loadAvailableKeystores());
+      } catch (err) {
+        console.error('Failed to reload keystores:', err);
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadAvailableKeystores]);
+
   // Filter out keystores that are already imported
   const filteredKeystores = useMemo(
     () => availableKeystores.filter((keystore) => !existingAddresses.includes(keystore)),
@@ -29,6 +29,7 @@ export function VanityAddressForm({
 }: VanityAddressFormProps) {
   const isGeneratingVanity = useWalletStore((state) => state.isGeneratingVanity);
   const setIsGeneratingVanity = useWalletStore((state) => state.setIsGeneratingVanity);
+  const operationId = useWalletStore((state) => state.addAddressOperationId);
 
   const isPasswordValid = newAddress.password && !newAddress.password.isZeroized()
     ? validatePassword(newAddress.password.getValue()).isValid
@@ -53,7 +54,9 @@ export function VanityAddressForm({
 
   const handleCancel = async () => {
     try {
-      await walletApi.cancelVanityWallet();
+      if (operationId) {
+        await walletApi.cancelOperation(operationId);
+      }
     } catch (e) {
       console.error('Failed to cancel vanity generation:', e);
     }
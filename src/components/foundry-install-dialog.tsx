@@ -0,0 +1,127 @@
+import { useState } from 'react';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { FormField, FormLabel } from '@/components/ui/form-field';
+import { useFoundryInstall } from '@/hooks/use-foundry-install';
+
+export function FoundryInstallDialog() {
+  const {
+    stage,
+    progress,
+    error,
+    dismissed,
+    install,
+    installFromArchive,
+    dismiss,
+  } = useFoundryInstall();
+  const [isOffline, setIsOffline] = useState(false);
+  const [archivePath, setArchivePath] = useState('');
+  const [sha256, setSha256] = useState('');
+
+  const isInstalling = stage === 'downloading' || stage === 'installing';
+  const isOpen =
+    !dismissed && (stage === 'missing' || stage === 'failed' || isInstalling);
+
+  const handleInstall = () => {
+    if (isOffline) {
+      installFromArchive(archivePath.trim(), sha256.trim());
+    } else {
+      install();
+    }
+  };
+
+  return (
+    <Dialog open={isOpen} onOpenChange={(open) => !open && dismiss()}>
+      <DialogContent className="max-w-xs" hideCloseButton>
+        <DialogHeader className="space-y-1">
+          <DialogTitle className="text-center text-sm">
+            {isInstalling ? 'Installing Foundry...' : 'Foundry Required'}
+          </DialogTitle>
+          <DialogDescription className="text-center text-xs">
+            {isInstalling ? (
+              progress ??
+              (stage === 'downloading'
+                ? 'Downloading foundryup...'
+                : 'Running foundryup...')
+            ) : isOffline ? (
+              <>
+                Install cast from a Foundry release archive on this machine. It
+                must match the SHA-256 checksum published with the release.
+              </>
+            ) : (
+              <>
+                ForgeKey manages keys through Foundry&apos;s cast, which was not
+                found. Install it with foundryup from foundry.paradigm.xyz?
+              </>
+            )}
+          </DialogDescription>
+        </DialogHeader>
+
+        {isOffline && !isInstalling && (
+          <div className="space-y-2">
+            <FormField>
+              <FormLabel>Archive Path</FormLabel>
+              <Input
+                placeholder="/path/to/foundry_v1.2.3_darwin_arm64.tar.gz"
+                value={archivePath}
+                onChange={(e: React.ChangeEvent<HTMLInputElement>) =>
+                  setArchivePath(e.target.value)
+                }
+              />
+            </FormField>
+            <FormField>
+              <FormLabel>SHA-256</FormLabel>
+              <Input
+                placeholder="e.g. 7a75e6c3..."
+                value={sha256}
+                onChange={(e: React.ChangeEvent<HTMLInputElement>) =>
+                  setSha256(e.target.value)
+                }
+              />
+            </FormField>
+          </div>
+        )}
+
+        {error && (
+          <p className="text-xs text-red-400 text-center break-words">{error}</p>
+        )}
+
+        {!isInstalling && (
+          <>
+            <DialogFooter className="flex flex-row gap-2 mt-2">
+              <Button
+                variant="outline"
+                className="flex-1 h-8 text-xs border border-purple-500/50 bg-transparent text-purple-300 hover:bg-purple-500/10 hover:border-purple-500 transition-all"
+                onClick={dismiss}
+              >
+                Not Now
+              </Button>
+              <Button
+                className="flex-1 h-8 text-xs"
+                onClick={handleInstall}
+                disabled={isOffline && (!archivePath.trim() || !sha256.trim())}
+              >
+                {stage === 'failed' ? 'Retry' : 'Install'}
+              </Button>
+            </DialogFooter>
+            <button
+              type="button"
+              className="text-xs text-gray-500 hover:text-gray-300 transition-colors"
+              onClick={() => setIsOffline(!isOffline)}
+            >
+              {isOffline ? 'Install online instead' : 'Install from a file instead'}
+            </button>
+          </>
+        )}
+      </DialogContent>
+    </Dialog>
+  );
+}
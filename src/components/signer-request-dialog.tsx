@@ -0,0 +1,134 @@
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { Button } from '@/components/ui/button';
+import { SignerPreview } from '@/api/wallet-api';
+import { useSignerRequests } from '@/hooks/use-signer-requests';
+
+const TITLES: Record<SignerPreview['kind'], string> = {
+  message: 'Sign Message',
+  'typed-data': 'Sign Typed Data',
+  transaction: 'Sign Transaction',
+  'password-pipe': 'Share Password',
+  connect: 'Connect Site',
+};
+
+function PreviewRow({ label, value }: { label: string; value: string }) {
+  return (
+    <div className="flex justify-between gap-2">
+      <span className="text-gray-500">{label}</span>
+      <span className="text-right break-all">{value}</span>
+    </div>
+  );
+}
+
+function PreviewDetails({ preview }: { preview: SignerPreview }) {
+  switch (preview.kind) {
+    case 'message':
+      return (
+        <pre className="whitespace-pre-wrap break-all">
+          {preview.text ?? preview.hex}
+        </pre>
+      );
+    case 'typed-data':
+      return (
+        <>
+          <PreviewRow label="Type" value={preview.primaryType} />
+          <pre className="whitespace-pre-wrap break-all">
+            {JSON.stringify(
+              { domain: preview.domain, message: preview.message },
+              null,
+              2
+            )}
+          </pre>
+        </>
+      );
+    case 'transaction':
+      return (
+        <>
+          <PreviewRow label="To" value={preview.to ?? 'New contract'} />
+          <PreviewRow label="Value" value={`${preview.value} ETH`} />
+          {preview.selector && (
+            <PreviewRow label="Function" value={preview.selector} />
+          )}
+          <PreviewRow label="Data" value={`${preview.dataLen} bytes`} />
+          <PreviewRow label="Chain" value={preview.chainId ?? '?'} />
+          <PreviewRow label="Nonce" value={preview.nonce ?? '?'} />
+          <PreviewRow label="Gas" value={preview.gas ?? '?'} />
+          {preview.maxFee && (
+            <PreviewRow label="Max fee" value={`${preview.maxFee} gwei`} />
+          )}
+        </>
+      );
+    case 'password-pipe':
+      return (
+        <p>
+          A local program wants the keystore password, to read once through a
+          pipe only your user can open. It can then sign anything with this
+          keystore.
+        </p>
+      );
+    case 'connect':
+      return (
+        <p>
+          The site will see the addresses of your unlocked keystores and can
+          ask you to sign with them. Every signature still needs your approval.
+        </p>
+      );
+  }
+}
+
+export function SignerRequestDialog() {
+  const { current, pendingCount, respond } = useSignerRequests();
+
+  return (
+    <Dialog
+      open={current !== null}
+      onOpenChange={(open) => !open && current && respond(current.id, false)}
+    >
+      <DialogContent className="max-w-xs" hideCloseButton>
+        {current && (
+          <>
+            <DialogHeader className="space-y-1">
+              <DialogTitle className="text-center text-sm">
+                {TITLES[current.preview.kind]}
+              </DialogTitle>
+              <DialogDescription className="text-center text-xs break-all">
+                {current.origin ?? 'A local program'}{' '}
+                {current.keystoreName
+                  ? `asks to sign with ${current.keystoreName}`
+                  : 'asks to connect'}
+                {pendingCount > 1 && ` (${pendingCount - 1} more waiting)`}
+              </DialogDescription>
+            </DialogHeader>
+
+            <div className="max-h-48 overflow-y-auto rounded-md bg-white/5 p-2 text-xs font-mono space-y-1">
+              <PreviewDetails preview={current.preview} />
+            </div>
+
+            <DialogFooter className="flex flex-row gap-2 mt-2">
+              <Button
+                variant="outline"
+                className="flex-1 h-8 text-xs border border-purple-500/50 bg-transparent text-purple-300 hover:bg-purple-500/10 hover:border-purple-500 transition-all"
+                onClick={() => respond(current.id, false)}
+              >
+                Reject
+              </Button>
+              <Button
+                className="flex-1 h-8 text-xs"
+                onClick={() => respond(current.id, true)}
+              >
+                {current.preview.kind === 'connect' ? 'Connect' : 'Sign'}
+              </Button>
+            </DialogFooter>
+          </>
+        )}
+      </DialogContent>
+    </Dialog>
+  );
+}
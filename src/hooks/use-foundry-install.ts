@@ -0,0 +1,79 @@
+import { useState, useEffect, useCallback } from 'react';
+import { FoundryInstallStage, walletApi } from '@/api/wallet-api';
+
+interface FoundryInstallState {
+  stage: FoundryInstallStage | null;
+  /** Last line printed by the installer */
+  progress: string | null;
+  error: string | null;
+  dismissed: boolean;
+}
+
+/**
+ * Tracks whether Foundry is installed and installs it once the user agrees.
+ */
+export function useFoundryInstall() {
+  const [state, setState] = useState<FoundryInstallState>({
+    stage: null,
+    progress: null,
+    error: null,
+    dismissed: false,
+  });
+
+  useEffect(() => {
+    const unlisten = walletApi.onFoundryInstallStatus(({ stage, message }) => {
+      setState((prev) => ({
+        ...prev,
+        stage,
+        progress:
+          stage === 'downloading' || stage === 'installing' ? message : null,
+        error: stage === 'failed' ? message : null,
+      }));
+    });
+
+    // The startup check may have finished before we started listening
+    walletApi.checkFoundry().catch((error) => {
+      console.error('Failed to check for Foundry:', error);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Also used to retry after a failure
+  const install = useCallback(async () => {
+    setState((prev) => ({ ...prev, stage: 'downloading', error: null }));
+    try {
+      await walletApi.installFoundry();
+    } catch (error) {
+      console.error('Failed to start Foundry installation:', error);
+      setState((prev) => ({ ...prev, stage: 'failed', error: String(error) }));
+    }
+  }, []);
+
+  const installFromArchive = useCallback(
+    async (archivePath: string, sha256: string) => {
+      setState((prev) => ({ ...prev, stage: 'installing', error: null }));
+      try {
+        await walletApi.installFoundryFromArchive(archivePath, sha256);
+        setState((prev) => ({ ...prev, stage: 'done', progress: null }));
+      } catch (error) {
+        console.error('Failed to install Foundry from archive:', error);
+        setState((prev) => ({ ...prev, stage: 'failed', error: String(error) }));
+      }
+    },
+    []
+  );
+
+  const dismiss = useCallback(() => {
+    setState((prev) => ({ ...prev, dismissed: true }));
+  }, []);
+
+  return {
+    ...state,
+    install,
+    installFromArchive,
+    dismiss,
+  };
+}
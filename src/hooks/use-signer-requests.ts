@@ -0,0 +1,35 @@
+import { useState, useEffect, useCallback } from 'react';
+import { SignerRequest, walletApi } from '@/api/wallet-api';
+
+/**
+ * Queues signatures the local signer asks the user to approve, oldest first.
+ */
+export function useSignerRequests() {
+  const [requests, setRequests] = useState<SignerRequest[]>([]);
+
+  useEffect(() => {
+    const unlisten = walletApi.onSignerRequest((request) => {
+      setRequests((prev) => [...prev, request]);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const respond = useCallback(async (id: number, approved: boolean) => {
+    setRequests((prev) => prev.filter((request) => request.id !== id));
+    try {
+      await walletApi.respondSignerRequest(id, approved);
+    } catch (error) {
+      // Timed out or the signer stopped, the request was rejected already
+      console.error('Failed to answer signing request:', error);
+    }
+  }, []);
+
+  return {
+    current: requests[0] ?? null,
+    pendingCount: requests.length,
+    respond,
+  };
+}
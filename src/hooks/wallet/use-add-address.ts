@@ -12,6 +12,7 @@ export function useAddAddress() {
   const vanityOptions = useWalletStore((state) => state.vanityOptions);
   const setSelectedKeystore = useWalletStore((state) => state.setSelectedKeystore);
   const setIsGeneratingVanity = useWalletStore((state) => state.setIsGeneratingVanity);
+  const setAddAddressOperationId = useWalletStore((state) => state.setAddAddressOperationId);
   const addAddress = useWalletStore((state) => state.addAddress);
   const resetAddressForm = useWalletStore((state) => state.resetAddressForm);
 
@@ -20,13 +21,18 @@ export function useAddAddress() {
       return false;
     }
 
+    // Lets the UI stop cast while it runs, with walletApi.cancelOperation
+    const operationId = crypto.randomUUID();
+    setAddAddressOperationId(operationId);
+
     let resolvedAddress: string;
     try {
       switch (addAddressStep) {
         case 'new':
           resolvedAddress = await walletApi.createNewWallet(
             newAddress.label,
-            newAddress.password
+            newAddress.password,
+            operationId
           );
           break;
 
@@ -41,7 +47,8 @@ export function useAddAddress() {
           try {
             resolvedAddress = await walletApi.createVanityWallet(
               vanityOpts,
-              newAddress.password
+              newAddress.password,
+              operationId
             );
           } catch (error) {
             const msg = error instanceof Error ? error.message : String(error);
@@ -61,7 +68,8 @@ export function useAddAddress() {
           resolvedAddress = await walletApi.importPrivateKey(
             newAddress.privateKey,
             newAddress.label,
-            newAddress.password
+            newAddress.password,
+            operationId
           );
           break;
 
@@ -88,6 +96,8 @@ export function useAddAddress() {
     } catch (error) {
       console.error('Error adding address:', error);
       return false;
+    } finally {
+      setAddAddressOperationId(null);
     }
   };
 
@@ -1,5 +1,5 @@
 import { Address } from '@/types/address';
-import { walletApi } from '@/api/wallet-api';
+import { isWrongPasswordError, walletApi } from '@/api/wallet-api';
 import { ZeroizedString } from '@/lib/zeroized-string';
 import { useZeroize } from '@/contexts/zeroize-context';
 import { useWalletStore } from '@/stores/wallet-store';
@@ -55,7 +55,9 @@ export function usePrivateKey() {
       setPrivateKeyError('');
     } catch (error) {
       console.error('Error getting private key:', error);
-      setPrivateKeyError('Invalid password');
+      setPrivateKeyError(
+        isWrongPasswordError(error) ? 'Invalid password' : String(error)
+      );
     }
   };
 
@@ -2,6 +2,7 @@ import { useEffect, useRef } from 'react';
 import { Keystore } from '@/types/address';
 import { useWalletSync } from './use-wallet-sync';
 import { useWalletStore } from '@/stores/wallet-store';
+import { walletApi } from '@/api/wallet-api';
 import { useNavigation } from '@/hooks/router/use-navigation';
 import { ROUTES } from '@/router/types';
 
@@ -49,6 +50,19 @@ export function useWalletReconciliation() {
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, []);
 
+  // Drop addresses whose keystore file was deleted outside the app
+  useEffect(() => {
+    const unlisten = walletApi.onKeystoresChanged(async (change) => {
+      if (change.kind !== 'removed') return;
+      const current = useWalletStore.getState().keystores;
+      setKeystores(await reconcileWallets(current));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, []);
+
   // Persist keystores to localStorage whenever they change
   useEffect(() => {
     const currentStorage = JSON.parse(
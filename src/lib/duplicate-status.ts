@@ -0,0 +1,27 @@
+import { DuplicateStatus } from '@/api/wallet-api';
+
+export interface DuplicateStatusLabel {
+  label: string;
+  /** importAccount refuses duplicates and accounts it can't check */
+  canImport: boolean;
+}
+
+/**
+ * Describes whether an import candidate is already a keystore, never
+ * presenting an unchecked account as new.
+ */
+export function describeDuplicateStatus(
+  duplicate: DuplicateStatus
+): DuplicateStatusLabel {
+  switch (duplicate.status) {
+    case 'duplicate':
+      return { label: `Already in ${duplicate.keystore}`, canImport: false };
+    case 'new':
+      return { label: 'Not imported yet', canImport: true };
+    case 'unknown':
+      return {
+        label: `Unlock ${duplicate.locked.join(', ')} to check for duplicates`,
+        canImport: false,
+      };
+  }
+}
@@ -3,12 +3,16 @@ import '@/styles/globals.css';
 import type { AppProps } from 'next/app';
 import { ZeroizeProvider } from '@/contexts/zeroize-context';
 import { UpdateDialog } from '@/components/update-dialog';
+import { FoundryInstallDialog } from '@/components/foundry-install-dialog';
+import { SignerRequestDialog } from '@/components/signer-request-dialog';
 
 export default function App({ Component, pageProps }: AppProps) {
   return (
     <ZeroizeProvider>
       <Component {...pageProps} />
       <UpdateDialog />
+      <FoundryInstallDialog />
+      <SignerRequestDialog />
     </ZeroizeProvider>
   );
 }
@@ -25,6 +25,8 @@ export interface WalletStore {
   newAddress: Address;
   vanityOptions: VanityOpts;
   isGeneratingVanity: boolean;
+  /** Operation id of the running create/import, to cancel it */
+  addAddressOperationId: string | null;
   isAddingGroup: boolean;
   newGroupName: string;
   isPasswordDialogOpen: boolean;
@@ -46,6 +48,7 @@ export interface WalletStore {
     value: VanityOpts | ((prev: VanityOpts) => VanityOpts)
   ) => void;
   setIsGeneratingVanity: (value: boolean) => void;
+  setAddAddressOperationId: (value: string | null) => void;
   setIsAddingGroup: (value: boolean) => void;
   setNewGroupName: (value: string) => void;
   setSelectedAddressForPrivateKey: (
@@ -83,6 +86,7 @@ const initialState = {
     address_label: '',
   },
   isGeneratingVanity: false,
+  addAddressOperationId: null,
   isAddingGroup: false,
   newGroupName: '',
   isPasswordDialogOpen: false,
@@ -163,6 +167,9 @@ export const useWalletStore = create<WalletStore>()(
         setIsGeneratingVanity: (value) =>
           set({ isGeneratingVanity: value }, false, 'setIsGeneratingVanity'),
 
+        setAddAddressOperationId: (value) =>
+          set({ addAddressOperationId: value }, false, 'setAddAddressOperationId'),
+
         setIsAddingGroup: (value) =>
           set({ isAddingGroup: value }, false, 'setIsAddingGroup'),
 